@@ -17,7 +17,10 @@ impl<'kdl> Name<'kdl> {
     pub fn span(&self) -> Span {
         match self.entry.kind {
             EntryKind::Node(_) => Span::from(self.entry.span.name..self.entry.span.end_ann), // (ty)name
-            EntryKind::Attr(_) => Span::from(self.entry.span.name..self.entry.span.ty - 1), // name=(ty)
+            EntryKind::Attr(_) => {
+                Span::from(self.entry.span.name..self.entry.span.ty.saturating_sub(1))
+                // name=(ty)
+            }
         }
     }
 }
@@ -1,7 +1,8 @@
 use {
     super::{details::*, Name, Ty, Value},
     crate::Span,
-    core::fmt,
+    alloc::vec::Vec,
+    core::{fmt, slice},
 };
 
 #[repr(transparent)]
@@ -19,6 +20,13 @@ impl<'kdl> Attr<'kdl> {
         Span::from(self.entry.span.name..self.entry.span.end)
     }
 
+    /// Whether this attribute is commented out with `/-`.
+    ///
+    /// See [`Document::from_str_with_disabled`](super::Document::from_str_with_disabled).
+    pub fn is_disabled(&self) -> bool {
+        self.entry.disabled
+    }
+
     pub fn name(&self) -> Option<&Name<'kdl>> {
         if self.entry.name.is_some() {
             Some(Name::ref_cast(&self.entry))
@@ -153,24 +161,32 @@ impl fmt::Debug for Argument<'_> {
 #[derive(Clone, Default)]
 pub struct AttrIter<'a, 'kdl> {
     pub(super) entries: &'a [Entry<'kdl>],
+    /// Whether to include disabled attributes.
+    pub(super) disabled: bool,
 }
 
 impl<'a, 'kdl> Iterator for AttrIter<'a, 'kdl> {
     type Item = &'a Attr<'kdl>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.entries.first().map(|entry| {
-            self.entries = &self.entries[1..];
-            Attr::ref_cast(entry)
-        })
+        while let Some((entry, rest)) = self.entries.split_first() {
+            self.entries = rest;
+            if self.disabled || !entry.disabled {
+                return Some(Attr::ref_cast(entry));
+            }
+        }
+        None
     }
 }
 
 impl<'a, 'kdl> DoubleEndedIterator for AttrIter<'a, 'kdl> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.entries.last().map(|entry| {
-            self.entries = &self.entries[..self.entries.len() - 1];
-            Attr::ref_cast(entry)
-        })
+        while let Some((entry, rest)) = self.entries.split_last() {
+            self.entries = rest;
+            if self.disabled || !entry.disabled {
+                return Some(Attr::ref_cast(entry));
+            }
+        }
+        None
     }
 }
 
@@ -179,3 +195,58 @@ impl fmt::Debug for AttrIter<'_, '_> {
         f.debug_list().entries(self.clone()).finish()
     }
 }
+
+/// A view of a node's properties with repeated keys deduplicated.
+///
+/// Created by [`Node::property_map`](super::Node::property_map).
+#[derive(Clone, Default)]
+pub struct PropertyMap<'a, 'kdl> {
+    pub(super) properties: Vec<&'a Property<'kdl>>,
+}
+
+impl<'a, 'kdl> PropertyMap<'a, 'kdl> {
+    pub fn get(&self, name: &str) -> Option<&'a Property<'kdl>> {
+        self.properties
+            .iter()
+            .copied()
+            .find(|property| &**property.name() == name)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    pub fn iter(&self) -> impl '_ + DoubleEndedIterator<Item = &'a Property<'kdl>> {
+        self.properties.iter().copied()
+    }
+}
+
+impl<'a, 'kdl> IntoIterator for PropertyMap<'a, 'kdl> {
+    type Item = &'a Property<'kdl>;
+    type IntoIter = alloc::vec::IntoIter<&'a Property<'kdl>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.properties.into_iter()
+    }
+}
+
+impl<'b, 'a, 'kdl> IntoIterator for &'b PropertyMap<'a, 'kdl> {
+    type Item = &'a Property<'kdl>;
+    type IntoIter = core::iter::Copied<slice::Iter<'b, &'a Property<'kdl>>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.properties.iter().copied()
+    }
+}
+
+impl fmt::Debug for PropertyMap<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -1,15 +1,29 @@
 use {
-    crate::{ast::details::*, visit, ParseError},
-    alloc::vec::Vec,
+    crate::{
+        ast::{details::*, Comment},
+        parse::NEWLINE_CHARS,
+        visit, ParseError,
+    },
+    alloc::{boxed::Box, vec::Vec},
     core::num::NonZeroU32,
 };
 
 pub(super) struct CollectAst<'a, 'kdl> {
     source: &'kdl str,
-    entries: Option<&'a mut Vec<Entry<'kdl>>>,
-    errors: Option<&'a mut Vec<ParseError>>,
+    entries: &'a mut Vec<Entry<'kdl>>,
+    errors: &'a mut Vec<ParseError>,
     start: usize,
     pos: usize,
+    /// Whether to keep slashdashed components, as disabled entries.
+    keep_disabled: bool,
+    /// Whether the entries being created are slashdashed.
+    slashdash: bool,
+    /// Comments in a block which may lead the next node.
+    comments: Vec<Comment<'kdl>>,
+    /// A comment after the last entry of a node.
+    trailing: Option<Comment<'kdl>>,
+    /// The parent's head entry and position, updated when this is dropped.
+    parent: Option<(usize, &'a mut usize)>,
 }
 
 impl<'a, 'kdl> CollectAst<'a, 'kdl> {
@@ -17,33 +31,52 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
         source: &'kdl str,
         entries: &'a mut Vec<Entry<'kdl>>,
         errors: &'a mut Vec<ParseError>,
+        keep_disabled: bool,
     ) -> Self {
         let mut this = CollectAst {
             source,
-            entries: Some(entries),
-            errors: Some(errors),
+            entries,
+            errors,
             start: 0,
             pos: 0,
+            keep_disabled,
+            slashdash: false,
+            comments: Vec::new(),
+            trailing: None,
+            parent: None,
         };
         this.entries().push(Entry {
             span: EntrySpan::at(0),
             name: None,
             ty: None,
             kind: EntryKind::Node(NodeMeta::default()),
+            disabled: false,
+            comments: None,
         });
         this
     }
 
     fn entries(&mut self) -> &mut Vec<Entry<'kdl>> {
         self.entries
-            .as_mut()
-            .expect("KDL visitor should not be visited while visiting children")
     }
 
     fn errors(&mut self) -> &mut Vec<ParseError> {
         self.errors
-            .as_mut()
-            .expect("KDL visitor should not be visited while visiting children")
+    }
+
+    fn child(&mut self, start: usize) -> CollectAst<'_, 'kdl> {
+        CollectAst {
+            source: self.source,
+            entries: self.entries,
+            errors: self.errors,
+            start,
+            pos: self.pos,
+            keep_disabled: self.keep_disabled,
+            slashdash: false,
+            comments: Vec::new(),
+            trailing: None,
+            parent: Some((self.start, &mut self.pos)),
+        }
     }
 
     fn head(&mut self) -> &mut Entry<'kdl> {
@@ -56,14 +89,65 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
         self.head().span.end = self.pos;
     }
 
+    fn do_node_comment(&mut self, comment: visit::Comment<'kdl>) {
+        if self.trailing.is_none() && !starts_line(self.source, comment.span().start) {
+            self.trailing = Some(Comment::new(comment));
+        }
+        self.do_trivia(comment.source());
+    }
+
+    fn do_children_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.do_trivia(comment.source());
+        let start = comment.span().start;
+        let last_child = self.head().kind.unwrap_node().last_child;
+        if let Some(last) = last_child.filter(|_| !starts_line(self.source, start)) {
+            let ix = self.start + last.get() as usize;
+            let source = self.source;
+            let sibling = &mut self.entries()[ix];
+            let between = &source[sibling.span.end..start];
+            if between.trim().is_empty() && !between.contains(NEWLINE_CHARS) {
+                let comments = sibling.comments.get_or_insert_with(Default::default);
+                if comments.trailing.is_none() {
+                    comments.trailing = Some(Comment::new(comment));
+                    return;
+                }
+            }
+        }
+        self.comments.push(Comment::new(comment));
+    }
+
+    /// Take the pending comments which lead a node starting at `pos`.
+    fn take_leading(&mut self, pos: usize) -> Vec<Comment<'kdl>> {
+        let mut first = self.comments.len();
+        let mut next = pos;
+        while let Some(comment) = first.checked_sub(1).map(|ix| &self.comments[ix]) {
+            let between = &self.source[comment.span().end..next];
+            if !between.trim().is_empty() || count_newlines(between) > 1 {
+                break;
+            }
+            first -= 1;
+            next = comment.span().start;
+        }
+        while first < self.comments.len()
+            && !starts_line(self.source, self.comments[first].span().start)
+        {
+            first += 1;
+        }
+        let leading = self.comments.split_off(first);
+        self.comments.clear();
+        leading
+    }
+
     fn do_error(&mut self, error: ParseError) {
         self.errors().push(error);
     }
 
-    fn do_node(&mut self) -> Self {
+    fn do_node(&mut self) -> CollectAst<'_, 'kdl> {
         let ix = self.entries().len();
         let here = self.start;
         let pos = self.pos;
+        let disabled = self.slashdash;
+        let leading = self.take_leading(pos);
         let meta = self.head().kind.unwrap_node_mut();
         let prev_child = meta.last_child;
         meta.last_child = NonZeroU32::new((ix - here).try_into().unwrap());
@@ -85,20 +169,23 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
                 prev_sibling,
                 ..NodeMeta::default()
             }),
+            disabled,
+            comments: if leading.is_empty() {
+                None
+            } else {
+                Some(Box::new(Comments {
+                    leading,
+                    trailing: None,
+                }))
+            },
         });
-        Self {
-            source: self.source,
-            entries: self.entries.take(),
-            errors: self.errors.take(),
-            start: ix,
-            pos,
-        }
+        self.child(ix)
     }
 
     fn do_type(&mut self, v: visit::Identifier<'kdl>) {
         let pos = self.pos - 1;
         let entry = self.head();
-        debug_assert!(matches!(entry.ty, None));
+        debug_assert!(entry.ty.is_none());
         entry.ty = Some(v.value().into());
         entry.span.ty = pos;
         entry.span.end_ann = pos + v.source().len() + 2;
@@ -109,7 +196,7 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
     fn do_name(&mut self, v: visit::Identifier<'kdl>) {
         let pos = self.pos;
         let entry = self.head();
-        debug_assert!(matches!(entry.name, None));
+        debug_assert!(entry.name.is_none());
         entry.name = Some(v.value().into());
         entry.span.name = pos;
         entry.span.end_ann = pos + v.source().len();
@@ -117,9 +204,11 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
         self.head().span.end = self.pos;
     }
 
-    fn do_attr(&mut self) -> Self {
+    fn do_attr(&mut self) -> CollectAst<'_, 'kdl> {
         let ix = self.entries().len();
         let pos = self.pos;
+        let disabled = self.slashdash;
+        self.trailing = None;
         let meta = self.head().kind.unwrap_node_mut();
         debug_assert_eq!(meta.first_child, None);
         debug_assert_eq!(meta.last_child, None);
@@ -130,24 +219,24 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
             name: None,
             ty: None,
             kind: EntryKind::Attr(AttrValue::Null),
+            disabled,
+            comments: None,
         });
-        Self {
-            source: self.source,
-            entries: self.entries.take(),
-            errors: self.errors.take(),
-            start: ix,
-            pos,
-        }
+        self.child(ix)
     }
 
-    fn do_children(&mut self) -> Self {
-        Self {
-            source: self.source,
-            entries: self.entries.take(),
-            errors: self.errors.take(),
-            start: self.start,
-            pos: self.pos,
-        }
+    fn do_children(&mut self) -> CollectAst<'_, 'kdl> {
+        self.trailing = None;
+        self.child(self.start)
+    }
+
+    fn do_slashdash(&mut self) -> bool {
+        self.slashdash = self.keep_disabled;
+        self.slashdash
+    }
+
+    fn finish_slashdash(&mut self) {
+        self.slashdash = false;
     }
 
     fn do_value(&mut self, v: visit::Value<'kdl>) {
@@ -165,16 +254,38 @@ impl<'a, 'kdl> CollectAst<'a, 'kdl> {
         };
         self.head().span.end = self.pos;
     }
+}
 
-    fn do_finish(&mut self, mut v: Self) {
-        self.entries = v.entries.take();
-        self.errors = v.errors.take();
-        self.pos = v.pos;
-        self.head().span.end = self.pos;
+fn starts_line(source: &str, pos: usize) -> bool {
+    let line = source[..pos].rsplit(NEWLINE_CHARS).next();
+    line.map_or(true, |line| line.trim().is_empty())
+}
+
+fn count_newlines(s: &str) -> usize {
+    s.matches(NEWLINE_CHARS).count() - s.matches("\r\n").count()
+}
+
+/// Finishes the node, as the lending traits have no `finish_*` methods.
+///
+/// This also runs when parsing stops at an error, or unwinds from a panic;
+/// the unfinished nodes then end where parsing stopped, and the parse errors
+/// explain what is missing.
+impl Drop for CollectAst<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(comment) = self.trailing.take() {
+            let comments = self.entries[self.start]
+                .comments
+                .get_or_insert_with(Default::default);
+            comments.trailing.get_or_insert(comment);
+        }
+        if let Some((start, pos)) = &mut self.parent {
+            **pos = self.pos;
+            self.entries[*start].span.end = self.pos;
+        }
     }
 }
 
-impl<'kdl> visit::Document<'kdl> for CollectAst<'_, 'kdl> {
+impl<'kdl> visit::LendDocument<'kdl> for CollectAst<'_, 'kdl> {
     type Output = ();
 
     fn finish(self) -> Self::Output {}
@@ -182,21 +293,36 @@ impl<'kdl> visit::Document<'kdl> for CollectAst<'_, 'kdl> {
         debug_assert_eq!(self.errors().last(), Some(&error));
         Ok(())
     }
+
+    fn split_trivia(&self) -> bool {
+        true
+    }
 }
 
-impl<'kdl> visit::Children<'kdl> for CollectAst<'_, 'kdl> {
-    type VisitNode = Self;
+impl<'kdl> visit::LendChildren<'kdl> for CollectAst<'_, 'kdl> {
+    type VisitNode<'a>
+        = CollectAst<'a, 'kdl>
+    where
+        Self: 'a;
 
     fn visit_trivia(&mut self, trivia: &'kdl str) {
         self.do_trivia(trivia);
     }
 
-    fn visit_node(&mut self) -> Self::VisitNode {
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.do_children_comment(comment);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
         self.do_node()
     }
 
-    fn finish_node(&mut self, v: Self::VisitNode) {
-        self.do_finish(v)
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.do_slashdash()
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.finish_slashdash();
     }
 
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
@@ -205,15 +331,28 @@ impl<'kdl> visit::Children<'kdl> for CollectAst<'_, 'kdl> {
     }
 }
 
-impl<'kdl> visit::Node<'kdl> for CollectAst<'_, 'kdl> {
-    type VisitArgument = Self;
-    type VisitProperty = Self;
-    type VisitChildren = Self;
+impl<'kdl> visit::LendNode<'kdl> for CollectAst<'_, 'kdl> {
+    type VisitArgument<'a>
+        = CollectAst<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = CollectAst<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = CollectAst<'a, 'kdl>
+    where
+        Self: 'a;
 
     fn visit_trivia(&mut self, trivia: &'kdl str) {
         self.do_trivia(trivia);
     }
 
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.do_node_comment(comment);
+    }
+
     fn visit_type(&mut self, v: visit::Identifier<'kdl>) {
         self.do_type(v);
     }
@@ -222,28 +361,24 @@ impl<'kdl> visit::Node<'kdl> for CollectAst<'_, 'kdl> {
         self.do_name(v);
     }
 
-    fn visit_argument(&mut self) -> Self::VisitArgument {
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
         self.do_attr()
     }
 
-    fn finish_argument(&mut self, v: Self::VisitArgument) {
-        self.do_finish(v)
-    }
-
-    fn visit_property(&mut self) -> Self::VisitProperty {
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
         self.do_attr()
     }
 
-    fn finish_property(&mut self, v: Self::VisitProperty) {
-        self.do_finish(v)
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.do_children()
     }
 
-    fn visit_children(&mut self) -> Self::VisitChildren {
-        self.do_children()
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.do_slashdash()
     }
 
-    fn finish_children(&mut self, v: Self::VisitChildren) {
-        self.do_finish(v)
+    fn finish_slashdash_entry(&mut self) {
+        self.finish_slashdash();
     }
 
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
@@ -0,0 +1,85 @@
+use {
+    super::details::*,
+    crate::{visit, Span},
+    core::fmt,
+};
+
+/// A comment attached to a [`Node`](super::Node).
+///
+/// Comments are attached by position:
+///
+/// - The *leading comments* of a node are the comments directly before it in
+///   the same block, with no blank line between them or between the last of
+///   them and the node. The first of them must start its line; comments that
+///   follow other code on the same line, like a node's `{`, are not leading
+///   comments.
+/// - The *trailing comment* of a node is the first comment on the line where
+///   the node ends: after its last entry, after its closing `}`, or after its
+///   terminating `;`. Slashdashed components are skipped over like comments.
+///
+/// Other comments, such as those between a node's entries, those separated
+/// from the next node by a blank line, or those at the end of a block, are
+/// not attached to any node.
+///
+/// ```kdl
+/// // leading, with the next line
+/// /* leading */
+/// node 1 // trailing
+///
+/// // not attached
+///
+/// a; b // trailing comment of b
+/// ```
+pub struct Comment<'kdl> {
+    source: StringValue<'kdl>,
+    span: Span,
+}
+
+impl<'kdl> Comment<'kdl> {
+    pub(super) fn new(comment: visit::Comment<'kdl>) -> Self {
+        Comment {
+            source: comment.source().into(),
+            span: comment.span(),
+        }
+    }
+
+    pub(super) fn into_owned(self) -> Comment<'static> {
+        Comment {
+            source: self.source.into_owned(),
+            span: self.span,
+        }
+    }
+
+    fn visit(&self) -> visit::Comment<'_> {
+        visit::Comment {
+            source: &self.source,
+            span: self.span,
+        }
+    }
+
+    /// The comment, including its delimiters.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn kind(&self) -> visit::CommentKind {
+        self.visit().kind()
+    }
+
+    /// The text of the comment, without the comment delimiters.
+    pub fn body(&self) -> &str {
+        self.visit().body()
+    }
+}
+
+impl fmt::Debug for Comment<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Span { start, end } = self.span;
+        let s = self.source();
+        write!(f, "{s:?}:{start}..{end}")
+    }
+}
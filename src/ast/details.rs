@@ -1,8 +1,10 @@
 use core::ptr;
 
 use {
-    alloc::{borrow::Cow, boxed::Box, string::String},
-    core::{marker::PhantomData, num::NonZeroU32, ops::Deref, ptr::NonNull, str},
+    alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec},
+    core::{
+        marker::PhantomData, mem::ManuallyDrop, num::NonZeroU32, ops::Deref, ptr::NonNull, str,
+    },
     rust_decimal::Decimal,
 };
 
@@ -11,6 +13,16 @@ pub(super) struct Entry<'a> {
     pub(super) name: Option<StringValue<'a>>,
     pub(super) ty: Option<StringValue<'a>>,
     pub(super) kind: EntryKind<'a>,
+    /// Commented out with `/-`; see `Document::from_str_with_disabled`.
+    pub(super) disabled: bool,
+    /// Only for nodes; see [`Comment`](super::Comment).
+    pub(super) comments: Option<Box<Comments<'a>>>,
+}
+
+#[derive(Default)]
+pub(super) struct Comments<'a> {
+    pub(super) leading: Vec<super::Comment<'a>>,
+    pub(super) trailing: Option<super::Comment<'a>>,
 }
 
 pub(super) enum EntryKind<'a> {
@@ -18,6 +30,31 @@ pub(super) enum EntryKind<'a> {
     Attr(AttrValue<'a>),
 }
 
+impl Entry<'_> {
+    pub(super) fn into_owned(self) -> Entry<'static> {
+        Entry {
+            span: self.span,
+            name: self.name.map(StringValue::into_owned),
+            ty: self.ty.map(StringValue::into_owned),
+            kind: match self.kind {
+                EntryKind::Node(meta) => EntryKind::Node(meta),
+                EntryKind::Attr(attr) => EntryKind::Attr(attr.into_owned()),
+            },
+            disabled: self.disabled,
+            comments: self.comments.map(|comments| {
+                Box::new(Comments {
+                    leading: comments
+                        .leading
+                        .into_iter()
+                        .map(|c| c.into_owned())
+                        .collect(),
+                    trailing: comments.trailing.map(|c| c.into_owned()),
+                })
+            }),
+        }
+    }
+}
+
 impl<'a> EntryKind<'a> {
     pub(super) fn is_node(&self) -> bool {
         match self {
@@ -103,12 +140,33 @@ pub(super) enum AttrValue<'a> {
     Null,
 }
 
+impl AttrValue<'_> {
+    fn into_owned(self) -> AttrValue<'static> {
+        match self {
+            AttrValue::String(s) => AttrValue::String(s.into_owned()),
+            AttrValue::Exact(d) => AttrValue::Exact(d),
+            AttrValue::Inexact(n) => AttrValue::Inexact(n),
+            AttrValue::True => AttrValue::True,
+            AttrValue::False => AttrValue::False,
+            AttrValue::Null => AttrValue::Null,
+        }
+    }
+}
+
 pub(super) struct StringValue<'a> {
     ptr: NonNull<u8>,
     len: usize,
     _lt: PhantomData<&'a str>,
 }
 
+// SAFETY: StringValue is semantically either `&'a str` (when `OWNED_BIT` is
+// clear) or a uniquely owned `Box<str>` (when it is set), both of which are
+// Send and Sync. The string is never mutated, and an owned allocation is only
+// freed by `drop`, which takes `&mut self`, so no other reference can observe
+// it being freed.
+unsafe impl Send for StringValue<'_> {}
+unsafe impl Sync for StringValue<'_> {}
+
 const OWNED_BIT: usize = 1 << 63;
 
 impl<'a> From<&'a str> for StringValue<'a> {
@@ -152,11 +210,25 @@ impl StringValue<'_> {
     fn ptr(&self) -> *mut str {
         ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len & !OWNED_BIT) as *mut str
     }
+
+    pub(super) fn into_owned(self) -> StringValue<'static> {
+        if self.owned() {
+            let this = ManuallyDrop::new(self);
+            StringValue {
+                ptr: this.ptr,
+                len: this.len,
+                _lt: PhantomData,
+            }
+        } else {
+            StringValue::from(Box::<str>::from(&*self))
+        }
+    }
 }
 
 impl Drop for StringValue<'_> {
     fn drop(&mut self) {
         if self.owned() {
+            // SAFETY: owned values come from `Box::into_raw` with this length.
             unsafe { drop(Box::from_raw(self.ptr())) };
         }
     }
@@ -165,10 +237,18 @@ impl Drop for StringValue<'_> {
 impl Deref for StringValue<'_> {
     type Target = str;
     fn deref(&self) -> &str {
+        // SAFETY: the pointer and length come from a `&'a str` which outlives
+        // self, or from a `Box<str>` which self owns.
         unsafe { &*self.ptr() }
     }
 }
 
+#[test]
+fn thread_safe() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Entry<'_>>();
+}
+
 #[test]
 #[cfg(target_pointer_width = "64")]
 fn feature() {
@@ -1,8 +1,13 @@
 use {
-    super::{collect::CollectAst, details::*, Node, NodeIter},
+    super::{
+        collect::CollectAst, details::*, ChildrenMut, DocumentIndex, Node, NodeIter, NodeMut,
+        Replay,
+    },
+    crate::visit,
     crate::ParseErrors,
-    alloc::vec::Vec,
-    core::fmt,
+    alloc::borrow::Cow,
+    alloc::{sync::Arc, vec::Vec},
+    core::{fmt, str::FromStr},
 };
 
 pub struct Document<'kdl> {
@@ -10,34 +15,209 @@ pub struct Document<'kdl> {
 }
 
 impl<'kdl> Document<'kdl> {
+    /// Create an empty document, to be filled in with [`push_node`].
+    ///
+    /// [`push_node`]: Document::push_node
+    pub fn new() -> Self {
+        Self {
+            entries: vec![Entry {
+                span: EntrySpan::at(0),
+                name: None,
+                ty: None,
+                kind: EntryKind::Node(NodeMeta::default()),
+                disabled: false,
+                comments: None,
+            }],
+        }
+    }
+
     #[allow(clippy::should_implement_trait)] // refinement
     pub fn from_str(kdl: &'kdl str) -> Result<Self, ParseErrors<&'kdl str>> {
+        Self::parse(kdl, false)
+    }
+
+    /// Parse a document, keeping nodes and entries commented out with `/-`
+    /// as disabled.
+    ///
+    /// Disabled nodes and entries are skipped by [`nodes`], [`Node::children`],
+    /// [`Node::attrs`], and everything built on them, as if they were not
+    /// there. They can be seen with [`nodes_with_disabled`] and friends, and
+    /// re-enabled with [`NodeMut::set_disabled`]. Commented-out children
+    /// blocks are not kept.
+    ///
+    /// [`nodes`]: Document::nodes
+    /// [`nodes_with_disabled`]: Document::nodes_with_disabled
+    pub fn from_str_with_disabled(kdl: &'kdl str) -> Result<Self, ParseErrors<&'kdl str>> {
+        Self::parse(kdl, true)
+    }
+
+    fn parse(kdl: &'kdl str, keep_disabled: bool) -> Result<Self, ParseErrors<&'kdl str>> {
         let mut entries = Vec::new();
         let mut errors = Vec::new();
-        let visitor = CollectAst::new(kdl, &mut entries, &mut errors);
+        let visitor = CollectAst::new(kdl, &mut entries, &mut errors, keep_disabled);
         crate::visit_kdl_string(kdl, visitor).expect("visiting should not fail");
         if errors.is_empty() {
             Ok(Self { entries })
         } else {
+            let paths = crate::error::error_paths(kdl, &errors);
             Err(ParseErrors {
                 source: kdl,
                 errors,
+                paths,
             })
         }
     }
 
+    /// Copy any strings borrowed from the source, so that the document no
+    /// longer borrows it.
+    pub fn into_owned(self) -> Document<'static> {
+        Document {
+            entries: self.entries.into_iter().map(Entry::into_owned).collect(),
+        }
+    }
+
     pub fn nodes(&self) -> NodeIter<'_, 'kdl> {
-        let dummy_node = Node::ref_cast(&*self.entries);
+        let dummy_node = Node::ref_cast(&self.entries);
         dummy_node.children()
     }
+
+    /// Like [`nodes`](Document::nodes), but including disabled nodes.
+    pub fn nodes_with_disabled(&self) -> NodeIter<'_, 'kdl> {
+        let dummy_node = Node::ref_cast(&self.entries);
+        dummy_node.children_with_disabled()
+    }
+
+    pub fn nodes_mut(&mut self) -> ChildrenMut<'_, 'kdl> {
+        ChildrenMut::new(&mut self.entries, 0)
+    }
+
+    /// Add a new top-level node after all existing nodes.
+    pub fn push_node(&mut self, name: impl Into<Cow<'kdl, str>>) -> NodeMut<'_, 'kdl> {
+        ChildrenMut::new(&mut self.entries, 0).into_push(name)
+    }
+
+    /// Get the first top-level node with the given name.
+    pub fn node(&self, name: &str) -> Option<&Node<'kdl>> {
+        self.nodes().find(|node| &**node.name() == name)
+    }
+
+    /// Iterate over the top-level nodes with the given name, in source order.
+    pub fn nodes_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl 'a + DoubleEndedIterator<Item = &'a Node<'kdl>> {
+        self.nodes().filter(move |node| &**node.name() == name)
+    }
+
+    /// Prepare to replay the document into a [`visit::LendDocument`].
+    ///
+    /// Non-finite numbers are replayed as `null`; see [`Replay`] for details.
+    pub fn replay(&self) -> Replay<'_, 'kdl> {
+        Replay::new(self)
+    }
+
+    /// Replay the document into a visitor which works with any source
+    /// lifetime.
+    ///
+    /// Use [`replay`](Document::replay) for visitors which borrow from
+    /// the source.
+    pub fn accept<V, O>(&self, visitor: V) -> O
+    where
+        V: for<'r> visit::LendDocument<'r, Output = O>,
+    {
+        self.replay().accept(visitor)
+    }
+
+    /// Build an index of every node in the document by name.
+    ///
+    /// See [`DocumentIndex`] for details.
+    pub fn index(&self) -> DocumentIndex<'_, 'kdl> {
+        DocumentIndex::new(self)
+    }
 }
 
-// impl FromStr for Document<'static> {
-//     type Err = ParseErrors;
-//     fn from_str(s: &str) -> Result<Self, Self::Err> {
-//         todo!()
-//     }
-// }
+impl Default for Document<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for Document<'static> {
+    type Err = ParseErrors;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Document::from_str(s) {
+            Ok(document) => Ok(document.into_owned()),
+            Err(errors) => Err(ParseErrors {
+                source: s.into(),
+                errors: errors.errors,
+                paths: errors.paths,
+            }),
+        }
+    }
+}
+
+/// A document which owns its source text.
+///
+/// Unlike [`Document::into_owned`], this does not copy any strings; the
+/// document keeps borrowing from the shared source.
+pub struct OwnedDocument {
+    // NB: must be dropped before the source it borrows from
+    document: Document<'static>,
+    source: Arc<str>,
+}
+
+impl OwnedDocument {
+    pub fn from_source(source: impl Into<Arc<str>>) -> Result<Self, ParseErrors<Arc<str>>> {
+        let source = source.into();
+        // SAFETY: the document borrows `kdl` for longer than it lives, so
+        // this relies on these invariants of `OwnedDocument`:
+        // - the string lives in the Arc's allocation, which does not move when
+        //   the Arc does, and `source` keeps it alive as long as `document`;
+        // - `document` is declared before `source`, so is dropped first;
+        // - `document` is only handed out by reference, with its lifetime
+        //   shortened to a borrow of self (`Document` is covariant), and
+        //   `into_document` detaches it before dropping `source`, so nothing
+        //   borrowed for `'static` escapes.
+        let kdl = unsafe { &*(&*source as *const str) };
+        match Document::from_str(kdl) {
+            Ok(document) => Ok(OwnedDocument { document, source }),
+            Err(errors) => Err(ParseErrors {
+                errors: errors.errors,
+                paths: errors.paths,
+                source,
+            }),
+        }
+    }
+
+    pub fn source(&self) -> &Arc<str> {
+        &self.source
+    }
+
+    pub fn document(&self) -> &Document<'_> {
+        &self.document
+    }
+
+    /// Copy any strings borrowed from the source, detaching the document.
+    pub fn into_document(self) -> Document<'static> {
+        let OwnedDocument { document, source } = self;
+        let document = document.into_owned();
+        drop(source);
+        document
+    }
+}
+
+impl FromStr for OwnedDocument {
+    type Err = ParseErrors<Arc<str>>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OwnedDocument::from_source(s)
+    }
+}
+
+impl fmt::Debug for OwnedDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.document(), f)
+    }
+}
 
 impl fmt::Debug for Document<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -0,0 +1,422 @@
+use {
+    super::{details::*, Literal, Node},
+    alloc::{borrow::Cow, vec::Vec},
+    core::{fmt, num::NonZeroU32, ops::Deref},
+};
+
+/// A mutable handle to a node in a [`Document`](super::Document).
+///
+/// Nodes are stored flattened, so growing or shrinking one needs access to
+/// the whole document; this handle provides that. It derefs to [`Node`] for
+/// read access.
+///
+/// Elements added this way have empty spans at the start of the document.
+///
+/// Attribute and child indices count disabled entries as well; see
+/// [`Document::from_str_with_disabled`](super::Document::from_str_with_disabled).
+pub struct NodeMut<'a, 'kdl> {
+    entries: &'a mut Vec<Entry<'kdl>>,
+    ix: usize,
+}
+
+/// A mutable handle to the children of a node or document.
+pub struct ChildrenMut<'a, 'kdl> {
+    entries: &'a mut Vec<Entry<'kdl>>,
+    parent: usize,
+}
+
+impl<'a, 'kdl> NodeMut<'a, 'kdl> {
+    pub fn set_name(&mut self, name: impl Into<Cow<'kdl, str>>) -> &mut Self {
+        self.entries[self.ix].name = Some(name.into().into());
+        self
+    }
+
+    pub fn set_type(&mut self, ty: impl Into<Cow<'kdl, str>>) -> &mut Self {
+        self.entries[self.ix].ty = Some(ty.into().into());
+        self
+    }
+
+    pub fn clear_type(&mut self) -> &mut Self {
+        self.entries[self.ix].ty = None;
+        self
+    }
+
+    pub fn push_argument(&mut self, value: impl Into<Literal<'kdl>>) -> &mut Self {
+        self.push_attr(None, value.into());
+        self
+    }
+
+    /// Set the value of a property.
+    ///
+    /// If the property is already present, the value of the occurrence which
+    /// wins (the last one) is replaced, keeping its type annotation.
+    /// Otherwise, a new property is added after all existing attributes.
+    pub fn set_property(
+        &mut self,
+        name: impl Into<Cow<'kdl, str>>,
+        value: impl Into<Literal<'kdl>>,
+    ) -> &mut Self {
+        let name = name.into();
+        let num_attrs = self.meta().num_attrs as usize;
+        let attrs = &mut self.entries[self.ix + 1..][..num_attrs];
+        match attrs
+            .iter_mut()
+            .rev()
+            .find(|attr| !attr.disabled && attr.name.as_deref() == Some(&*name))
+        {
+            Some(attr) => attr.kind = EntryKind::Attr(value.into().into_attr()),
+            None => self.push_attr(Some(name), value.into()),
+        }
+        self
+    }
+
+    /// Set the type annotation of the attribute at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_attr_type(
+        &mut self,
+        index: usize,
+        ty: Option<impl Into<Cow<'kdl, str>>>,
+    ) -> &mut Self {
+        assert!(
+            index < self.meta().num_attrs as usize,
+            "attr index out of bounds"
+        );
+        self.entries[self.ix + 1 + index].ty = ty.map(|ty| ty.into().into());
+        self
+    }
+
+    /// Remove the attribute (argument or property) at the given index.
+    ///
+    /// Returns `false` if there was no such attribute.
+    pub fn remove_attr(&mut self, index: usize) -> bool {
+        if index >= self.meta().num_attrs as usize {
+            return false;
+        }
+        self.meta_mut().num_attrs -= 1;
+        let at = self.ix + 1 + index;
+        remove_entries(self.entries, at, 1);
+        true
+    }
+
+    /// Disable or re-enable this node, as if commenting it out with `/-`.
+    pub fn set_disabled(&mut self, disabled: bool) -> &mut Self {
+        self.entries[self.ix].disabled = disabled;
+        self
+    }
+
+    /// Disable or re-enable the attribute at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_attr_disabled(&mut self, index: usize, disabled: bool) -> &mut Self {
+        assert!(
+            index < self.meta().num_attrs as usize,
+            "attr index out of bounds"
+        );
+        self.entries[self.ix + 1 + index].disabled = disabled;
+        self
+    }
+
+    pub fn children_mut(&mut self) -> ChildrenMut<'_, 'kdl> {
+        ChildrenMut {
+            entries: self.entries,
+            parent: self.ix,
+        }
+    }
+
+    /// Add a new child node after all existing children.
+    pub fn push_child(&mut self, name: impl Into<Cow<'kdl, str>>) -> NodeMut<'_, 'kdl> {
+        ChildrenMut {
+            entries: self.entries,
+            parent: self.ix,
+        }
+        .into_push(name)
+    }
+
+    fn meta(&self) -> &NodeMeta {
+        self.entries[self.ix].kind.unwrap_node()
+    }
+
+    fn meta_mut(&mut self) -> &mut NodeMeta {
+        self.entries[self.ix].kind.unwrap_node_mut()
+    }
+
+    fn push_attr(&mut self, name: Option<Cow<'kdl, str>>, value: Literal<'kdl>) {
+        let at = self.ix + 1 + self.meta().num_attrs as usize;
+        insert_entry(
+            self.entries,
+            at,
+            Entry {
+                span: EntrySpan::at(0),
+                name: name.map(Into::into),
+                ty: None,
+                kind: EntryKind::Attr(value.into_attr()),
+                disabled: false,
+                comments: None,
+            },
+        );
+        self.meta_mut().num_attrs += 1;
+    }
+}
+
+impl<'kdl> Deref for NodeMut<'_, 'kdl> {
+    type Target = Node<'kdl>;
+    fn deref(&self) -> &Node<'kdl> {
+        Node::ref_cast(&self.entries[self.ix..])
+    }
+}
+
+impl fmt::Debug for NodeMut<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, 'kdl> ChildrenMut<'a, 'kdl> {
+    pub(super) fn new(entries: &'a mut Vec<Entry<'kdl>>, parent: usize) -> Self {
+        Self { entries, parent }
+    }
+
+    pub fn len(&self) -> usize {
+        self.meta().num_childs as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<NodeMut<'_, 'kdl>> {
+        let ix = self.child_ix(index)?;
+        Some(NodeMut {
+            entries: self.entries,
+            ix,
+        })
+    }
+
+    /// Insert a new node so that it becomes the child at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, name: impl Into<Cow<'kdl, str>>) -> NodeMut<'_, 'kdl> {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+
+        let parent = self.parent;
+        let prev = index.checked_sub(1).map(|i| self.child_ix(i).unwrap());
+        let at = match self.child_ix(index) {
+            Some(next) => next,
+            None => parent + subtree_len(self.entries, parent),
+        };
+
+        insert_entry(
+            self.entries,
+            at,
+            Entry {
+                span: EntrySpan::at(0),
+                name: Some(name.into().into()),
+                ty: None,
+                kind: EntryKind::Node(NodeMeta::default()),
+                disabled: false,
+                comments: None,
+            },
+        );
+
+        let has_next = index < len;
+        if let Some(prev) = prev {
+            let offset = offset(prev, at);
+            self.entries[prev].kind.unwrap_node_mut().next_sibling = offset;
+            self.entries[at].kind.unwrap_node_mut().prev_sibling = offset;
+        }
+        if has_next {
+            let offset = offset(at, at + 1);
+            self.entries[at].kind.unwrap_node_mut().next_sibling = offset;
+            self.entries[at + 1].kind.unwrap_node_mut().prev_sibling = offset;
+        }
+        let meta = self.meta_mut();
+        if index == 0 {
+            meta.first_child = offset(parent, at);
+        }
+        if !has_next {
+            meta.last_child = offset(parent, at);
+        }
+        meta.num_childs += 1;
+
+        NodeMut {
+            entries: self.entries,
+            ix: at,
+        }
+    }
+
+    /// Add a new node after all existing children.
+    pub fn push(&mut self, name: impl Into<Cow<'kdl, str>>) -> NodeMut<'_, 'kdl> {
+        self.insert(self.len(), name)
+    }
+
+    pub(super) fn into_push(self, name: impl Into<Cow<'kdl, str>>) -> NodeMut<'a, 'kdl> {
+        let index = self.len();
+        let Self { entries, parent } = self;
+        let ix = ChildrenMut {
+            entries: &mut *entries,
+            parent,
+        }
+        .insert(index, name)
+        .ix;
+        NodeMut { entries, ix }
+    }
+
+    /// Remove the child node at `index`, along with all of its descendants.
+    ///
+    /// Returns `false` if there was no such child.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let at = match self.child_ix(index) {
+            Some(at) => at,
+            None => return false,
+        };
+        let parent = self.parent;
+        let len = subtree_len(self.entries, at);
+        let meta = self.entries[at].kind.unwrap_node();
+        let prev = meta.prev_sibling.map(|off| at - off.get() as usize);
+        let next = meta.next_sibling.map(|off| at + off.get() as usize);
+
+        // Link the neighbours up with pre-removal indices; removal fixes them.
+        match prev {
+            Some(prev) => {
+                self.entries[prev].kind.unwrap_node_mut().next_sibling =
+                    next.and_then(|next| offset(prev, next))
+            }
+            None => self.meta_mut().first_child = next.and_then(|next| offset(parent, next)),
+        }
+        match next {
+            Some(next) => {
+                self.entries[next].kind.unwrap_node_mut().prev_sibling =
+                    prev.and_then(|prev| offset(prev, next))
+            }
+            None => self.meta_mut().last_child = prev.and_then(|prev| offset(parent, prev)),
+        }
+        self.meta_mut().num_childs -= 1;
+
+        remove_entries(self.entries, at, len);
+        true
+    }
+
+    fn meta(&self) -> &NodeMeta {
+        self.entries[self.parent].kind.unwrap_node()
+    }
+
+    fn meta_mut(&mut self) -> &mut NodeMeta {
+        self.entries[self.parent].kind.unwrap_node_mut()
+    }
+
+    fn child_ix(&self, index: usize) -> Option<usize> {
+        let mut ix = self.parent + self.meta().first_child?.get() as usize;
+        for _ in 0..index {
+            ix += self.entries[ix].kind.unwrap_node().next_sibling?.get() as usize;
+        }
+        Some(ix)
+    }
+}
+
+impl fmt::Debug for ChildrenMut<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&Node::ref_cast(&self.entries[self.parent..]).children(), f)
+    }
+}
+
+fn offset(from: usize, to: usize) -> Option<NonZeroU32> {
+    NonZeroU32::new((to - from).try_into().unwrap())
+}
+
+/// The number of entries making up the node at `ix` and its descendants.
+fn subtree_len(entries: &[Entry<'_>], ix: usize) -> usize {
+    let meta = entries[ix].kind.unwrap_node();
+    match meta.last_child {
+        Some(last) => last.get() as usize + subtree_len(entries, ix + last.get() as usize),
+        None => 1 + meta.num_attrs as usize,
+    }
+}
+
+/// Apply `f` to every relative link which may cross the entries from `at` to
+/// `end` as `(from, to, link)`.
+///
+/// Only nodes enclosing those entries, and their children up to the first one
+/// starting at or after `end`, can have such links, so the rest of the
+/// document is not walked.
+fn for_each_link_across(
+    entries: &mut [Entry<'_>],
+    at: usize,
+    end: usize,
+    mut f: impl FnMut(usize, usize, &mut NonZeroU32),
+) {
+    // find the nodes before adjusting any links, as those are what we walk
+    let mut nodes = Vec::from([0]);
+    let mut parent = Some(0);
+    while let Some(ix) = parent.take() {
+        let mut child = entries[ix]
+            .kind
+            .unwrap_node()
+            .first_child
+            .map(|link| ix + link.get() as usize);
+        while let Some(ix) = child {
+            nodes.push(ix);
+            if ix >= end {
+                break;
+            }
+            let next = entries[ix]
+                .kind
+                .unwrap_node()
+                .next_sibling
+                .map(|link| ix + link.get() as usize);
+            if ix < at && next.map_or(true, |next| next > end) {
+                parent = Some(ix);
+            }
+            child = next;
+        }
+    }
+
+    for ix in nodes {
+        let meta = entries[ix].kind.unwrap_node_mut();
+        let forward = [
+            &mut meta.next_sibling,
+            &mut meta.first_child,
+            &mut meta.last_child,
+        ];
+        for link in forward.into_iter().flatten() {
+            f(ix, ix + link.get() as usize, link);
+        }
+        if let Some(link) = &mut meta.prev_sibling {
+            f(ix, ix - link.get() as usize, link);
+        }
+    }
+}
+
+/// Insert an entry, keeping links that cross the insertion point intact.
+fn insert_entry<'kdl>(entries: &mut Vec<Entry<'kdl>>, at: usize, entry: Entry<'kdl>) {
+    for_each_link_across(entries, at, at, |from, to, link| {
+        if (from < at) != (to < at) {
+            *link = NonZeroU32::new(link.get() + 1).unwrap();
+        }
+    });
+    entries.insert(at, entry);
+}
+
+/// Remove entries, keeping links that cross the removed range intact.
+///
+/// Links into the removed range must have already been unlinked.
+fn remove_entries(entries: &mut Vec<Entry<'_>>, at: usize, len: usize) {
+    let removed = at..at + len;
+    for_each_link_across(entries, at, at + len, |from, to, link| {
+        if !removed.contains(&from) && (from < at) != (to < at) {
+            debug_assert!(!removed.contains(&to), "dangling link into removed entries");
+            *link = NonZeroU32::new(link.get() - len as u32).unwrap();
+        }
+    });
+    entries.drain(removed);
+}
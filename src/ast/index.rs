@@ -0,0 +1,91 @@
+use {
+    super::{Document, Node},
+    alloc::vec::Vec,
+    core::fmt,
+    std::collections::HashMap,
+};
+
+/// A prebuilt lookup table from node names to nodes, across the whole tree.
+///
+/// Names are compared by their unescaped value, so `"foo"` and `foo` are the
+/// same name. Nodes are listed in source (pre-)order; parents come before
+/// their children.
+///
+/// Indexing type annotations as well is opt-in with [`with_types`].
+///
+/// [`with_types`]: DocumentIndex::with_types
+#[derive(Clone)]
+pub struct DocumentIndex<'a, 'kdl> {
+    by_name: HashMap<&'a str, Vec<&'a Node<'kdl>>>,
+    by_type: Option<HashMap<&'a str, Vec<&'a Node<'kdl>>>>,
+}
+
+impl<'a, 'kdl> DocumentIndex<'a, 'kdl> {
+    /// Index the nodes of a document by name.
+    pub fn new(document: &'a Document<'kdl>) -> Self {
+        Self::build(document, false)
+    }
+
+    /// Index the nodes of a document by name and by type annotation.
+    pub fn with_types(document: &'a Document<'kdl>) -> Self {
+        Self::build(document, true)
+    }
+
+    fn build(document: &'a Document<'kdl>, types: bool) -> Self {
+        let mut by_name: HashMap<_, Vec<_>> = HashMap::new();
+        let mut by_type: Option<HashMap<_, Vec<_>>> = types.then(HashMap::new);
+        for node in all_nodes(document) {
+            by_name.entry(&**node.name()).or_default().push(node);
+            if let (Some(by_type), Some(ty)) = (&mut by_type, node.ty()) {
+                by_type.entry(&**ty).or_default().push(node);
+            }
+        }
+        Self { by_name, by_type }
+    }
+
+    /// All nodes with the given name, anywhere in the document.
+    pub fn nodes_named(&self, name: &str) -> &[&'a Node<'kdl>] {
+        self.by_name.get(name).map_or(&[], |nodes| &nodes[..])
+    }
+
+    /// All nodes with the given type annotation, anywhere in the document.
+    ///
+    /// Returns `None` if the index was not built [`with_types`].
+    ///
+    /// [`with_types`]: DocumentIndex::with_types
+    pub fn nodes_typed(&self, ty: &str) -> Option<&[&'a Node<'kdl>]> {
+        let by_type = self.by_type.as_ref()?;
+        Some(by_type.get(ty).map_or(&[], |nodes| &nodes[..]))
+    }
+
+    /// Iterate over every distinct node name in the document.
+    pub fn names(&self) -> impl '_ + Iterator<Item = &'a str> {
+        self.by_name.keys().copied()
+    }
+}
+
+fn all_nodes<'a, 'kdl>(document: &'a Document<'kdl>) -> impl Iterator<Item = &'a Node<'kdl>> {
+    // Walk the tree rather than the flattened entries, to skip disabled nodes
+    // along with their descendants.
+    let mut stack = vec![document.nodes()];
+    core::iter::from_fn(move || loop {
+        match stack.last_mut()?.next() {
+            Some(node) => {
+                stack.push(node.children());
+                return Some(node);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    })
+}
+
+impl fmt::Debug for DocumentIndex<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DocumentIndex")
+            .field("names", &self.by_name.len())
+            .field("types", &self.by_type.as_ref().map(HashMap::len))
+            .finish()
+    }
+}
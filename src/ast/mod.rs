@@ -3,14 +3,22 @@ mod details;
 mod ann;
 mod attr;
 mod collect;
+mod comment;
 mod document;
+mod edit;
+mod index;
 mod node;
+mod replay;
 mod value;
 
 pub use self::{
     ann::{Name, Ty},
-    attr::{Argument, Attr, AttrIter, Property},
-    document::Document,
+    attr::{Argument, Attr, AttrIter, Property, PropertyMap},
+    comment::Comment,
+    document::{Document, OwnedDocument},
+    edit::{ChildrenMut, NodeMut},
+    index::DocumentIndex,
     node::{Node, NodeIter},
-    value::Value,
+    replay::Replay,
+    value::{Literal, Value},
 };
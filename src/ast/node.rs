@@ -1,6 +1,7 @@
 use {
-    super::{details::*, Argument, AttrIter, Name, Property, Ty},
+    super::{details::*, Argument, AttrIter, Comment, Name, Property, PropertyMap, Ty},
     crate::Span,
+    alloc::{collections::BTreeSet, vec::Vec},
     core::fmt,
 };
 
@@ -33,11 +34,44 @@ impl<'kdl> Node<'kdl> {
         }
     }
 
+    /// Whether this node is commented out with `/-`.
+    ///
+    /// See [`Document::from_str_with_disabled`](super::Document::from_str_with_disabled).
+    pub fn is_disabled(&self) -> bool {
+        self.entries[0].disabled
+    }
+
+    /// The comments directly before this node.
+    ///
+    /// See [`Comment`] for how comments are attached to nodes.
+    pub fn leading_comments(&self) -> &[Comment<'kdl>] {
+        match &self.entries[0].comments {
+            Some(comments) => &comments.leading,
+            None => &[],
+        }
+    }
+
+    /// The comment on the same line after this node.
+    ///
+    /// See [`Comment`] for how comments are attached to nodes.
+    pub fn trailing_comment(&self) -> Option<&Comment<'kdl>> {
+        self.entries[0].comments.as_ref()?.trailing.as_ref()
+    }
+
     pub fn attrs(&self) -> AttrIter<'_, 'kdl> {
+        AttrIter {
+            disabled: false,
+            ..self.attrs_with_disabled()
+        }
+    }
+
+    /// Like [`attrs`](Node::attrs), but including disabled attributes.
+    pub fn attrs_with_disabled(&self) -> AttrIter<'_, 'kdl> {
         let meta = self.entries[0].kind.unwrap_node();
         let num_attrs = meta.num_attrs;
         AttrIter {
             entries: &self.entries[1..=num_attrs as usize],
+            disabled: true,
         }
     }
 
@@ -49,29 +83,108 @@ impl<'kdl> Node<'kdl> {
         self.attrs().filter_map(|attr| attr.as_argument())
     }
 
+    /// Get the value of the property with the given name.
+    ///
+    /// Per the KDL spec, if a property key is repeated, the last one wins.
+    pub fn property(&self, name: &str) -> Option<&Property<'kdl>> {
+        self.attrs()
+            .rev()
+            .filter_map(|attr| attr.as_property())
+            .find(|property| &**property.name() == name)
+    }
+
+    /// Get the argument at the given index, counting only arguments.
+    pub fn argument(&self, index: usize) -> Option<&Argument<'kdl>> {
+        self.arguments().nth(index)
+    }
+
+    /// The properties of this node, with repeated keys deduplicated.
+    ///
+    /// Only the last occurrence of each key is kept, in source order.
+    pub fn property_map(&self) -> PropertyMap<'_, 'kdl> {
+        PropertyMap {
+            properties: self
+                .properties_marked()
+                .filter_map(|(property, live)| live.then_some(property))
+                .collect(),
+        }
+    }
+
+    /// The properties of this node which are overridden by a later property
+    /// with the same key, in source order.
+    pub fn duplicate_properties(&self) -> impl Iterator<Item = &Property<'kdl>> {
+        self.properties_marked()
+            .filter_map(|(property, live)| (!live).then_some(property))
+    }
+
+    /// The properties of this node in source order, each with whether it is
+    /// the last occurrence of its key, found in a single reverse pass.
+    fn properties_marked(&self) -> impl Iterator<Item = (&Property<'kdl>, bool)> {
+        let mut seen = BTreeSet::new();
+        let mut marked: Vec<_> = self
+            .attrs()
+            .rev()
+            .filter_map(|attr| attr.as_property())
+            .map(|property| (property, seen.insert(&**property.name())))
+            .collect();
+        marked.reverse();
+        marked.into_iter()
+    }
+
     pub fn children(&self) -> NodeIter<'_, 'kdl> {
+        NodeIter {
+            disabled: false,
+            ..self.children_with_disabled()
+        }
+    }
+
+    /// Like [`children`](Node::children), but including disabled nodes.
+    pub fn children_with_disabled(&self) -> NodeIter<'_, 'kdl> {
         let meta = self.entries[0].kind.unwrap_node();
         match (meta.first_child, meta.last_child) {
             (Some(first), Some(last)) => NodeIter {
                 entries: &self.entries[first.get() as usize..],
                 tail: (last.get() - first.get()) as usize,
+                disabled: true,
+            },
+            (None, None) => NodeIter {
+                disabled: true,
+                ..Default::default()
             },
-            (None, None) => Default::default(),
             _ => unreachable!("corrupted KDL AST"),
         }
     }
+
+    /// Get the first child node with the given name.
+    pub fn child(&self, name: &str) -> Option<&Node<'kdl>> {
+        self.children().find(|node| &**node.name() == name)
+    }
+
+    /// Iterate over the child nodes with the given name, in source order.
+    pub fn children_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl 'a + DoubleEndedIterator<Item = &'a Node<'kdl>> {
+        self.children().filter(move |node| &**node.name() == name)
+    }
 }
 
 impl fmt::Debug for Node<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
-            f.debug_struct("Node")
-                .field("span", &self.span())
-                .field("ty", &self.ty())
+            let mut f = f.debug_struct("Node");
+            f.field("span", &self.span());
+            if !self.leading_comments().is_empty() {
+                f.field("leading_comments", &self.leading_comments());
+            }
+            f.field("ty", &self.ty())
                 .field("name", &self.name())
                 .field("attrs", &self.attrs())
-                .field("children", &self.children())
-                .finish()
+                .field("children", &self.children());
+            if let Some(comment) = self.trailing_comment() {
+                f.field("trailing_comment", comment);
+            }
+            f.finish()
         } else {
             f.debug_struct("Node").finish_non_exhaustive()
         }
@@ -82,13 +195,13 @@ impl fmt::Debug for Node<'_> {
 pub struct NodeIter<'a, 'kdl> {
     pub(super) entries: &'a [Entry<'kdl>],
     pub(super) tail: usize,
+    /// Whether to include disabled nodes.
+    pub(super) disabled: bool,
 }
 
-impl<'a, 'kdl> Iterator for NodeIter<'a, 'kdl> {
-    type Item = &'a Node<'kdl>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let entry = self.entries.get(0)?;
+impl<'a, 'kdl> NodeIter<'a, 'kdl> {
+    fn next_any(&mut self) -> Option<&'a Node<'kdl>> {
+        let entry = self.entries.first()?;
         let meta = entry.kind.unwrap_node();
         match meta.next_sibling {
             None => {
@@ -107,13 +220,7 @@ impl<'a, 'kdl> Iterator for NodeIter<'a, 'kdl> {
         }
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (1, Some(self.tail))
-    }
-}
-
-impl<'a, 'kdl> DoubleEndedIterator for NodeIter<'a, 'kdl> {
-    fn next_back(&mut self) -> Option<Self::Item> {
+    fn next_back_any(&mut self) -> Option<&'a Node<'kdl>> {
         let entry = self.entries.get(self.tail)?;
         let meta = entry.kind.unwrap_node();
         match meta.prev_sibling {
@@ -134,6 +241,22 @@ impl<'a, 'kdl> DoubleEndedIterator for NodeIter<'a, 'kdl> {
     }
 }
 
+impl<'a, 'kdl> Iterator for NodeIter<'a, 'kdl> {
+    type Item = &'a Node<'kdl>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let disabled = self.disabled;
+        core::iter::from_fn(|| self.next_any()).find(|node| disabled || !node.is_disabled())
+    }
+}
+
+impl<'a, 'kdl> DoubleEndedIterator for NodeIter<'a, 'kdl> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let disabled = self.disabled;
+        core::iter::from_fn(|| self.next_back_any()).find(|node| disabled || !node.is_disabled())
+    }
+}
+
 impl fmt::Debug for NodeIter<'_, '_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.clone()).finish()
@@ -0,0 +1,340 @@
+use {
+    super::{details::*, Document},
+    crate::{
+        utils::Fmt,
+        visit::{self, prelude::*, LendChildren as _},
+        Span,
+    },
+    alloc::{string::String, vec::Vec},
+    core::fmt::{self, Write},
+};
+
+const INDENT: &str = "    ";
+
+/// A document prepared to be replayed into a [`visit::LendDocument`].
+///
+/// Replaying makes the same visitor calls as parsing [`source`] would, where
+/// [`source`] is the document rendered in a canonical layout: one node per
+/// line, children indented by four spaces, and no comments. All terminals and
+/// trivia given to the visitor borrow from this rendering.
+///
+/// The rendering is lossy in one way: KDL cannot represent non-finite
+/// numbers, so infinite and NaN values (such as from `1e999`) are replayed as
+/// `null`.
+///
+/// [`source`]: Replay::source
+pub struct Replay<'a, 'kdl> {
+    entries: &'a [Entry<'kdl>],
+    source: String,
+    /// The rendered name, type, and value of each entry.
+    terminals: Vec<[Span; 3]>,
+}
+
+impl<'a, 'kdl> Replay<'a, 'kdl> {
+    pub(super) fn new(document: &'a Document<'kdl>) -> Self {
+        let mut this = Replay {
+            entries: &document.entries,
+            source: String::new(),
+            terminals: vec![[Span::default(); 3]; document.entries.len()],
+        };
+        this.render_children(0, 0);
+        this
+    }
+
+    /// The canonical KDL text which the replay corresponds to.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Replay the document into a visitor.
+    pub fn accept<'r, V: visit::LendDocument<'r>>(&'r self, mut visitor: V) -> V::Output {
+        let mut cursor = Cursor {
+            replay: self,
+            pos: 0,
+        };
+        cursor.children(&mut visitor, 0, 0);
+        debug_assert_eq!(cursor.pos, self.source.len());
+        visitor.finish()
+    }
+
+    fn push(&mut self, s: &str) {
+        self.source.push_str(s);
+    }
+
+    fn render(&mut self, f: impl FnOnce(&mut String) -> fmt::Result) -> Span {
+        let start = self.source.len();
+        f(&mut self.source).expect("formatting to a string should not fail");
+        Span::from(start..self.source.len())
+    }
+
+    fn render_identifier(&mut self, id: &str) -> Span {
+        self.render(|f| {
+            if valid_bareword(id) {
+                f.write_str(id)
+            } else {
+                write!(f, "{}", escape(id))
+            }
+        })
+    }
+
+    fn render_type(&mut self, ix: usize) {
+        if let Some(ty) = &self.entries[ix].ty {
+            self.push("(");
+            self.terminals[ix][1] = self.render_identifier(ty);
+            self.push(")");
+        }
+    }
+
+    fn render_children(&mut self, parent: usize, depth: usize) {
+        for ix in children(self.entries, parent) {
+            for _ in 0..depth {
+                self.push(INDENT);
+            }
+            self.render_node(ix, depth);
+            if has_children(self.entries, ix) {
+                self.push("\n");
+            }
+        }
+        for _ in 1..depth {
+            self.push(INDENT);
+        }
+    }
+
+    fn render_node(&mut self, ix: usize, depth: usize) {
+        let entries = self.entries;
+        self.render_type(ix);
+        self.terminals[ix][0] = self.render_identifier(entries[ix].name.as_deref().unwrap_or(""));
+        for attr in attrs(entries, ix) {
+            self.push(" ");
+            if let Some(name) = &entries[attr].name {
+                self.terminals[attr][0] = self.render_identifier(name);
+                self.push("=");
+            }
+            self.render_type(attr);
+            self.terminals[attr][2] = self.render(|f| match entries[attr].kind.unwrap_attr() {
+                AttrValue::String(s) => write!(f, "{}", escape(s)),
+                AttrValue::Exact(d) => write!(f, "{d}"),
+                AttrValue::Inexact(n) if n.is_finite() => write!(f, "{n:?}"),
+                // KDL cannot represent non-finite numbers
+                AttrValue::Inexact(_) => f.write_str("null"),
+                AttrValue::True => f.write_str("true"),
+                AttrValue::False => f.write_str("false"),
+                AttrValue::Null => f.write_str("null"),
+            });
+        }
+        if has_children(entries, ix) {
+            self.push(" {\n");
+            self.render_children(ix, depth + 1);
+            self.push("}");
+        } else {
+            self.push("\n");
+        }
+    }
+}
+
+impl fmt::Debug for Replay<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+struct Cursor<'r, 'a, 'kdl> {
+    replay: &'r Replay<'a, 'kdl>,
+    pos: usize,
+}
+
+impl<'r> Cursor<'r, '_, '_> {
+    fn take(&mut self, len: usize) -> &'r str {
+        let start = self.pos;
+        self.pos += len;
+        &self.replay.source[start..self.pos]
+    }
+
+    fn terminal(&mut self, ix: usize, which: usize) -> &'r str {
+        let span = self.replay.terminals[ix][which];
+        debug_assert_eq!(span.start, self.pos);
+        self.take(span.len())
+    }
+
+    fn identifier(&mut self, ix: usize, which: usize) -> visit::Identifier<'r> {
+        let source = self.terminal(ix, which);
+        if source.starts_with('"') {
+            visit::Identifier::String(visit::String { source })
+        } else {
+            visit::Identifier::Bare(source)
+        }
+    }
+
+    fn value(&mut self, ix: usize) -> visit::Value<'r> {
+        let source = self.terminal(ix, 2);
+        match self.replay.entries[ix].kind.unwrap_attr() {
+            AttrValue::String(_) => visit::Value::String(visit::String { source }),
+            AttrValue::Exact(_) => visit::Value::Number(visit::Number { source }),
+            AttrValue::Inexact(n) if n.is_finite() => {
+                visit::Value::Number(visit::Number { source })
+            }
+            AttrValue::Inexact(_) => visit::Value::Null,
+            AttrValue::True => visit::Value::Boolean(true),
+            AttrValue::False => visit::Value::Boolean(false),
+            AttrValue::Null => visit::Value::Null,
+        }
+    }
+
+    fn children(
+        &mut self,
+        visitor: &mut impl visit::LendChildren<'r>,
+        parent: usize,
+        depth: usize,
+    ) {
+        let entries = self.replay.entries;
+        for ix in children(entries, parent) {
+            if depth > 0 {
+                visitor.visit_trivia(self.take(INDENT.len() * depth));
+            }
+            let mut node_visitor = visitor.visit_node();
+            self.node(&mut node_visitor, ix, depth);
+            drop(node_visitor);
+            if has_children(entries, ix) {
+                visitor.visit_trivia(self.take(1));
+            }
+        }
+        if depth > 1 {
+            visitor.visit_trivia(self.take(INDENT.len() * (depth - 1)));
+        }
+    }
+
+    fn node(&mut self, visitor: &mut impl visit::LendNode<'r>, ix: usize, depth: usize) {
+        let entries = self.replay.entries;
+        if entries[ix].ty.is_some() {
+            visitor.visit_trivia(self.take(1));
+            visitor.visit_type(self.identifier(ix, 1));
+            visitor.visit_trivia(self.take(1));
+        }
+        visitor.visit_name(self.identifier(ix, 0));
+
+        for attr in attrs(entries, ix) {
+            visitor.visit_trivia(self.take(1));
+            if entries[attr].name.is_some() {
+                let mut property_visitor = visitor.visit_property();
+                property_visitor.visit_name(self.identifier(attr, 0));
+                property_visitor.visit_trivia(self.take(1));
+                if entries[attr].ty.is_some() {
+                    property_visitor.visit_trivia(self.take(1));
+                    property_visitor.visit_type(self.identifier(attr, 1));
+                    property_visitor.visit_trivia(self.take(1));
+                }
+                property_visitor.visit_value(self.value(attr));
+            } else {
+                let mut argument_visitor = visitor.visit_argument();
+                if entries[attr].ty.is_some() {
+                    argument_visitor.visit_trivia(self.take(1));
+                    argument_visitor.visit_type(self.identifier(attr, 1));
+                    argument_visitor.visit_trivia(self.take(1));
+                }
+                argument_visitor.visit_value(self.value(attr));
+            }
+        }
+
+        if has_children(entries, ix) {
+            visitor.visit_trivia(self.take(1));
+            visitor.visit_trivia(self.take(1));
+            let mut children_visitor = visitor.visit_children();
+            children_visitor.visit_trivia(self.take(1));
+            self.children(&mut children_visitor, ix, depth + 1);
+            drop(children_visitor);
+            visitor.visit_trivia(self.take(1));
+        } else {
+            visitor.visit_trivia(self.take(1));
+        }
+    }
+}
+
+fn has_children(entries: &[Entry<'_>], ix: usize) -> bool {
+    children(entries, ix).next().is_some()
+}
+
+fn attrs<'a>(entries: &'a [Entry<'_>], ix: usize) -> impl 'a + Iterator<Item = usize> {
+    (ix + 1..=ix + entries[ix].kind.unwrap_node().num_attrs as usize)
+        .filter(move |&attr| !entries[attr].disabled)
+}
+
+fn children<'a>(entries: &'a [Entry<'_>], ix: usize) -> impl 'a + Iterator<Item = usize> {
+    let first = entries[ix].kind.unwrap_node().first_child;
+    let mut next = first.map(|off| ix + off.get() as usize);
+    core::iter::from_fn(move || {
+        let this = next?;
+        next = entries[this]
+            .kind
+            .unwrap_node()
+            .next_sibling
+            .map(|off| this + off.get() as usize);
+        Some(this)
+    })
+    .filter(move |&ix| !entries[ix].disabled)
+}
+
+/// Whether `s` can be written as a bare identifier, rather than quoted.
+fn valid_bareword(s: &str) -> bool {
+    fn looks_like_number(s: &str) -> bool {
+        s.starts_with(|c: char| c.is_ascii_digit())
+            || (s.starts_with(['+', '-'])
+                && s.chars().nth(1).map_or(false, |c: char| c.is_ascii_digit()))
+    }
+
+    fn looks_like_string(s: &str) -> bool {
+        s.starts_with(r#"r""#) || s.starts_with(r#"r#"#)
+    }
+
+    fn is_keyword(s: &str) -> bool {
+        matches!(s, "true" | "false" | "null")
+    }
+
+    !(s.is_empty()
+        || s.contains([
+            '\\', '/', '(', ')', '{', '}', '<', '>', ';', '[', ']', '=', ',', '"',
+        ])
+        || s.contains(|c: char| c.is_whitespace() || c == '\u{FEFF}')
+        || is_keyword(s)
+        || looks_like_string(s)
+        || looks_like_number(s))
+}
+
+/// Render `src` as a quoted KDL string, escaping whatever needs it.
+fn escape(src: &str) -> impl '_ + fmt::Display {
+    fn should_escape(c: char) -> bool {
+        match c {
+            // KDL Escape Sequences should always be escaped
+            '\n' | '\r' | '\t' | '\\' | '"' | '\u{8}' | '\u{C}' => true,
+            // KDL Newline should always be escaped
+            '\u{85}' | '\u{2028}' | '\u{2029}' => true,
+            // Invisible characters are escaped to make them visible
+            '\u{FEFF}' => true,
+            c => c.is_control(),
+        }
+    }
+
+    Fmt(move |f| {
+        let mut src = src;
+        f.write_char('"')?;
+        while let Some(next_escaped) = src.find(should_escape) {
+            f.write_str(&src[..next_escaped])?;
+            src = &src[next_escaped..];
+            let escaped = src.chars().next().unwrap();
+            match escaped {
+                '\r' => f.write_str(r"\r")?,
+                '\n' => f.write_str(r"\n")?,
+                '\t' => f.write_str(r"\t")?,
+                '\\' => f.write_str(r"\\")?,
+                '"' => f.write_str(r#"\""#)?,
+                '\u{8}' => f.write_str(r"\b")?,
+                '\u{C}' => f.write_str(r"\f")?,
+                c => write!(f, r"\u{{{:04X}}}", c as u32)?,
+            }
+            src = &src[escaped.len_utf8()..];
+        }
+        f.write_str(src)?;
+        f.write_char('"')
+    })
+}
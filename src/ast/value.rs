@@ -1,8 +1,9 @@
 use {
     super::details::*,
     crate::Span,
+    alloc::{borrow::Cow, string::String},
     core::fmt,
-    rust_decimal::prelude::{Decimal, ToPrimitive},
+    rust_decimal::prelude::{Decimal, FromPrimitive, ToPrimitive},
 };
 
 #[repr(transparent)]
@@ -71,3 +72,95 @@ impl fmt::Debug for Value<'_> {
         }
     }
 }
+
+/// A value to put into a document, such as with
+/// [`NodeMut::push_argument`](super::NodeMut::push_argument).
+///
+/// Usually created with one of the `From` conversions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal<'kdl> {
+    String(Cow<'kdl, str>),
+    Decimal(Decimal),
+    Float(f64),
+    Boolean(bool),
+    Null,
+}
+
+impl<'kdl> Literal<'kdl> {
+    pub(super) fn into_attr(self) -> AttrValue<'kdl> {
+        match self {
+            Literal::String(s) => AttrValue::String(s.into()),
+            Literal::Decimal(d) => AttrValue::Exact(d),
+            Literal::Float(n) => match Decimal::from_f64(n) {
+                Some(d) => AttrValue::Exact(d),
+                None => AttrValue::Inexact(n),
+            },
+            Literal::Boolean(true) => AttrValue::True,
+            Literal::Boolean(false) => AttrValue::False,
+            Literal::Null => AttrValue::Null,
+        }
+    }
+}
+
+impl<'kdl> From<&'kdl str> for Literal<'kdl> {
+    fn from(s: &'kdl str) -> Self {
+        Literal::String(s.into())
+    }
+}
+
+impl From<String> for Literal<'_> {
+    fn from(s: String) -> Self {
+        Literal::String(s.into())
+    }
+}
+
+impl<'kdl> From<Cow<'kdl, str>> for Literal<'kdl> {
+    fn from(s: Cow<'kdl, str>) -> Self {
+        Literal::String(s)
+    }
+}
+
+impl From<Decimal> for Literal<'_> {
+    fn from(d: Decimal) -> Self {
+        Literal::Decimal(d)
+    }
+}
+
+impl From<f64> for Literal<'_> {
+    fn from(n: f64) -> Self {
+        Literal::Float(n)
+    }
+}
+
+impl From<f32> for Literal<'_> {
+    fn from(n: f32) -> Self {
+        Literal::Float(n.into())
+    }
+}
+
+impl From<bool> for Literal<'_> {
+    fn from(b: bool) -> Self {
+        Literal::Boolean(b)
+    }
+}
+
+impl From<()> for Literal<'_> {
+    fn from((): ()) -> Self {
+        Literal::Null
+    }
+}
+
+macro_rules! impl_from_int {($($I:ident),* $(,)?) => {
+    $(
+        impl From<$I> for Literal<'_> {
+            fn from(n: $I) -> Self {
+                Literal::Decimal(n.into())
+            }
+        }
+    )*
+} }
+
+impl_from_int! {
+    u8, u16, u32, u64, usize,
+    i8, i16, i32, i64, isize,
+}
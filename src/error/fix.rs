@@ -0,0 +1,236 @@
+use crate::{ParseError, Span};
+#[cfg(feature = "alloc")]
+use {
+    super::many::SourceCode,
+    crate::ParseErrors,
+    alloc::{string::String, vec::Vec},
+    core::fmt,
+};
+
+/// A suggested edit to the source which fixes (part of) a [`ParseError`].
+///
+/// All suggestions for one error are meant to be applied together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Suggestion {
+    /// The source to replace. Empty for insertions.
+    pub span: Span,
+    /// The text to replace the span with. Empty for deletions.
+    pub replacement: &'static str,
+    pub applicability: Applicability,
+}
+
+/// How confident a [`Suggestion`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Applicability {
+    /// The suggestion is definitely what was meant, and can be applied
+    /// without review, e.g. by [`apply_fixes`].
+    MachineApplicable,
+    /// The suggestion fixes the error, but may not be what was meant.
+    MaybeIncorrect,
+}
+
+impl Suggestion {
+    fn new(span: Span, replacement: &'static str, applicability: Applicability) -> Self {
+        Suggestion {
+            span,
+            replacement,
+            applicability,
+        }
+    }
+
+    fn insert(at: usize, text: &'static str, applicability: Applicability) -> Self {
+        Self::new(Span::from(at..at), text, applicability)
+    }
+
+    fn delete(span: Span) -> Self {
+        Self::new(span, "", Applicability::MachineApplicable)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn overlaps(&self, other: &Suggestion) -> bool {
+        other.span.start < self.span.end && self.span.start < other.span.end
+    }
+}
+
+const HASHES: &str = "################################";
+
+impl ParseError {
+    /// Edits to the source which fix this error, if any are known.
+    pub fn suggestions(&self) -> impl Iterator<Item = Suggestion> {
+        use Applicability::*;
+        let quote = |span: Span| {
+            [
+                Some(Suggestion::insert(span.start, "\"", MachineApplicable)),
+                Some(Suggestion::insert(span.end, "\"", MachineApplicable)),
+                None,
+            ]
+        };
+        // replaces the quotes at either end of a string with double quotes
+        let requote = |open: Span, close: Span| {
+            [
+                Some(Suggestion::new(open, "\"", MachineApplicable)),
+                Some(Suggestion::new(close, "\"", MachineApplicable)),
+                None,
+            ]
+        };
+        // removes a separator, or replaces it with whitespace if there is
+        // none around it
+        let separate = |span: Span, remove: Option<Span>| {
+            let fix = match remove {
+                Some(remove) => Suggestion::delete(remove),
+                None => Suggestion::new(span, " ", MachineApplicable),
+            };
+            [Some(fix), None, None]
+        };
+        let fix = match *self {
+            ParseError::BareValue { span, .. } => [
+                Some(Suggestion::insert(span.start, "- ", MaybeIncorrect)),
+                None,
+                None,
+            ],
+            ParseError::ColonSeparator {
+                span,
+                value,
+                equals,
+            } => {
+                let separator = match equals {
+                    Some(equals) => Suggestion::new(equals, "=", MachineApplicable),
+                    None => Suggestion::delete(span),
+                };
+                match value {
+                    Some(value) => {
+                        let [open, close, _] = quote(value);
+                        [Some(separator), open, close]
+                    }
+                    None => [Some(separator), None, None],
+                }
+            }
+            ParseError::CommaSeparator { span, remove } => separate(span, remove),
+            ParseError::EscapedContent { span, .. } => [
+                Some(Suggestion::insert(span.start, "// ", MaybeIncorrect)),
+                None,
+                None,
+            ],
+            ParseError::EscapedEof { span, .. } => [Some(Suggestion::delete(span)), None, None],
+            ParseError::Generic { .. } => [None, None, None],
+            ParseError::InvalidStringEscape { span, .. } => {
+                let backslash = Span::from(span.start..span.start + 1);
+                [
+                    Some(Suggestion::new(backslash, "", MaybeIncorrect)),
+                    None,
+                    None,
+                ]
+            }
+            ParseError::InvalidWhitespaceAfterType { span, .. } => {
+                [Some(Suggestion::delete(span)), None, None]
+            }
+            ParseError::InvalidWhitespaceInProperty { span, span2 }
+            | ParseError::InvalidWhitespaceInType { span, span2 } => [
+                Some(Suggestion::delete(span)),
+                span2.map(Suggestion::delete),
+                None,
+            ],
+            ParseError::MissingValue { span, .. } => [
+                Some(Suggestion::insert(span.end, "null", MaybeIncorrect)),
+                None,
+                None,
+            ],
+            ParseError::MissingWhitespaceBeforeArgument { here, .. }
+            | ParseError::MissingWhitespaceBeforeProperty { here, .. } => [
+                Some(Suggestion::insert(here, " ", MachineApplicable)),
+                None,
+                None,
+            ],
+            ParseError::NodeAssignment { span, remove } => separate(span, remove),
+            #[cfg(feature = "decimal")]
+            ParseError::NumberOutOfRange { .. } => [None, None, None],
+            ParseError::ReservedCharacter { .. } => [None, None, None],
+            ParseError::SectionHeader { span, name, end } => [
+                Some(Suggestion::new(
+                    Span::from(span.start..name.start),
+                    "",
+                    MaybeIncorrect,
+                )),
+                Some(Suggestion::new(
+                    Span::from(name.end..span.end),
+                    " {",
+                    MaybeIncorrect,
+                )),
+                Some(Suggestion::insert(end, "\n}", MaybeIncorrect)),
+            ],
+            ParseError::SingleQuotedString { span, .. } => requote(
+                Span::from(span.start..span.start + 1),
+                Span::from(span.end - 1..span.end),
+            ),
+            ParseError::SmartQuotedString { open, close, .. } => requote(open, close),
+            ParseError::TooManyErrors { .. } => [None, None, None],
+            ParseError::UnclosedBlockComment { .. } => [None, None, None],
+            ParseError::UnclosedChildren { end, dedent, .. } => {
+                let at = dedent.unwrap_or(end).start;
+                [
+                    Some(Suggestion::insert(at, "}\n", MaybeIncorrect)),
+                    None,
+                    None,
+                ]
+            }
+            ParseError::UnclosedString { .. } => [None, None, None],
+            ParseError::UnclosedRawString { span, span2 } => {
+                let insert = span2.and_then(|end| {
+                    let missing = (span.len() - 2).checked_sub(end.len() - 1)?;
+                    let hashes = HASHES.get(..missing)?;
+                    Some(Suggestion::insert(end.end, hashes, MaybeIncorrect))
+                });
+                [insert, None, None]
+            }
+            ParseError::UnmatchedCloseBrace { span, .. } => {
+                [Some(Suggestion::new(span, "", MaybeIncorrect)), None, None]
+            }
+            ParseError::UnquotedPropertyName { span, .. } => quote(span),
+            ParseError::UnquotedValue { span, .. } => quote(span),
+        };
+        fix.into_iter().flatten()
+    }
+}
+
+/// Apply the [machine applicable](Applicability::MachineApplicable) fixes
+/// for the errors to the source they were reported for.
+///
+/// An error's fix is only applied if all of its suggestions are machine
+/// applicable and none of them overlap a fix for an earlier error.
+///
+/// ```
+/// # use kdl_visit::{apply_fixes, ParseErrors};
+/// let source = r#"node (type) 1"two" key=value"#;
+/// let errors = ParseErrors::from_str(source).unwrap();
+/// assert_eq!(apply_fixes(source, &errors), r#"node (type)1 "two" key="value""#);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn apply_fixes<Source: fmt::Debug + SourceCode>(
+    source: &str,
+    errors: &ParseErrors<Source>,
+) -> String {
+    let mut edits = Vec::<Suggestion>::new();
+    for error in &errors.errors {
+        let fix: Vec<_> = error.suggestions().collect();
+        let applies = fix.iter().all(|suggestion| {
+            suggestion.applicability == Applicability::MachineApplicable
+                && !edits.iter().any(|edit| edit.overlaps(suggestion))
+        });
+        if applies {
+            edits.extend(fix);
+        }
+    }
+    edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+
+    let mut fixed = String::with_capacity(source.len());
+    let mut pos = 0;
+    for edit in edits {
+        fixed.push_str(&source[pos..edit.span.start]);
+        fixed.push_str(edit.replacement);
+        pos = edit.span.end;
+    }
+    fixed.push_str(&source[pos..]);
+    fixed
+}
@@ -0,0 +1,250 @@
+use {
+    super::{
+        many::SourceCode,
+        plain::{floor_char_boundary, Label, Line, Report},
+    },
+    crate::{ParseErrors, Span},
+    alloc::vec::Vec,
+    core::fmt::{self, Write},
+};
+
+impl<Source: fmt::Debug + SourceCode + AsRef<str>> ParseErrors<Source> {
+    /// Display the errors as [JSON Lines](https://jsonlines.org/), one
+    /// object per error.
+    ///
+    /// Each object has the `path`, `severity`, diagnostic `code`, `message`,
+    /// the `nodes` the error is in (such as `"server > routes"`) if any,
+    /// `help` (or `null`), and `labels`: the highlighted regions, primary
+    /// first, each with its byte `start` and `end`, one-based `line`,
+    /// `column`, `end_line` and `end_column` (exclusive, in characters), and
+    /// `text` (or `null`).
+    ///
+    /// ```
+    /// # use kdl_visit::ParseErrors;
+    /// let errors = ParseErrors::from_str("node key=value").unwrap();
+    /// assert_eq!(
+    ///     errors.display_json_lines("config.kdl").to_string(),
+    ///     concat!(
+    ///         r#"{"path":"config.kdl","severity":"error","code":"kdl::unquoted_value","#,
+    ///         r#""message":"node value strings must be quoted","help":"add quotes around the name","#,
+    ///         r#""labels":[{"start":9,"end":14,"line":1,"column":10,"end_line":1,"end_column":15,"text":null}]}"#,
+    ///         "\n",
+    ///     ),
+    /// );
+    /// ```
+    pub fn display_json_lines<'a>(&'a self, path: &'a str) -> impl 'a + fmt::Display {
+        let source = self.source.as_ref();
+        JsonLines(
+            self.located()
+                .map(move |(error, nodes)| error.report(path, source, nodes.copied())),
+        )
+    }
+
+    /// Display the errors as a [SARIF 2.1.0](https://sarifweb.azurewebsites.net/)
+    /// log, for code scanning tools.
+    ///
+    /// Rule ids are the diagnostic codes, such as `kdl::unquoted_value`.
+    /// Regions have byte offsets and one-based lines and columns, counted in
+    /// Unicode code points.
+    pub fn display_sarif<'a>(&'a self, path: &'a str) -> impl 'a + fmt::Display {
+        let source = self.source.as_ref();
+        Sarif(
+            self.located()
+                .map(move |(error, nodes)| error.report(path, source, nodes.copied())),
+        )
+    }
+}
+
+/// Displays the reports as JSON Lines.
+pub(crate) struct JsonLines<I>(pub(crate) I);
+
+impl<'a, I: Iterator<Item = Report<'a>> + Clone> fmt::Display for JsonLines<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in self.0.clone() {
+            write!(f, r#"{{"path":{},"#, Str(report.path))?;
+            write!(f, r#""severity":{},"#, Str(report.severity))?;
+            write!(f, r#""code":{},"#, Str(report.code))?;
+            write!(f, r#""message":{},"#, Str(report.message))?;
+            if let Some(nodes) = &report.nodes {
+                write!(f, r#""nodes":{},"#, Str(nodes))?;
+            }
+            match &report.help {
+                Some(help) => write!(f, r#""help":{},"#, Str(help))?,
+                None => f.write_str(r#""help":null,"#)?,
+            }
+            f.write_str(r#""labels":["#)?;
+            for (i, label) in report.labels.iter().flatten().enumerate() {
+                if i != 0 {
+                    f.write_str(",")?;
+                }
+                let region = Region::new(report.source, label);
+                write!(
+                    f,
+                    r#"{{"start":{},"end":{},"#,
+                    region.span.start, region.span.end
+                )?;
+                write!(
+                    f,
+                    r#""line":{},"column":{},"#,
+                    region.start.0, region.start.1
+                )?;
+                write!(
+                    f,
+                    r#""end_line":{},"end_column":{},"#,
+                    region.end.0, region.end.1
+                )?;
+                match &label.text {
+                    Some(text) => write!(f, r#""text":{}}}"#, Str(text))?,
+                    None => f.write_str(r#""text":null}"#)?,
+                }
+            }
+            f.write_str("]}\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays the reports as a SARIF log with a single run.
+pub(crate) struct Sarif<I>(pub(crate) I);
+
+impl<'a, I: Iterator<Item = Report<'a>> + Clone> fmt::Display for Sarif<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rules = Vec::<&Report<'_>>::new();
+        let reports: Vec<_> = self.0.clone().collect();
+        for report in &reports {
+            if !rules.iter().any(|rule| rule.code == report.code) {
+                rules.push(report);
+            }
+        }
+
+        f.write_str(r#"{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","#)?;
+        f.write_str(r#""version":"2.1.0","runs":[{"tool":{"driver":{"#)?;
+        write!(f, r#""name":{},"#, Str(env!("CARGO_PKG_NAME")))?;
+        write!(f, r#""version":{},"#, Str(env!("CARGO_PKG_VERSION")))?;
+        f.write_str(r#""rules":["#)?;
+        for (n, report) in rules.iter().enumerate() {
+            if n != 0 {
+                f.write_str(",")?;
+            }
+            write!(f, r#"{{"id":{}"#, Str(report.code))?;
+            if let Some(help) = &report.help {
+                write!(f, r#","help":{{"text":{}}}"#, Str(help))?;
+            }
+            f.write_str("}")?;
+        }
+        f.write_str(r#"]}},"columnKind":"unicodeCodePoints","results":["#)?;
+
+        for (i, report) in reports.iter().enumerate() {
+            if i != 0 {
+                f.write_str(",")?;
+            }
+            let rule_index = (rules.iter())
+                .position(|rule| rule.code == report.code)
+                .unwrap_or_default();
+            write!(f, r#"{{"ruleId":{},"#, Str(report.code))?;
+            write!(f, r#""ruleIndex":{rule_index},"#)?;
+            write!(f, r#""level":{},"#, Str(report.severity))?;
+            write!(f, r#""message":{{"text":{}}},"#, Str(report.message))?;
+            let mut labels = report.labels.iter().flatten();
+            f.write_str(r#""locations":["#)?;
+            if let Some(primary) = labels.next() {
+                location(f, report, primary, None)?;
+            }
+            f.write_str(r#"],"relatedLocations":["#)?;
+            for (id, label) in labels.enumerate() {
+                if id != 0 {
+                    f.write_str(",")?;
+                }
+                location(f, report, label, Some(id))?;
+            }
+            if let Some(help) = &report.help {
+                write!(f, r#"],"properties":{{"help":{}}}}}"#, Str(help))?;
+            } else {
+                f.write_str("]}")?;
+            }
+        }
+        f.write_str("]}]}\n")
+    }
+}
+
+fn location(
+    f: &mut fmt::Formatter<'_>,
+    report: &Report<'_>,
+    label: &Label,
+    id: Option<usize>,
+) -> fmt::Result {
+    let region = Region::new(report.source, label);
+    f.write_str("{")?;
+    if let Some(id) = id {
+        write!(f, r#""id":{id},"#)?;
+    }
+    write!(
+        f,
+        r#""physicalLocation":{{"artifactLocation":{{"uri":{}}},"#,
+        Str(report.path)
+    )?;
+    write!(f, r#""region":{{"startLine":{},"#, region.start.0)?;
+    write!(f, r#""startColumn":{},"#, region.start.1)?;
+    write!(f, r#""endLine":{},"#, region.end.0)?;
+    write!(f, r#""endColumn":{},"#, region.end.1)?;
+    write!(f, r#""byteOffset":{},"#, region.span.start)?;
+    write!(f, r#""byteLength":{}}}}}"#, region.span.len())?;
+    if let Some(text) = &label.text {
+        write!(f, r#","message":{{"text":{}}}"#, Str(text))?;
+    }
+    f.write_str("}")
+}
+
+/// Where a label starts and ends, as byte offsets and one-based lines and
+/// columns.
+///
+/// Offsets are rounded down to character boundaries, as spans are not
+/// trusted to fall on them.
+struct Region {
+    span: Span,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl Region {
+    fn new(source: &str, label: &Label) -> Self {
+        let start = floor_char_boundary(source, label.span.start);
+        let end = floor_char_boundary(source, label.span.end).max(start);
+        Region {
+            span: Span::from(start..end),
+            start: Line::position(source, start),
+            end: Line::position(source, end),
+        }
+    }
+}
+
+/// Displays as a JSON string.
+struct Str<T>(T);
+
+impl<T: fmt::Display> fmt::Display for Str<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+        write!(Escape(f), "{}", self.0)?;
+        f.write_str("\"")
+    }
+}
+
+/// Escapes JSON string contents.
+struct Escape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for Escape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '"' => self.0.write_str(r#"\""#)?,
+                '\\' => self.0.write_str(r"\\")?,
+                '\n' => self.0.write_str(r"\n")?,
+                '\r' => self.0.write_str(r"\r")?,
+                '\t' => self.0.write_str(r"\t")?,
+                c if c.is_control() => write!(self.0, r"\u{:04x}", c as u32)?,
+                c => self.0.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
 use {
-    crate::{visit, visit_kdl_string, ParseError},
+    crate::{visit, visit_kdl_string, NodePath, ParseError},
     alloc::{borrow::Cow, string::String, vec::Vec},
     core::{fmt, str::FromStr},
     displaydoc::Display,
 };
 
+#[cfg(feature = "miette")]
+use {super::path::PathNote, ref_cast::RefCast};
+
 #[cfg(not(feature = "miette"))]
 mod hidden {
     use super::*;
@@ -12,30 +15,62 @@ mod hidden {
     impl SourceCode for &'_ str {}
     impl SourceCode for String {}
     impl SourceCode for Cow<'_, str> {}
+    impl SourceCode for alloc::sync::Arc<str> {}
 }
 
 #[cfg(not(feature = "miette"))]
-use hidden::SourceCode;
+pub(crate) use hidden::SourceCode;
 #[cfg(feature = "miette")]
-use miette::SourceCode;
+pub(crate) use miette::SourceCode;
 
 /// A collection of errors that occurred during parsing KDL.
 #[derive(Debug, Display, Clone)]
-#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
 #[displaydoc("errors occured while parsing")]
 pub struct ParseErrors<Source: fmt::Debug + SourceCode = String> {
-    #[cfg_attr(feature = "miette", source_code)]
     pub source: Source,
-    #[cfg_attr(feature = "miette", related)]
     pub errors: Vec<ParseError>,
+    /// The names of the nodes whose children each of the `errors` occurred
+    /// in, in the same order, as found by [`ParseError::path`]. Empty paths,
+    /// and errors without a path here, are at the top level.
+    pub paths: Vec<NodePath>,
 }
 
 #[cfg(feature = "std")]
 impl<Source: fmt::Debug + SourceCode> std::error::Error for ParseErrors<Source> {}
 
+#[cfg(feature = "miette")]
+impl<Source: fmt::Debug + SourceCode> miette::Diagnostic for ParseErrors<Source> {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source)
+    }
+
+    /// The errors, each followed by a note of the nodes it is in, if any.
+    fn related<'a>(
+        &'a self,
+    ) -> Option<std::boxed::Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+        Some(std::boxed::Box::new(self.located().flat_map(
+            |(error, path)| {
+                let note = path.map(|path| PathNote::ref_cast(path) as &dyn miette::Diagnostic);
+                core::iter::once(error as &dyn miette::Diagnostic).chain(note)
+            },
+        )))
+    }
+}
+
+impl<Source: fmt::Debug + SourceCode> ParseErrors<Source> {
+    /// The errors, each with the nodes it is in, if any.
+    pub(crate) fn located(&self) -> impl Iterator<Item = (&ParseError, Option<&NodePath>)> + Clone {
+        let paths = (self.paths.iter())
+            .map(|path| (!path.is_empty()).then_some(path))
+            .chain(core::iter::repeat(None));
+        self.errors.iter().zip(paths)
+    }
+}
+
 #[cfg(feature = "render")]
 impl<Source: fmt::Debug + SourceCode> ParseErrors<Source> {
+    /// Render the errors with miette, noting which nodes each is in.
     fn render_impl(
         &self,
         theme: miette::GraphicalTheme,
@@ -74,19 +109,71 @@ impl ParseErrors<Cow<'_, str>> {
         ParseErrors {
             source: self.source.into_owned(),
             errors: self.errors,
+            paths: self.paths,
         }
     }
 }
 
+/// The path of each error in the source, found in one pass over it.
+pub(crate) fn error_paths(source: &str, errors: &[ParseError]) -> Vec<NodePath> {
+    let mut order: Vec<usize> = (0..errors.len()).collect();
+    order.sort_by_key(|&i| errors[i].span().start);
+    let mut found: Vec<_> = (order.iter())
+        .map(|&i| (errors[i].span().start, NodePath::EMPTY))
+        .collect();
+    crate::parse::paths_at(source, &mut found);
+
+    let mut paths = vec![NodePath::EMPTY; errors.len()];
+    for (i, (_, path)) in order.into_iter().zip(found) {
+        paths[i] = path;
+    }
+    paths
+}
+
 impl<'kdl> ParseErrors<&'kdl str> {
+    /// Collect the errors in the source, or `Err(())` if there are none.
+    ///
+    /// Errors which are likely caused by an earlier error are left out:
+    /// those repeating an earlier error at the same place, and those
+    /// inside a string or children block an earlier error reports as
+    /// unclosed. To stop after some number of errors, use
+    /// [`from_str_with_limit`](Self::from_str_with_limit).
     #[allow(clippy::should_implement_trait, clippy::result_unit_err)]
     pub fn from_str(source: &'kdl str) -> Result<Self, ()> {
+        Self::from_str_with_limit(source, usize::MAX)
+    }
+
+    /// Collect the errors in the source as with
+    /// [`from_str`](Self::from_str), or `Err(())` if there are none, but
+    /// after `limit` errors, stop with a final
+    /// [`TooManyErrors`](ParseError::TooManyErrors).
+    ///
+    /// ```
+    /// # use kdl_visit::ParseErrors;
+    /// let errors = ParseErrors::from_str_with_limit("a x=y\nb x=y\nc x=y", 2).unwrap();
+    /// let messages: Vec<_> = errors.errors.iter().map(|error| error.to_string()).collect();
+    /// assert_eq!(
+    ///     messages,
+    ///     [
+    ///         "node value strings must be quoted",
+    ///         "node value strings must be quoted",
+    ///         "too many errors; stopped after 2",
+    ///     ],
+    /// );
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn from_str_with_limit(source: &'kdl str, limit: usize) -> Result<Self, ()> {
         let mut errors = vec![];
-        let _ = visit_kdl_string(source, CollectErrors::new(&mut errors));
+        let _ = visit_kdl_string(source, CollectErrors::new(&mut errors, limit));
         if errors.is_empty() {
             Err(())
         } else {
-            Ok(ParseErrors { source, errors })
+            let paths = error_paths(source, &errors);
+            Ok(ParseErrors {
+                source,
+                errors,
+                paths,
+            })
         }
     }
 }
@@ -94,121 +181,136 @@ impl<'kdl> ParseErrors<&'kdl str> {
 impl FromStr for ParseErrors {
     type Err = ();
     fn from_str(source: &str) -> Result<Self, Self::Err> {
-        let errors = ParseErrors::from_str(source);
+        let errors = ParseErrors::from_str(source)?;
         Ok(ParseErrors {
-            errors: errors?.errors,
             source: source.into(),
+            errors: errors.errors,
+            paths: errors.paths,
         })
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct CollectErrors<'a> {
-    errors: Option<&'a mut Vec<ParseError>>,
+    errors: &'a mut Vec<ParseError>,
+    limit: usize,
 }
 
 impl<'a> CollectErrors<'a> {
-    fn new(errors: &'a mut Vec<ParseError>) -> Self {
-        Self {
-            errors: Some(errors),
-        }
+    fn new(errors: &'a mut Vec<ParseError>, limit: usize) -> Self {
+        Self { errors, limit }
     }
 
-    fn inner(&mut self) -> &mut Vec<ParseError> {
-        self.errors
-            .as_mut()
-            .expect("kdl visitor should not be called while visiting a child component")
+    fn child(&mut self) -> CollectErrors<'_> {
+        CollectErrors {
+            errors: self.errors,
+            limit: self.limit,
+        }
     }
 
-    fn push(&mut self, error: ParseError) {
-        self.inner().push(error);
+    fn push(&mut self, error: ParseError) -> Result<(), ParseError> {
+        if follows_from(self.errors, &error) {
+            return Ok(());
+        }
+        if self.errors.len() >= self.limit {
+            let note = ParseError::TooManyErrors {
+                span: error.span(),
+                limit: self.limit,
+                _private: (),
+            };
+            self.errors.push(note);
+            return Err(note);
+        }
+        self.errors.push(error);
+        Ok(())
     }
 }
 
-impl visit::Document<'_> for CollectErrors<'_> {
+/// Whether the error is likely caused by one already collected: it is the
+/// same kind of error at the same span, or inside a string or children block
+/// left unclosed.
+fn follows_from(errors: &[ParseError], error: &ParseError) -> bool {
+    let span = error.span();
+    errors.iter().any(|earlier| match *earlier {
+        ParseError::UnclosedString { span: open, .. }
+        | ParseError::UnclosedRawString { span: open, .. } => open.start <= span.start,
+        ParseError::UnclosedChildren {
+            span: open, end, ..
+        } => open.start <= span.start && span.start < end.start,
+        _ => {
+            core::mem::discriminant(earlier) == core::mem::discriminant(error)
+                && earlier.span() == span
+        }
+    })
+}
+
+impl visit::LendDocument<'_> for CollectErrors<'_> {
     type Output = ();
 
     fn finish(self) {}
-    fn finish_error(mut self, error: ParseError) -> Result<(), ParseError> {
-        debug_assert_eq!(
-            self.inner().last().copied(),
-            Some(error),
+    fn finish_error(self, error: ParseError) -> Result<(), ParseError> {
+        debug_assert!(
+            self.errors.last() == Some(&error) || follows_from(self.errors, &error),
             "finish_error should be called with the last error"
         );
         Ok(())
     }
 }
 
-impl visit::Children<'_> for CollectErrors<'_> {
-    type VisitNode = Self;
+impl visit::LendChildren<'_> for CollectErrors<'_> {
+    type VisitNode<'a>
+        = CollectErrors<'a>
+    where
+        Self: 'a;
 
-    fn visit_node(&mut self) -> Self::VisitNode {
-        Self {
-            errors: self.errors.take(),
-        }
-    }
-
-    fn finish_node(&mut self, mut node: Self::VisitNode) {
-        self.errors = node.errors.take();
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child()
     }
 
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
-        self.push(error);
-        Ok(())
+        self.push(error)
     }
 }
 
-impl visit::Node<'_> for CollectErrors<'_> {
-    type VisitArgument = Self;
-    type VisitProperty = Self;
-    type VisitChildren = Self;
-
-    fn visit_argument(&mut self) -> Self::VisitArgument {
-        Self {
-            errors: self.errors.take(),
-        }
-    }
-
-    fn finish_argument(&mut self, mut argument: Self::VisitArgument) {
-        self.errors = argument.errors.take();
-    }
+impl visit::LendNode<'_> for CollectErrors<'_> {
+    type VisitArgument<'a>
+        = CollectErrors<'a>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = CollectErrors<'a>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = CollectErrors<'a>
+    where
+        Self: 'a;
 
-    fn visit_property(&mut self) -> Self::VisitProperty {
-        Self {
-            errors: self.errors.take(),
-        }
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child()
     }
 
-    fn finish_property(&mut self, mut property: Self::VisitProperty) {
-        self.errors = property.errors.take();
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child()
     }
 
-    fn visit_children(&mut self) -> Self::VisitChildren {
-        Self {
-            errors: self.errors.take(),
-        }
-    }
-
-    fn finish_children(&mut self, mut children: Self::VisitChildren) {
-        self.errors = children.errors.take();
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child()
     }
 
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
-        self.push(error);
-        Ok(())
+        self.push(error)
     }
 }
 
 impl visit::Argument<'_> for CollectErrors<'_> {
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
-        self.push(error);
-        Ok(())
+        self.push(error)
     }
 }
 
 impl visit::Property<'_> for CollectErrors<'_> {
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
-        self.push(error);
-        Ok(())
+        self.push(error)
     }
 }
@@ -1,9 +1,29 @@
+mod fix;
+#[cfg(feature = "alloc")]
+mod json;
 #[cfg(feature = "alloc")]
 mod many;
 mod one;
+mod path;
+mod plain;
+
+#[cfg(feature = "alloc")]
+pub(crate) use self::many::SourceCode;
+#[cfg(feature = "alloc")]
+pub use self::{fix::apply_fixes, many::ParseErrors};
+pub use self::{
+    fix::{Applicability, Suggestion},
+    one::ParseError,
+    path::NodePath,
+};
 
+#[cfg(feature = "ast")]
+pub(crate) use self::many::error_paths;
+pub(crate) use self::plain::{Label, Report, Text};
 #[cfg(feature = "alloc")]
-pub use self::many::ParseErrors;
-pub use self::one::ParseError;
+pub(crate) use self::{
+    json::{JsonLines, Sarif},
+    plain::Reports,
+};
 
 pub(crate) const ERROR_STRING: &str = r#""<error>""#;
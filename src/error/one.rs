@@ -1,4 +1,4 @@
-use {crate::Span, displaydoc::Display};
+use {super::NodePath, crate::Span, displaydoc::Display};
 
 /// An error that can be encountered while parsing KDL.
 ///
@@ -11,7 +11,8 @@ use {crate::Span, displaydoc::Display};
 /// - How errors in KDL are diagnosed may change to improve diagnostics.
 ///
 /// It is recommended to interact with errors just through the implementations
-/// of [`Display`](core::fmt::Display) and [`Diagnostic`](miette::Diagnostic).
+/// of [`Display`](core::fmt::Display) and [`Diagnostic`](miette::Diagnostic),
+/// or [`display_plain`](ParseError::display_plain) without miette.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Debug, Display, Copy, Clone, PartialEq)]
 #[non_exhaustive]
@@ -62,6 +63,84 @@ pub enum ParseError {
         _private: (),
     },
 
+    /// A colon was used to separate a node's name or a property's key from
+    /// its value, as in YAML.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_colon_separator.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_colon_separator.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Remove the colon; nodes are separated from their arguments by
+    /// whitespace alone.
+    ///
+    /// ```kdl
+    /// name "my-app"
+    /// ```
+    ///
+    /// Within a node, replace the colon with `=` to make a property.
+    ///
+    /// ```kdl
+    /// server port=8080
+    /// ```
+    #[displaydoc("colons do not separate names from values")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::colon_separator),
+            help("separate node names from arguments with whitespace, and property keys from values with `=`")
+        )
+    )]
+    ColonSeparator {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[cfg_attr(feature = "miette", label("this value also needs quotes"))]
+        #[doc(hidden)]
+        value: Option<Span>,
+        /// The colon and any whitespace after it, when they separate a
+        /// property key from its value and should be replaced by `=`.
+        #[doc(hidden)]
+        equals: Option<Span>,
+    },
+
+    /// A comma was used to separate values, as in JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_comma_separator.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_comma_separator.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Remove the commas.
+    ///
+    /// ```kdl
+    /// ports 80 443 8080
+    /// ```
+    #[displaydoc("commas do not separate values")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(kdl::comma_separator), help("separate values with whitespace"))
+    )]
+    CommaSeparator {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[doc(hidden)]
+        remove: Option<Span>,
+    },
+
     /// A line continuation was found that was not followed by a newline.
     ///
     /// # Example
@@ -375,6 +454,40 @@ pub enum ParseError {
         here: usize,
     },
 
+    /// A node was assigned a value with `=`, as in TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_node_assignment.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_node_assignment.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Remove the `=`; a node's value is written as an argument.
+    ///
+    /// ```kdl
+    /// title "KDL"
+    /// ```
+    #[displaydoc("nodes are not assigned values with `=`")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::node_assignment),
+            help("write the value as an argument of the node")
+        )
+    )]
+    NodeAssignment {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[doc(hidden)]
+        remove: Option<Span>,
+    },
+
     /// A number exceeded implementation limits. Only emitted when parsing to an
     /// AST; the visitor does not emit this error by itself.
     ///
@@ -408,6 +521,253 @@ pub enum ParseError {
         why: &'static str,
     },
 
+    /// A character reserved by KDL was found outside of a string.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_reserved_character.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_reserved_character.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Quote the string or identifier containing the character.
+    ///
+    /// ```kdl
+    /// node "<value>"
+    /// ```
+    #[displaydoc("`{character}` is a reserved character")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::reserved_character),
+            help("quote the string or identifier containing it")
+        )
+    )]
+    ReservedCharacter {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[doc(hidden)]
+        character: char,
+    },
+
+    /// A section header was found, as in TOML or INI.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_section_header.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_section_header.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Put the section's nodes in the children block of a node named after
+    /// the section.
+    ///
+    /// ```kdl
+    /// server {
+    /// host "localhost"
+    /// port 8080
+    /// }
+    /// ```
+    #[displaydoc("section headers are not supported")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::section_header),
+            help("put the section's nodes in the children block of a node")
+        )
+    )]
+    SectionHeader {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[doc(hidden)]
+        name: Span,
+        #[doc(hidden)]
+        end: usize,
+    },
+
+    /// A string was quoted with single quotes.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_single_quotes.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_single_quotes.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Use double quotes.
+    ///
+    /// ```kdl
+    /// greeting "hello world"
+    /// ```
+    #[displaydoc("strings must be quoted with double quotes")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::single_quotes),
+            help("replace the single quotes with double quotes")
+        )
+    )]
+    SingleQuotedString {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[doc(hidden)]
+        _private: (),
+    },
+
+    /// A string was quoted with curly "smart" quotes.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_smart_quotes.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_smart_quotes.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Use straight double quotes.
+    ///
+    /// ```kdl
+    /// greeting "hello world"
+    /// ```
+    #[displaydoc("strings must be quoted with straight double quotes")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::smart_quotes),
+            help("replace the curly quotes with straight double quotes")
+        )
+    )]
+    SmartQuotedString {
+        #[cfg_attr(feature = "miette", label)]
+        span: Span,
+        #[doc(hidden)]
+        open: Span,
+        #[doc(hidden)]
+        close: Span,
+    },
+
+    /// So many errors were found that collecting them stopped, so that the
+    /// errors caused by earlier ones don't bury the rest.
+    ///
+    /// This is the last of the errors collected by
+    /// [`ParseErrors::from_str_with_limit`](crate::ParseErrors::from_str_with_limit),
+    /// pointing at the first error left out.
+    ///
+    /// # Potential fixes
+    ///
+    /// Fix the errors before it, starting from the first, and parse again.
+    #[displaydoc("too many errors; stopped after {limit}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::too_many_errors),
+            help("fix the errors above and parse again")
+        )
+    )]
+    TooManyErrors {
+        #[cfg_attr(feature = "miette", label("stopped here"))]
+        span: Span,
+        /// How many errors were collected before stopping.
+        limit: usize,
+        #[doc(hidden)]
+        _private: (),
+    },
+
+    /// A block comment was not closed, commenting out the rest of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_unclosed_block_comment.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_unclosed_block_comment.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Close the comment with `*/`. Block comments nest, so each `/*` inside
+    /// the comment needs its own `*/`.
+    ///
+    /// ```kdl
+    /// node /* a comment */
+    /// ```
+    #[displaydoc("unclosed block comment")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(kdl::unclosed_comment), help("close the comment with `*/`"))
+    )]
+    UnclosedBlockComment {
+        #[cfg_attr(feature = "miette", label("opened here"))]
+        span: Span,
+        #[doc(hidden)]
+        _private: (),
+    },
+
+    /// A children block was not closed.
+    ///
+    /// When a `}` is less indented than the node whose block it would close,
+    /// but lines up with an enclosing node, it is taken to close the enclosing
+    /// node's block instead, leaving the inner block unclosed.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_unclosed_children.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_unclosed_children.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Add a closing curly brace where the block was meant to end.
+    ///
+    /// ```kdl
+    /// server {
+    ///     routes {
+    ///         get "/"
+    ///     }
+    ///     tls true
+    /// }
+    /// ```
+    #[displaydoc("unclosed children block")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(kdl::unclosed_children), help("add a closing curly brace"))
+    )]
+    UnclosedChildren {
+        #[cfg_attr(feature = "miette", label("opened here"))]
+        span: Span,
+        #[cfg_attr(feature = "miette", label("still open here"))]
+        #[doc(hidden)]
+        end: Span,
+        #[cfg_attr(feature = "miette", label("the indentation suggests it closes here"))]
+        #[doc(hidden)]
+        dedent: Option<Span>,
+    },
+
     /// A string literal was not closed.
     ///
     /// # Examples
@@ -465,6 +825,42 @@ pub enum ParseError {
         span2: Option<Span>,
     },
 
+    /// A closing curly brace was found without a children block to close.
+    ///
+    /// # Examples
+    ///
+    /// ```kdl
+    #[doc = include_str!("../../tests/corpus/error_unmatched_close_brace.kdl")]
+    /// ```
+    ///
+    /// ```text
+    #[doc = include_str!("../../tests/examples/error_unmatched_close_brace.stderr")]
+    /// ```
+    ///
+    /// # Potential fixes
+    ///
+    /// Remove the brace, or add the opening curly brace it was meant to match.
+    ///
+    /// ```kdl
+    /// node {
+    ///     child
+    /// }
+    /// ```
+    #[displaydoc("unmatched closing curly brace")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(kdl::unmatched_brace),
+            help("remove the brace, or add a matching opening curly brace")
+        )
+    )]
+    UnmatchedCloseBrace {
+        #[cfg_attr(feature = "miette", label("no children block to close"))]
+        span: Span,
+        #[doc(hidden)]
+        _private: (),
+    },
+
     /// A value such as a number, boolean, or null was used as a property key.
     ///
     /// # Examples
@@ -534,3 +930,26 @@ pub enum ParseError {
 
 #[cfg(feature = "std")]
 impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// The names of the nodes whose children the error occurred in,
+    /// outermost first, or `None` if it occurred at the top level.
+    ///
+    /// To keep errors small, the path is not stored in them, but found by
+    /// parsing `source`, which the error was reported for, up to the error.
+    /// [`ParseErrors`](crate::ParseErrors) finds the paths of all its errors
+    /// in one pass, as its `paths`.
+    ///
+    /// ```
+    /// # use kdl_visit::visit_kdl_string;
+    /// let source = "services {\n    web {\n        env key=value\n    }\n}";
+    /// let error = visit_kdl_string(source, ()).unwrap_err();
+    /// let path = error.path(source).unwrap();
+    /// assert_eq!(path.as_str(), "services > web");
+    /// assert_eq!(format!("{error}{path:#}"), "node value strings must be quoted (in services > web)");
+    /// ```
+    pub fn path(&self, source: &str) -> Option<NodePath> {
+        let path = crate::parse::path_at(source, self.span().start);
+        (!path.is_empty()).then_some(path)
+    }
+}
@@ -0,0 +1,128 @@
+use core::fmt;
+
+const CAPACITY: usize = 63;
+const ELLIPSIS: &str = "…";
+const SEPARATOR: &str = " > ";
+
+/// The names of the nodes enclosing where a [`ParseError`](crate::ParseError)
+/// occurred, outermost first, such as `services > web > env`, as found by
+/// [`ParseError::path`](crate::ParseError::path).
+///
+/// The path is stored inline, so that it needs no allocator. Paths too long
+/// to fit are shortened from the outermost end, as in `… > web > env`.
+///
+/// Displays as the path; the alternate form (`{:#}`) displays as a suffix for
+/// error messages, ` (in services > web > env)`, or nothing if it is empty.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodePath {
+    len: u8,
+    text: [u8; CAPACITY],
+}
+
+impl NodePath {
+    /// The path outside of any node.
+    pub const EMPTY: Self = NodePath {
+        len: 0,
+        text: [0; CAPACITY],
+    };
+
+    /// Build the path from the names of the enclosing nodes, innermost first.
+    pub(crate) fn from_innermost<'a>(names: impl Iterator<Item = &'a str>) -> Self {
+        let mut path = Self::EMPTY;
+        let mut start = CAPACITY;
+        let mut prepend = |start: &mut usize, text: &str| {
+            *start -= text.len();
+            path.text[*start..][..text.len()].copy_from_slice(text.as_bytes());
+        };
+
+        let mut names = names.peekable();
+        while let Some(name) = names.next() {
+            let separator = if start == CAPACITY { "" } else { SEPARATOR };
+            let reserve = match names.peek() {
+                Some(_) => ELLIPSIS.len() + SEPARATOR.len(),
+                None => 0,
+            };
+            if name.len() + separator.len() + reserve <= start {
+                prepend(&mut start, separator);
+                prepend(&mut start, name);
+            } else if start == CAPACITY {
+                // the innermost name alone is too long; keep its end
+                let fits = CAPACITY - ELLIPSIS.len() - reserve;
+                let mut keep = name.len().saturating_sub(fits);
+                while !name.is_char_boundary(keep) {
+                    keep += 1;
+                }
+                prepend(&mut start, &name[keep..]);
+                prepend(&mut start, ELLIPSIS);
+                if reserve != 0 {
+                    prepend(&mut start, SEPARATOR);
+                    prepend(&mut start, ELLIPSIS);
+                }
+                break;
+            } else {
+                prepend(&mut start, separator);
+                prepend(&mut start, ELLIPSIS);
+                break;
+            }
+        }
+
+        path.text.copy_within(start.., 0);
+        path.len = (CAPACITY - start) as u8;
+        path
+    }
+
+    /// The path as text, with names separated by ` > `.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.text[..usize::from(self.len)]).unwrap_or_default()
+    }
+
+    /// Whether the path has no names, as outside of any node.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for NodePath {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl fmt::Debug for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (f.alternate(), self.is_empty()) {
+            (true, true) => Ok(()),
+            (true, false) => write!(f, " (in {})", self.as_str()),
+            (false, _) => f.write_str(self.as_str()),
+        }
+    }
+}
+
+/// Advice pointing out which node an error occurred in.
+#[cfg(feature = "miette")]
+#[derive(Debug, ref_cast::RefCast)]
+#[repr(transparent)]
+pub(crate) struct PathNote(pub(crate) NodePath);
+
+#[cfg(feature = "miette")]
+impl fmt::Display for PathNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in {}", self.0)
+    }
+}
+
+#[cfg(feature = "miette")]
+impl std::error::Error for PathNote {}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for PathNote {
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Advice)
+    }
+}
@@ -0,0 +1,480 @@
+#[cfg(feature = "alloc")]
+use {super::many::SourceCode, crate::ParseErrors};
+use {
+    crate::{parse::NEWLINE_CHARS, NodePath, ParseError, Span},
+    core::fmt,
+};
+
+/// Label or help text of a diagnostic, formatted when displayed so that it
+/// can be rendered without allocating.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Text {
+    Str(&'static str),
+    Found(&'static str),
+    Expected(&'static str),
+    OpenedWith(usize),
+    BestEnd(usize),
+    Use(char),
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let es = |n| if n != 1 { "es" } else { "" };
+        match *self {
+            Text::Str(s) => f.write_str(s),
+            Text::Found(found) => write!(f, "found {found}"),
+            Text::Expected(expected) => write!(f, "expected {expected}"),
+            Text::OpenedWith(n) => write!(f, "opened with {n} hash{}", es(n)),
+            Text::BestEnd(n) => {
+                write!(f, "this is the best possible end with {n} hash{}", es(n))
+            }
+            Text::Use(c) => write!(f, "use {c:?}"),
+        }
+    }
+}
+
+/// A span of source highlighted by a diagnostic.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Label {
+    pub(crate) span: Span,
+    pub(crate) text: Option<Text>,
+}
+
+impl Label {
+    pub(crate) fn new(span: Span, text: Option<Text>) -> Self {
+        Label { span, text }
+    }
+}
+
+impl ParseError {
+    /// The diagnostic code, e.g. `kdl::bare_value`.
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            ParseError::BareValue { .. } => "kdl::bare_value",
+            ParseError::ColonSeparator { .. } => "kdl::colon_separator",
+            ParseError::CommaSeparator { .. } => "kdl::comma_separator",
+            ParseError::EscapedContent { .. } => "kdl::escaped_content",
+            ParseError::EscapedEof { .. } => "kdl::escaped_eof",
+            ParseError::Generic { .. } => "kdl::unexpected",
+            ParseError::InvalidStringEscape { .. } => "kdl::invalid_escape",
+            ParseError::InvalidWhitespaceAfterType { .. } => "kdl::whitespace::after_type",
+            ParseError::InvalidWhitespaceInProperty { .. } => "kdl::whitespace::in_property",
+            ParseError::InvalidWhitespaceInType { .. } => "kdl::whitespace::in_type",
+            ParseError::MissingValue { .. } => "kdl::missing_value",
+            ParseError::MissingWhitespaceBeforeArgument { .. } => {
+                "kdl::whitespace::before_argument"
+            }
+            ParseError::MissingWhitespaceBeforeProperty { .. } => {
+                "kdl::whitespace::before_property"
+            }
+            ParseError::NodeAssignment { .. } => "kdl::node_assignment",
+            #[cfg(feature = "decimal")]
+            ParseError::NumberOutOfRange { .. } => "kdl::number_out_of_range",
+            ParseError::ReservedCharacter { .. } => "kdl::reserved_character",
+            ParseError::SectionHeader { .. } => "kdl::section_header",
+            ParseError::SingleQuotedString { .. } => "kdl::single_quotes",
+            ParseError::SmartQuotedString { .. } => "kdl::smart_quotes",
+            ParseError::TooManyErrors { .. } => "kdl::too_many_errors",
+            ParseError::UnclosedBlockComment { .. } => "kdl::unclosed_comment",
+            ParseError::UnclosedChildren { .. } => "kdl::unclosed_children",
+            ParseError::UnclosedString { .. } => "kdl::unclosed_string",
+            ParseError::UnclosedRawString { .. } => "kdl::unclosed_string",
+            ParseError::UnmatchedCloseBrace { .. } => "kdl::unmatched_brace",
+            ParseError::UnquotedPropertyName { .. } => "kdl::value_as_property_key",
+            ParseError::UnquotedValue { .. } => "kdl::unquoted_value",
+        }
+    }
+
+    pub(crate) fn help(&self) -> Option<Text> {
+        let help = match *self {
+            ParseError::BareValue { .. } => "put the value inside a node",
+            ParseError::ColonSeparator { .. } => {
+                "separate node names from arguments with whitespace, and property keys from values with `=`"
+            }
+            ParseError::CommaSeparator { .. } => "separate values with whitespace",
+            ParseError::EscapedEof { .. } => "remove the line continuation",
+            ParseError::Generic { expected, .. } => return Some(Text::Expected(expected)),
+            ParseError::InvalidStringEscape { .. } => {
+                r#"valid escapes are \n, \r, \t, \\, \", \b, \f, and \u{XXXX}"#
+            }
+            ParseError::InvalidWhitespaceAfterType { .. }
+            | ParseError::InvalidWhitespaceInProperty { .. }
+            | ParseError::InvalidWhitespaceInType { .. } => "remove the whitespace",
+            ParseError::MissingValue { .. } => "add a value",
+            ParseError::NodeAssignment { .. } => "write the value as an argument of the node",
+            #[cfg(feature = "decimal")]
+            ParseError::NumberOutOfRange { .. } => "precision between 1e-20 and 1e+20 is supported",
+            ParseError::ReservedCharacter { .. } => "quote the string or identifier containing it",
+            ParseError::SectionHeader { .. } => {
+                "put the section's nodes in the children block of a node"
+            }
+            ParseError::SingleQuotedString { .. } => "replace the single quotes with double quotes",
+            ParseError::SmartQuotedString { .. } => {
+                "replace the curly quotes with straight double quotes"
+            }
+            ParseError::TooManyErrors { .. } => "fix the errors above and parse again",
+            ParseError::UnclosedBlockComment { .. } => "close the comment with `*/`",
+            ParseError::UnclosedChildren { .. } => "add a closing curly brace",
+            ParseError::UnmatchedCloseBrace { .. } => {
+                "remove the brace, or add a matching opening curly brace"
+            }
+            ParseError::UnquotedPropertyName { .. } => "this is a value; try quoting it",
+            ParseError::UnquotedValue { .. } => "add quotes around the name",
+            ParseError::EscapedContent { .. }
+            | ParseError::MissingWhitespaceBeforeArgument { .. }
+            | ParseError::MissingWhitespaceBeforeProperty { .. }
+            | ParseError::UnclosedString { .. }
+            | ParseError::UnclosedRawString { .. } => return None,
+        };
+        Some(Text::Str(help))
+    }
+
+    /// Where the error is, as highlighted by its primary label.
+    pub(crate) fn span(&self) -> Span {
+        let [primary, ..] = self.labels();
+        primary.map_or(Span::from(0..0), |label| label.span)
+    }
+
+    /// The highlighted spans, primary first.
+    pub(crate) fn labels(&self) -> [Option<Label>; 3] {
+        let label = |span, text| Some(Label::new(span, text));
+        match *self {
+            ParseError::BareValue { span, .. }
+            | ParseError::CommaSeparator { span, .. }
+            | ParseError::EscapedEof { span, .. }
+            | ParseError::InvalidStringEscape { span, .. }
+            | ParseError::InvalidWhitespaceAfterType { span, .. }
+            | ParseError::MissingValue { span, .. }
+            | ParseError::NodeAssignment { span, .. }
+            | ParseError::ReservedCharacter { span, .. }
+            | ParseError::SectionHeader { span, .. }
+            | ParseError::SingleQuotedString { span, .. }
+            | ParseError::SmartQuotedString { span, .. }
+            | ParseError::UnquotedPropertyName { span, .. }
+            | ParseError::UnquotedValue { span, .. } => [label(span, None), None, None],
+            ParseError::ColonSeparator { span, value, .. } => [
+                label(span, None),
+                value.and_then(|value| {
+                    label(value, Some(Text::Str("this value also needs quotes")))
+                }),
+                None,
+            ],
+            ParseError::EscapedContent { escape, span } => [
+                label(escape, Some(Text::Str("this line continuation"))),
+                label(span, Some(Text::Str("try commenting this out"))),
+                None,
+            ],
+            ParseError::Generic { span, found, .. } => {
+                [label(span, Some(Text::Found(found))), None, None]
+            }
+            ParseError::InvalidWhitespaceInProperty { span, span2 }
+            | ParseError::InvalidWhitespaceInType { span, span2 } => [
+                label(span, None),
+                span2.and_then(|span2| label(span2, None)),
+                None,
+            ],
+            ParseError::MissingWhitespaceBeforeArgument { span, here } => [
+                label(
+                    (here..here).into(),
+                    Some(Text::Str("whitespace needed here")),
+                ),
+                label(span, Some(Text::Str("before this argument"))),
+                None,
+            ],
+            ParseError::MissingWhitespaceBeforeProperty { span, here } => [
+                label(
+                    (here..here).into(),
+                    Some(Text::Str("whitespace needed here")),
+                ),
+                label(span, Some(Text::Str("before this property"))),
+                None,
+            ],
+            #[cfg(feature = "decimal")]
+            ParseError::NumberOutOfRange { span, why } => {
+                [label(span, Some(Text::Str(why))), None, None]
+            }
+            ParseError::TooManyErrors { span, .. } => {
+                [label(span, Some(Text::Str("stopped here"))), None, None]
+            }
+            ParseError::UnclosedBlockComment { span, .. }
+            | ParseError::UnclosedString { span, .. } => {
+                [label(span, Some(Text::Str("opened here"))), None, None]
+            }
+            ParseError::UnclosedChildren { span, end, dedent } => [
+                label(span, Some(Text::Str("opened here"))),
+                label(end, Some(Text::Str("still open here"))),
+                dedent.and_then(|dedent| {
+                    label(
+                        dedent,
+                        Some(Text::Str("the indentation suggests it closes here")),
+                    )
+                }),
+            ],
+            ParseError::UnclosedRawString { span, span2 } => [
+                label(span, Some(Text::OpenedWith(span.len() - 2))),
+                span2.and_then(|end| label(end, Some(Text::BestEnd(end.len() - 1)))),
+                None,
+            ],
+            ParseError::UnmatchedCloseBrace { span, .. } => [
+                label(span, Some(Text::Str("no children block to close"))),
+                None,
+                None,
+            ],
+        }
+    }
+
+    /// Display the error rustc-style, as `path:line:col: error[code]: message`
+    /// followed by the source lines it highlights.
+    ///
+    /// Unlike `ParseErrors::render`, this does not need miette or std.
+    ///
+    /// ```
+    /// # use kdl_visit::{visit_kdl_string, ParseError};
+    /// let source = "node key=value";
+    /// let error = visit_kdl_string(source, ()).unwrap_err();
+    /// assert_eq!(
+    ///     error.display_plain("config.kdl", source).to_string(),
+    ///     "\
+    /// config.kdl:1:10: error[kdl::unquoted_value]: node value strings must be quoted
+    ///   |
+    /// 1 | node key=value
+    ///   |          ^^^^^
+    ///   = help: add quotes around the name
+    /// ",
+    /// );
+    /// ```
+    pub fn display_plain<'a>(&'a self, path: &'a str, source: &'a str) -> impl 'a + fmt::Display {
+        self.report(path, source, self.path(source))
+    }
+
+    /// The report of the error, which is in the `nodes`.
+    pub(crate) fn report<'a>(
+        &'a self,
+        path: &'a str,
+        source: &'a str,
+        nodes: Option<NodePath>,
+    ) -> Report<'a> {
+        Report {
+            path,
+            source,
+            severity: "error",
+            code: self.code(),
+            message: self,
+            labels: self.labels(),
+            nodes,
+            help: self.help(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<Source: fmt::Debug + SourceCode + AsRef<str>> ParseErrors<Source> {
+    /// Display the errors [rustc-style](ParseError::display_plain), separated
+    /// by blank lines.
+    pub fn display_plain<'a>(&'a self, path: &'a str) -> impl 'a + fmt::Display {
+        let source = self.source.as_ref();
+        Reports(
+            self.located()
+                .map(move |(error, nodes)| error.report(path, source, nodes.copied())),
+        )
+    }
+}
+
+/// Displays each item of the iterator, separated by blank lines.
+#[cfg(feature = "alloc")]
+pub(crate) struct Reports<I>(pub(crate) I);
+
+#[cfg(feature = "alloc")]
+impl<I, D> fmt::Display for Reports<I>
+where
+    I: Iterator<Item = D> + Clone,
+    D: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, report) in self.0.clone().enumerate() {
+            if i != 0 {
+                f.write_str("\n")?;
+            }
+            report.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A diagnostic in a source file, which displays as plain text.
+pub(crate) struct Report<'a> {
+    pub(crate) path: &'a str,
+    pub(crate) source: &'a str,
+    pub(crate) severity: &'static str,
+    pub(crate) code: &'static str,
+    pub(crate) message: &'a dyn fmt::Display,
+    pub(crate) labels: [Option<Label>; 3],
+    /// The nodes whose children the diagnostic is in.
+    pub(crate) nodes: Option<NodePath>,
+    pub(crate) help: Option<Text>,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Report {
+            path,
+            source,
+            severity,
+            code,
+            message,
+            labels,
+            nodes,
+            help,
+        } = self;
+        let mut labels = *labels;
+        let (line, col) = match &labels[0] {
+            Some(primary) => Line::position(source, primary.span.start),
+            None => (1, 1),
+        };
+        writeln!(f, "{path}:{line}:{col}: {severity}[{code}]: {message}")?;
+
+        labels.sort_unstable_by_key(|label| label.map(|label| label.span.start));
+        let labels = labels.iter().flatten();
+        let last = labels
+            .clone()
+            .last()
+            .map(|label| Line::of(source, label.span.start));
+        let width = last.map_or(1, |line| digits(line.number));
+        let gutter = |f: &mut fmt::Formatter<'_>| write!(f, "{:width$} |", "");
+
+        if last.is_some() {
+            gutter(f)?;
+            f.write_str("\n")?;
+        }
+        let mut shown = None;
+        for label in labels {
+            let line = Line::of(source, label.span.start);
+            if shown != Some(line.number) {
+                write!(f, "{:>width$} |", line.number)?;
+                let text = &source[line.start..line.end];
+                if !text.is_empty() {
+                    f.write_str(" ")?;
+                    write_expanded(f, text)?;
+                }
+                f.write_str("\n")?;
+                shown = Some(line.number);
+            }
+            gutter(f)?;
+            f.write_str(" ")?;
+            // spans are not trusted to fall on character boundaries
+            let start = floor_char_boundary(source, label.span.start.min(line.end));
+            let end = floor_char_boundary(source, label.span.end.clamp(start, line.end));
+            for _ in 0..width_of(&source[line.start..start]) {
+                f.write_str(" ")?;
+            }
+            for _ in 0..width_of(&source[start..end]).max(1) {
+                f.write_str("^")?;
+            }
+            if let Some(text) = label.text {
+                write!(f, " {text}")?;
+            }
+            f.write_str("\n")?;
+        }
+        if let Some(nodes) = nodes {
+            writeln!(f, "{:width$} = note: in {nodes}", "")?;
+        }
+        if let Some(help) = help {
+            writeln!(f, "{:width$} = help: {help}", "")?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of source.
+#[derive(Clone, Copy)]
+pub(super) struct Line {
+    /// One-based line number.
+    number: usize,
+    start: usize,
+    /// Where the line ends, excluding the newline.
+    end: usize,
+}
+
+impl Line {
+    /// The line containing the offset, or the last line if it is out of range.
+    fn of(source: &str, offset: usize) -> Self {
+        let mut line = Line {
+            number: 1,
+            start: 0,
+            end: source.len(),
+        };
+        let mut chars = source.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if !NEWLINE_CHARS.contains(&c) {
+                continue;
+            }
+            if i >= offset {
+                line.end = i;
+                break;
+            }
+            if c == '\r' {
+                chars.next_if(|&(_, c)| c == '\n');
+            }
+            let next = chars.peek().map_or(source.len(), |&(i, _)| i);
+            if next > offset {
+                line.end = i;
+                break;
+            }
+            line.number += 1;
+            line.start = next;
+        }
+        line
+    }
+
+    /// The one-based line and column of the offset.
+    pub(super) fn position(source: &str, offset: usize) -> (usize, usize) {
+        let line = Line::of(source, offset);
+        (line.number, line.column(source, offset))
+    }
+
+    /// One-based column of the offset, in characters.
+    fn column(self, source: &str, offset: usize) -> usize {
+        let offset = floor_char_boundary(source, offset.clamp(self.start, source.len()));
+        source[self.start..offset].chars().count() + 1
+    }
+}
+
+/// The greatest character boundary in `source` at or before the offset, as
+/// in the unstable `str::floor_char_boundary`.
+pub(super) fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    if offset >= source.len() {
+        return source.len();
+    }
+    let mut offset = offset;
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+const TAB: &str = "    ";
+
+fn width_of(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c == '\t' { TAB.len() } else { 1 })
+        .sum()
+}
+
+fn write_expanded(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    for (i, part) in text.split('\t').enumerate() {
+        if i != 0 {
+            f.write_str(TAB)?;
+        }
+        f.write_str(part)?;
+    }
+    Ok(())
+}
+
+fn digits(mut n: usize) -> usize {
+    let mut digits = 1;
+    while n >= 10 {
+        n /= 10;
+        digits += 1;
+    }
+    digits
+}
@@ -14,13 +14,20 @@ extern crate std;
 #[cfg(feature = "ast")]
 pub mod ast;
 mod error;
+pub mod lint;
 mod parse;
 mod span;
 mod utils;
 pub mod visit;
 
+#[cfg(feature = "alloc")]
+pub(crate) use self::error::SourceCode;
 pub(crate) use self::error::ERROR_STRING;
-pub use self::{error::ParseError, parse::visit_kdl_string, span::Span};
+pub use self::{
+    error::{Applicability, NodePath, ParseError, Suggestion},
+    parse::{try_visit_kdl_string, visit_kdl_string, visit_kdl_string_dyn},
+    span::Span,
+};
 
 #[cfg(feature = "alloc")]
-pub use self::error::ParseErrors;
+pub use self::error::{apply_fixes, ParseErrors};
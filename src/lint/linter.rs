@@ -0,0 +1,710 @@
+use {
+    super::{Level, Lint, Rules, Warning},
+    crate::{parse::NEWLINE_CHARS, visit, ParseError, Span, ERROR_STRING},
+    alloc::{borrow::Cow, collections::BTreeMap, string::String, vec::Vec},
+    core::fmt::Write as _,
+};
+
+/// An adapter which checks the document against lint [`Rules`], reporting
+/// [`Warning`]s to the wrapped visitor's `visit_warning` before passing each
+/// callback on to it.
+///
+/// Warnings are reported to the visitor of the component they concern: e.g.
+/// a [`DuplicateSiblingName`](Lint::DuplicateSiblingName) to the node visitor
+/// just before it is finished, and a
+/// [`TrailingWhitespace`](Lint::TrailingWhitespace) to the visitor which
+/// visited the newline.
+///
+/// ```
+/// # use kdl_visit::{lint, visit, visit_kdl_string};
+/// #[derive(Default)]
+/// struct Lints(Vec<lint::Lint>);
+///
+/// impl visit::Document<'_> for Lints {
+///     type Output = Vec<lint::Lint>;
+///     fn finish(self) -> Self::Output {
+///         self.0
+///     }
+/// }
+///
+/// impl visit::Children<'_> for Lints {
+///     type VisitNode = Self;
+///     fn visit_node(&mut self) -> Self {
+///         Lints::default()
+///     }
+///     fn finish_node(&mut self, node: Self) {
+///         self.0.extend(node.0);
+///     }
+/// }
+///
+/// impl visit::Node<'_> for Lints {
+///     type VisitArgument = ();
+///     type VisitProperty = ();
+///     type VisitChildren = Self;
+///     fn visit_argument(&mut self) {}
+///     fn visit_property(&mut self) {}
+///     fn visit_children(&mut self) -> Self {
+///         Lints::default()
+///     }
+///     fn finish_children(&mut self, children: Self) {
+///         self.0.extend(children.0);
+///     }
+///     fn visit_warning(&mut self, warning: lint::Warning) {
+///         self.0.push(warning.lint());
+///     }
+/// }
+///
+/// let kdl = "node a=1 a=2 \n";
+/// let linter = lint::Linter::new(kdl, lint::Rules::new(), Lints::default());
+/// let lints = visit_kdl_string(kdl, linter)?;
+/// assert_eq!(lints, [lint::Lint::DuplicateProperty, lint::Lint::TrailingWhitespace]);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+#[derive(Debug)]
+pub struct Linter<'kdl, V> {
+    visitor: V,
+    state: State<'kdl>,
+    scope: Scope<'kdl>,
+}
+
+/// The visitor a [`Linter`] lends for each component, which borrows the
+/// linter's state and checks the component against its siblings when it is
+/// dropped.
+#[derive(Debug)]
+pub struct LintChild<'a, 'kdl, V> {
+    visitor: V,
+    /// The visitor's `visit_warning`, to report warnings when dropped.
+    warn: fn(&mut V, Warning),
+    state: &'a mut State<'kdl>,
+    /// The scope this component is checked against.
+    parent: &'a mut Scope<'kdl>,
+    kind: Kind,
+    /// Where this component starts.
+    start: usize,
+    /// The name of this node, or the key of this property, once visited.
+    name: Option<(Cow<'kdl, str>, Span)>,
+    scope: Scope<'kdl>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Node,
+    Argument,
+    Property,
+    Children,
+}
+
+/// What the children of a component are checked against.
+#[derive(Debug, Default)]
+struct Scope<'kdl> {
+    /// For children, the first node with each name; for nodes, the last
+    /// property with each key.
+    seen: BTreeMap<Cow<'kdl, str>, Span>,
+    /// The first property of this node.
+    first_property: Option<Span>,
+    /// Whether a slashdashed component is being visited.
+    slashdash: bool,
+}
+
+#[derive(Debug)]
+struct State<'kdl> {
+    source: &'kdl str,
+    rules: Rules,
+    cursor: usize,
+    /// Whether only whitespace has been visited on this line so far.
+    line_start: bool,
+    /// The last component, if it was whitespace within a line.
+    whitespace: Option<Span>,
+    /// The first indentation, which sets the indentation style.
+    indent: Option<(char, Span)>,
+    warnings: Vec<Warning>,
+}
+
+impl<'kdl, V> Linter<'kdl, V> {
+    pub fn new(source: &'kdl str, rules: Rules, visitor: V) -> Self {
+        let state = State {
+            source,
+            rules,
+            cursor: 0,
+            line_start: true,
+            whitespace: None,
+            indent: None,
+            warnings: Vec::new(),
+        };
+        Linter {
+            visitor,
+            state,
+            scope: Scope::default(),
+        }
+    }
+
+    pub fn into_inner(self) -> V {
+        self.visitor
+    }
+
+    /// Report the pending warnings to the visitor.
+    fn flush(&mut self)
+    where
+        V: visit::LendChildren<'kdl>,
+    {
+        for warning in self.state.warnings.drain(..) {
+            self.visitor.visit_warning(warning);
+        }
+    }
+}
+
+impl<'a, 'kdl, V> LintChild<'a, 'kdl, V> {
+    fn new(
+        visitor: V,
+        warn: fn(&mut V, Warning),
+        state: &'a mut State<'kdl>,
+        parent: &'a mut Scope<'kdl>,
+        kind: Kind,
+    ) -> Self {
+        LintChild {
+            visitor,
+            warn,
+            start: state.cursor,
+            state,
+            parent,
+            kind,
+            name: None,
+            scope: Scope::default(),
+        }
+    }
+
+    /// Report the pending warnings to the visitor.
+    fn flush(&mut self) {
+        for warning in self.state.warnings.drain(..) {
+            (self.warn)(&mut self.visitor, warning);
+        }
+    }
+}
+
+impl<V> Drop for LintChild<'_, '_, V> {
+    /// Check this component against its siblings. Warnings about entries
+    /// are left for the node to report.
+    fn drop(&mut self) {
+        let span = Span::from(self.start..self.state.cursor);
+        let slashdash = self.parent.slashdash;
+        match self.kind {
+            Kind::Node => {
+                if let (Some((name, span)), false) = (self.name.take(), slashdash) {
+                    match self.parent.seen.get(&name) {
+                        Some(&first) => {
+                            self.state
+                                .warn(Lint::DuplicateSiblingName, span, Some(first));
+                        }
+                        None => drop(self.parent.seen.insert(name, span)),
+                    }
+                }
+                // Reported to the node visitor before it is finished.
+                self.flush();
+            }
+            Kind::Argument => {
+                if let (Some(first), false) = (self.parent.first_property, slashdash) {
+                    self.state
+                        .warn(Lint::PropertyBeforeArgument, span, Some(first));
+                }
+            }
+            Kind::Property => {
+                if let (Some((key, _)), false) = (self.name.take(), slashdash) {
+                    self.parent.first_property.get_or_insert(span);
+                    if let Some(earlier) = self.parent.seen.insert(key, span) {
+                        self.state
+                            .warn(Lint::DuplicateProperty, earlier, Some(span));
+                    }
+                }
+            }
+            Kind::Children => {}
+        }
+    }
+}
+
+/// The value of an identifier, or `None` if it has an invalid escape, which
+/// the parser reports as an error.
+fn value(identifier: visit::Identifier<'_>) -> Option<Cow<'_, str>> {
+    match identifier {
+        visit::Identifier::String(string) if string.raw_value().is_none() => {
+            let mut value = String::new();
+            write!(value, "{}", string.as_value()).ok()?;
+            Some(Cow::Owned(value))
+        }
+        _ => Some(identifier.value()),
+    }
+}
+
+impl<'kdl> State<'kdl> {
+    fn identifier(&mut self, identifier: visit::Identifier<'kdl>) -> Option<Span> {
+        let span = self.scan(identifier.source())?;
+        if let visit::Identifier::Bare(_) = identifier {
+            self.confusables(span);
+        }
+        Some(span)
+    }
+
+    fn ty(&mut self, annotation: visit::Identifier<'kdl>) {
+        if let Some(span) = self.identifier(annotation) {
+            // A type which could not be unescaped was already reported.
+            if value(annotation).map_or(false, |ty| !self.rules.is_known_type(&ty)) {
+                let open = self.source[..span.start].rfind('(');
+                let close = self.source[span.end..].find(')');
+                let parens = Span::from(
+                    open.unwrap_or(span.start)
+                        ..close.map_or(span.end, |close| span.end + close + 1),
+                );
+                self.warn(Lint::UnusedTypeAnnotation, parens, None);
+            }
+        }
+    }
+
+    /// Scan a name, returning it to compare with its siblings' unless it
+    /// could not be unescaped.
+    fn name(&mut self, name: visit::Identifier<'kdl>) -> Option<(Cow<'kdl, str>, Span)> {
+        let span = self.identifier(name)?;
+        Some((value(name)?, span))
+    }
+
+    fn finish_lines(&mut self) {
+        if let Some(whitespace) = self.whitespace.take() {
+            self.warn(Lint::TrailingWhitespace, whitespace, None);
+        }
+    }
+
+    fn warn(&mut self, lint: Lint, span: Span, related: Option<Span>) {
+        let level = self.rules.level(lint);
+        if level != Level::Allow {
+            self.warnings.push(Warning {
+                related,
+                ..Warning::new(lint, level, span)
+            });
+        }
+    }
+
+    fn locate(&self, text: &str) -> Option<usize> {
+        let start = (text.as_ptr() as usize).checked_sub(self.source.as_ptr() as usize)?;
+        let end = start.checked_add(text.len())?;
+        (end <= self.source.len()).then_some(start)
+    }
+
+    /// Advance past source text, checking the whitespace lints.
+    fn scan(&mut self, text: &'kdl str) -> Option<Span> {
+        let start = if let Some(start) = self.locate(text) {
+            start
+        } else if text == ERROR_STRING {
+            // The source of a broken string was already visited as trivia.
+            return None;
+        } else {
+            // Keywords are not slices of the source.
+            self.cursor
+        };
+        self.cursor = start + text.len();
+        let span = Span::from(start..self.cursor);
+
+        if !text.is_empty() && text.chars().all(|c| NEWLINE_CHARS.contains(&c)) {
+            if let Some(whitespace) = self.whitespace.take() {
+                self.warn(Lint::TrailingWhitespace, whitespace, None);
+            }
+            self.line_start = true;
+        } else if !text.is_empty() && text.chars().all(char::is_whitespace) {
+            if self.line_start {
+                self.indentation(text, span);
+            }
+            self.confusables(span);
+            self.whitespace = Some(span);
+            self.line_start = false;
+        } else {
+            // Single-line comments include the whitespace before the newline.
+            let trimmed = text.trim_end_matches(|c: char| c.is_whitespace());
+            self.whitespace = (trimmed.len() < text.len())
+                .then(|| Span::from(start + trimmed.len()..self.cursor));
+            self.line_start = false;
+        }
+        Some(span)
+    }
+
+    fn indentation(&mut self, text: &str, span: Span) {
+        let style = match (text.contains(' '), text.contains('\t')) {
+            (true, true) => return self.warn(Lint::MixedIndentation, span, None),
+            (true, false) => ' ',
+            (false, true) => '\t',
+            (false, false) => return,
+        };
+        match self.indent {
+            Some((indent, first)) if indent != style => {
+                self.warn(Lint::MixedIndentation, span, Some(first));
+            }
+            Some(_) => {}
+            None => self.indent = Some((style, span)),
+        }
+    }
+
+    fn confusables(&mut self, span: Span) {
+        let level = self.rules.level(Lint::Confusable);
+        if level == Level::Allow {
+            return;
+        }
+        let text = &self.source[span.start..span.end];
+        for (offset, c) in text.char_indices() {
+            if let Some(like) = lookalike(c) {
+                let start = span.start + offset;
+                self.warnings.push(Warning {
+                    confusable: Some((c, like)),
+                    ..Warning::new(
+                        Lint::Confusable,
+                        level,
+                        (start..start + c.len_utf8()).into(),
+                    )
+                });
+            }
+        }
+    }
+}
+
+/// The ASCII character a non-ASCII character is easily mistaken for.
+fn lookalike(c: char) -> Option<char> {
+    let like = match c {
+        '\u{A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFF01 + 0x21)?,
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+        '\u{2018}' | '\u{2019}' | '\u{2032}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{2033}' => '"',
+        'а' | 'α' => 'a',
+        'с' | 'ϲ' => 'c',
+        'ԁ' => 'd',
+        'е' => 'e',
+        'һ' => 'h',
+        'і' | 'ι' => 'i',
+        'ј' => 'j',
+        'ӏ' => 'l',
+        'о' | 'ο' => 'o',
+        'р' | 'ρ' => 'p',
+        'ѕ' => 's',
+        'ν' => 'v',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' => 'y',
+        'А' | 'Α' => 'A',
+        'В' | 'Β' => 'B',
+        'С' | 'Ϲ' => 'C',
+        'Е' | 'Ε' => 'E',
+        'Н' | 'Η' => 'H',
+        'І' | 'Ι' => 'I',
+        'Ј' => 'J',
+        'К' | 'Κ' => 'K',
+        'М' | 'Μ' => 'M',
+        'Ν' => 'N',
+        'О' | 'Ο' => 'O',
+        'Р' | 'Ρ' => 'P',
+        'Ѕ' => 'S',
+        'Т' | 'Τ' => 'T',
+        'Х' | 'Χ' => 'X',
+        'Υ' | 'Ү' => 'Y',
+        'Ζ' => 'Z',
+        _ => return None,
+    };
+    Some(like)
+}
+
+/// The callbacks of [`visit::LendChildren`], shared by the linter and the
+/// children it lends.
+macro_rules! lint_children {
+    () => {
+        fn visit_trivia(&mut self, trivia: &'kdl str) {
+            self.state.scan(trivia);
+            self.visitor.visit_trivia(trivia);
+            self.flush();
+        }
+
+        fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+            self.state.scan(comment.source());
+            self.visitor.visit_comment(comment);
+            self.flush();
+        }
+
+        fn visit_newline(&mut self, newline: &'kdl str) {
+            self.state.scan(newline);
+            self.visitor.visit_newline(newline);
+            self.flush();
+        }
+
+        fn visit_escline(&mut self, escline: &'kdl str) {
+            self.state.scan(escline);
+            self.visitor.visit_escline(escline);
+            self.flush();
+        }
+
+        fn visit_node(&mut self) -> Self::VisitNode<'_> {
+            self.flush();
+            LintChild::new(
+                self.visitor.visit_node(),
+                visit::LendNode::visit_warning,
+                &mut self.state,
+                &mut self.scope,
+                Kind::Node,
+            )
+        }
+
+        fn visit_slashdash_node(&mut self) -> bool {
+            self.scope.slashdash = self.visitor.visit_slashdash_node();
+            self.scope.slashdash
+        }
+
+        fn finish_slashdash_node(&mut self) {
+            self.scope.slashdash = false;
+            self.visitor.finish_slashdash_node();
+        }
+
+        fn visit_warning(&mut self, warning: Warning) {
+            self.visitor.visit_warning(warning);
+        }
+
+        fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+            self.visitor.visit_error(error)
+        }
+    };
+}
+
+impl<'kdl, V: visit::LendDocument<'kdl>> visit::LendDocument<'kdl> for Linter<'kdl, V> {
+    type Output = V::Output;
+
+    fn finish(mut self) -> Self::Output {
+        self.state.finish_lines();
+        self.flush();
+        self.visitor.finish()
+    }
+
+    fn finish_error(mut self, error: ParseError) -> Result<Self::Output, ParseError> {
+        self.flush();
+        self.visitor.finish_error(error)
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.visitor.split_trivia()
+    }
+}
+
+impl<'kdl, V: visit::LendChildren<'kdl>> visit::LendChildren<'kdl> for Linter<'kdl, V> {
+    type VisitNode<'a>
+        = LintChild<'a, 'kdl, V::VisitNode<'a>>
+    where
+        Self: 'a;
+
+    lint_children!();
+}
+
+impl<'kdl, V: visit::LendChildren<'kdl>> visit::LendChildren<'kdl> for LintChild<'_, 'kdl, V> {
+    type VisitNode<'a>
+        = LintChild<'a, 'kdl, V::VisitNode<'a>>
+    where
+        Self: 'a;
+
+    lint_children!();
+}
+
+impl<'kdl, V: visit::LendNode<'kdl>> visit::LendNode<'kdl> for LintChild<'_, 'kdl, V> {
+    type VisitArgument<'a>
+        = LintChild<'a, 'kdl, V::VisitArgument<'a>>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = LintChild<'a, 'kdl, V::VisitProperty<'a>>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = LintChild<'a, 'kdl, V::VisitChildren<'a>>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.state.scan(trivia);
+        self.visitor.visit_trivia(trivia);
+        self.flush();
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.state.scan(comment.source());
+        self.visitor.visit_comment(comment);
+        self.flush();
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.state.scan(newline);
+        self.visitor.visit_newline(newline);
+        self.flush();
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.state.scan(escline);
+        self.visitor.visit_escline(escline);
+        self.flush();
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.state.ty(annotation);
+        self.visitor.visit_type(annotation);
+        self.flush();
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.name = self.state.name(name);
+        self.visitor.visit_name(name);
+        self.flush();
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.flush();
+        LintChild::new(
+            self.visitor.visit_argument(),
+            visit::Argument::visit_warning,
+            self.state,
+            &mut self.scope,
+            Kind::Argument,
+        )
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.flush();
+        LintChild::new(
+            self.visitor.visit_property(),
+            visit::Property::visit_warning,
+            self.state,
+            &mut self.scope,
+            Kind::Property,
+        )
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.flush();
+        LintChild::new(
+            self.visitor.visit_children(),
+            visit::LendChildren::visit_warning,
+            self.state,
+            &mut self.scope,
+            Kind::Children,
+        )
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.scope.slashdash = self.visitor.visit_slashdash_entry();
+        self.scope.slashdash
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.scope.slashdash = false;
+        self.visitor.finish_slashdash_entry();
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        self.visitor.visit_slashdash_children()
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        self.visitor.finish_slashdash_children();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+impl<'kdl, V: visit::Property<'kdl>> visit::Property<'kdl> for LintChild<'_, 'kdl, V> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.state.scan(trivia);
+        self.visitor.visit_trivia(trivia);
+        self.flush();
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.state.scan(comment.source());
+        self.visitor.visit_comment(comment);
+        self.flush();
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.state.scan(newline);
+        self.visitor.visit_newline(newline);
+        self.flush();
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.state.scan(escline);
+        self.visitor.visit_escline(escline);
+        self.flush();
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.name = self.state.name(name);
+        self.visitor.visit_name(name);
+        self.flush();
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.state.ty(annotation);
+        self.visitor.visit_type(annotation);
+        self.flush();
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.state.scan(value.source());
+        self.visitor.visit_value(value);
+        self.flush();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+impl<'kdl, V: visit::Argument<'kdl>> visit::Argument<'kdl> for LintChild<'_, 'kdl, V> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.state.scan(trivia);
+        self.visitor.visit_trivia(trivia);
+        self.flush();
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.state.scan(comment.source());
+        self.visitor.visit_comment(comment);
+        self.flush();
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.state.scan(newline);
+        self.visitor.visit_newline(newline);
+        self.flush();
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.state.scan(escline);
+        self.visitor.visit_escline(escline);
+        self.flush();
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.state.ty(annotation);
+        self.visitor.visit_type(annotation);
+        self.flush();
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.state.scan(value.source());
+        self.visitor.visit_value(value);
+        self.flush();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
@@ -0,0 +1,276 @@
+//! Warnings for KDL which parses, but is likely to be a mistake.
+//!
+//! Lints are checked by the [`Linter`] adapter, which reports [`Warning`]s
+//! to the wrapped visitor's `visit_warning`. Which lints are checked, and
+//! how severely they are reported, is configured with [`Rules`]. To just
+//! collect the warnings for a document, use [`Warnings::collect`].
+
+#[cfg(feature = "alloc")]
+mod linter;
+#[cfg(feature = "alloc")]
+mod rules;
+#[cfg(feature = "alloc")]
+mod warnings;
+
+#[cfg(feature = "alloc")]
+pub use self::{
+    linter::{LintChild, Linter},
+    rules::Rules,
+    warnings::Warnings,
+};
+use {
+    crate::{
+        error::{Label, Report, Text},
+        Span,
+    },
+    core::fmt,
+};
+
+/// A check for likely mistakes in KDL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Lint {
+    /// A property key is repeated in a node, so all but the last are unused.
+    DuplicateProperty,
+    /// Sibling nodes have the same name. Allowed by default.
+    DuplicateSiblingName,
+    /// An argument follows a property in the same node. Allowed by default.
+    PropertyBeforeArgument,
+    /// A type annotation is neither reserved by the KDL spec nor configured
+    /// as known with [`Rules::known_type`]. Allowed by default.
+    UnusedTypeAnnotation,
+    /// Some lines are indented with tabs and others with spaces, or a line is
+    /// indented with both.
+    MixedIndentation,
+    /// A line ends with whitespace.
+    TrailingWhitespace,
+    /// A bare identifier or whitespace contains a non-ASCII character which
+    /// looks like an ASCII one.
+    Confusable,
+}
+
+/// How a [`Lint`] is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Not checked.
+    Allow,
+    /// Reported with warning severity.
+    Warn,
+    /// Reported with error severity.
+    Deny,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 7] = [
+        Lint::DuplicateProperty,
+        Lint::DuplicateSiblingName,
+        Lint::PropertyBeforeArgument,
+        Lint::UnusedTypeAnnotation,
+        Lint::MixedIndentation,
+        Lint::TrailingWhitespace,
+        Lint::Confusable,
+    ];
+
+    /// The name of the lint, e.g. `duplicate_property`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::DuplicateProperty => "duplicate_property",
+            Lint::DuplicateSiblingName => "duplicate_sibling_name",
+            Lint::PropertyBeforeArgument => "property_before_argument",
+            Lint::UnusedTypeAnnotation => "unused_type_annotation",
+            Lint::MixedIndentation => "mixed_indentation",
+            Lint::TrailingWhitespace => "trailing_whitespace",
+            Lint::Confusable => "confusable",
+        }
+    }
+
+    /// Look up a lint by its [`name`](Lint::name).
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+
+    /// The diagnostic code of the lint, e.g. `kdl::lint::duplicate_property`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lint::DuplicateProperty => "kdl::lint::duplicate_property",
+            Lint::DuplicateSiblingName => "kdl::lint::duplicate_sibling_name",
+            Lint::PropertyBeforeArgument => "kdl::lint::property_before_argument",
+            Lint::UnusedTypeAnnotation => "kdl::lint::unused_type_annotation",
+            Lint::MixedIndentation => "kdl::lint::mixed_indentation",
+            Lint::TrailingWhitespace => "kdl::lint::trailing_whitespace",
+            Lint::Confusable => "kdl::lint::confusable",
+        }
+    }
+
+    pub fn default_level(self) -> Level {
+        match self {
+            Lint::DuplicateSiblingName
+            | Lint::PropertyBeforeArgument
+            | Lint::UnusedTypeAnnotation => Level::Allow,
+            Lint::DuplicateProperty
+            | Lint::MixedIndentation
+            | Lint::TrailingWhitespace
+            | Lint::Confusable => Level::Warn,
+        }
+    }
+
+    fn help(self) -> Option<&'static str> {
+        match self {
+            Lint::DuplicateProperty => Some("remove the unused property"),
+            Lint::DuplicateSiblingName => None,
+            Lint::PropertyBeforeArgument => Some("move the arguments before the properties"),
+            Lint::UnusedTypeAnnotation => Some("remove the type annotation"),
+            Lint::MixedIndentation => Some("indent consistently with either tabs or spaces"),
+            Lint::TrailingWhitespace => Some("remove the whitespace"),
+            Lint::Confusable => None,
+        }
+    }
+}
+
+/// A likely mistake found by a [`Lint`].
+///
+/// Like [`ParseError`](crate::ParseError), it is recommended to interact with
+/// warnings through [`Display`](fmt::Display) and
+/// [`Diagnostic`](miette::Diagnostic), which uses [`Severity::Error`] for
+/// denied lints.
+///
+/// [`Severity::Error`]: miette::Severity::Error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Warning {
+    lint: Lint,
+    level: Level,
+    span: Span,
+    related: Option<Span>,
+    confusable: Option<(char, char)>,
+}
+
+impl Warning {
+    #[cfg(feature = "alloc")]
+    pub(crate) fn new(lint: Lint, level: Level, span: Span) -> Self {
+        Warning {
+            lint,
+            level,
+            span,
+            related: None,
+            confusable: None,
+        }
+    }
+
+    pub fn lint(&self) -> Lint {
+        self.lint
+    }
+
+    /// The level the lint was configured at; never [`Level::Allow`].
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Another location involved in the warning, such as the property which
+    /// overrides a [`DuplicateProperty`](Lint::DuplicateProperty).
+    pub fn related(&self) -> Option<Span> {
+        self.related
+    }
+
+    fn related_label(&self) -> &'static str {
+        match self.lint {
+            Lint::DuplicateProperty => "overridden here",
+            Lint::DuplicateSiblingName => "first used here",
+            Lint::PropertyBeforeArgument => "after this property",
+            Lint::MixedIndentation => "indentation style set here",
+            _ => "related",
+        }
+    }
+
+    pub(crate) fn help(&self) -> Option<Text> {
+        match self.confusable {
+            Some((_, like)) => Some(Text::Use(like)),
+            None => self.lint.help().map(Text::Str),
+        }
+    }
+
+    /// The highlighted spans, primary first.
+    pub(crate) fn labels(&self) -> [Option<Label>; 3] {
+        let related = self.related_label();
+        [
+            Some(Label::new(self.span, None)),
+            (self.related).map(|span| Label::new(span, Some(Text::Str(related)))),
+            None,
+        ]
+    }
+
+    /// Display the warning rustc-style, like
+    /// [`ParseError::display_plain`](crate::ParseError::display_plain).
+    pub fn display_plain<'a>(&'a self, path: &'a str, source: &'a str) -> impl 'a + fmt::Display {
+        self.report(path, source)
+    }
+
+    pub(crate) fn report<'a>(&'a self, path: &'a str, source: &'a str) -> Report<'a> {
+        Report {
+            path,
+            source,
+            severity: match self.level {
+                Level::Deny => "error",
+                Level::Allow | Level::Warn => "warning",
+            },
+            code: self.lint.code(),
+            message: self,
+            labels: self.labels(),
+            nodes: None,
+            help: self.help(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.lint {
+            Lint::DuplicateProperty => f.write_str("property is overridden by a later property"),
+            Lint::DuplicateSiblingName => f.write_str("sibling node has the same name"),
+            Lint::PropertyBeforeArgument => f.write_str("argument follows a property"),
+            Lint::UnusedTypeAnnotation => f.write_str("type annotation is not a known type"),
+            Lint::MixedIndentation => f.write_str("indentation mixes tabs and spaces"),
+            Lint::TrailingWhitespace => f.write_str("trailing whitespace"),
+            Lint::Confusable => match self.confusable {
+                Some((found, like)) => write!(
+                    f,
+                    "found {found:?} (U+{:04X}), which looks like {like:?}",
+                    found as u32,
+                ),
+                None => f.write_str("confusable character"),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Warning {}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Warning {
+    fn code<'a>(&'a self) -> Option<std::boxed::Box<dyn fmt::Display + 'a>> {
+        Some(std::boxed::Box::new(self.lint.code()))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        match self.level {
+            Level::Deny => Some(miette::Severity::Error),
+            Level::Allow | Level::Warn => Some(miette::Severity::Warning),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<std::boxed::Box<dyn fmt::Display + 'a>> {
+        Some(std::boxed::Box::new(Warning::help(self)?))
+    }
+
+    fn labels(&self) -> Option<std::boxed::Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let labels = self.labels().into_iter().flatten().map(|label| {
+            let text = label.text.map(|text| format!("{text}"));
+            miette::LabeledSpan::new_with_span(text, label.span)
+        });
+        Some(std::boxed::Box::new(labels))
+    }
+}
@@ -0,0 +1,107 @@
+use {
+    super::{Level, Lint},
+    alloc::{borrow::Cow, vec::Vec},
+};
+
+/// The type annotations reserved by the KDL spec.
+const RESERVED_TYPES: &[&str] = &[
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "isize",
+    "usize",
+    "f32",
+    "f64",
+    "decimal64",
+    "decimal128",
+    "date-time",
+    "time",
+    "date",
+    "duration",
+    "decimal",
+    "currency",
+    "country-2",
+    "country-3",
+    "country-subdivision",
+    "email",
+    "idn-email",
+    "hostname",
+    "idn-hostname",
+    "ipv4",
+    "ipv6",
+    "url",
+    "url-reference",
+    "irl",
+    "irl-reference",
+    "url-template",
+    "uuid",
+    "regex",
+    "base64",
+];
+
+/// Which lints are checked by a [`Linter`](super::Linter), and at what level.
+///
+/// ```
+/// # use kdl_visit::lint::{Level, Lint, Rules};
+/// let mut rules = Rules::new();
+/// rules
+///     .set(Lint::TrailingWhitespace, Level::Allow)
+///     .set(Lint::UnusedTypeAnnotation, Level::Deny)
+///     .known_type("semver");
+/// assert_eq!(rules.level(Lint::DuplicateProperty), Level::Warn);
+/// assert_eq!(rules.level(Lint::TrailingWhitespace), Level::Allow);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rules {
+    levels: [Level; Lint::ALL.len()],
+    known_types: Vec<Cow<'static, str>>,
+}
+
+impl Rules {
+    /// Every lint at its [default level](Lint::default_level).
+    pub fn new() -> Self {
+        Rules {
+            levels: Lint::ALL.map(Lint::default_level),
+            known_types: Vec::new(),
+        }
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        self.levels[lint as usize]
+    }
+
+    pub fn set(&mut self, lint: Lint, level: Level) -> &mut Self {
+        self.levels[lint as usize] = level;
+        self
+    }
+
+    /// Set every lint to the same level.
+    pub fn set_all(&mut self, level: Level) -> &mut Self {
+        self.levels = [level; Lint::ALL.len()];
+        self
+    }
+
+    /// Add a type annotation which is not an
+    /// [unused type annotation](Lint::UnusedTypeAnnotation).
+    pub fn known_type(&mut self, ty: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.known_types.push(ty.into());
+        self
+    }
+
+    pub(super) fn is_known_type(&self, ty: &str) -> bool {
+        RESERVED_TYPES.contains(&ty) || self.known_types.iter().any(|known| known == ty)
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,247 @@
+use {
+    super::{Level, Linter, Rules, Warning},
+    crate::{
+        error::{JsonLines, Reports, Sarif},
+        visit, visit_kdl_string, ParseError, SourceCode,
+    },
+    alloc::{borrow::Cow, string::String, vec::Vec},
+    core::fmt,
+};
+
+/// A collection of warnings found while linting KDL.
+///
+/// Like [`ParseErrors`](crate::ParseErrors), but for [`Warning`]s; as a
+/// [`Diagnostic`](miette::Diagnostic), it has error severity if any of the
+/// warnings are [denied](Level::Deny).
+#[derive(Debug, Clone)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
+pub struct Warnings<Source: fmt::Debug + SourceCode = String> {
+    pub source: Source,
+    pub warnings: Vec<Warning>,
+}
+
+impl<Source: fmt::Debug + SourceCode> Warnings<Source> {
+    /// Whether any of the warnings are [denied](Level::Deny).
+    pub fn is_denied(&self) -> bool {
+        self.warnings
+            .iter()
+            .any(|warning| warning.level() == Level::Deny)
+    }
+}
+
+impl<Source: fmt::Debug + SourceCode> fmt::Display for Warnings<Source> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("warnings occured while linting")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Source: fmt::Debug + SourceCode> std::error::Error for Warnings<Source> {}
+
+#[cfg(feature = "miette")]
+impl<Source: fmt::Debug + SourceCode> miette::Diagnostic for Warnings<Source> {
+    fn severity(&self) -> Option<miette::Severity> {
+        match self.is_denied() {
+            true => Some(miette::Severity::Error),
+            false => Some(miette::Severity::Warning),
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source)
+    }
+
+    fn related<'a>(
+        &'a self,
+    ) -> Option<std::boxed::Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+        Some(std::boxed::Box::new(
+            self.warnings
+                .iter()
+                .map(|warning| warning as &dyn miette::Diagnostic),
+        ))
+    }
+}
+
+#[cfg(feature = "render")]
+impl<Source: fmt::Debug + SourceCode> Warnings<Source> {
+    fn render_impl(
+        &self,
+        theme: miette::GraphicalTheme,
+        writer: &mut impl fmt::Write,
+    ) -> fmt::Result {
+        miette::GraphicalReportHandler::new_themed(theme)
+            .with_urls(false)
+            .render_report(writer, self)
+    }
+
+    pub fn render<'a>(
+        &'a self,
+        get_theme: impl 'a + Fn() -> miette::GraphicalTheme,
+    ) -> impl 'a + fmt::Display {
+        struct Display<F>(F);
+        impl<F> fmt::Display for Display<F>
+        where
+            F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                (self.0)(f)
+            }
+        }
+
+        Display(move |f: &mut fmt::Formatter<'_>| self.render_impl(get_theme(), f))
+    }
+
+    pub fn display(&self, theme: miette::GraphicalTheme) -> impl '_ + fmt::Display {
+        let theme = std::cell::Cell::new(Some(theme));
+        self.render(move || theme.take().unwrap())
+    }
+}
+
+impl<Source: fmt::Debug + SourceCode + AsRef<str>> Warnings<Source> {
+    /// Display the warnings [rustc-style](Warning::display_plain), separated
+    /// by blank lines.
+    pub fn display_plain<'a>(&'a self, path: &'a str) -> impl 'a + fmt::Display {
+        let source = self.source.as_ref();
+        Reports((self.warnings.iter()).map(move |warning| warning.report(path, source)))
+    }
+
+    /// Display the warnings as JSON Lines, like
+    /// [`ParseErrors::display_json_lines`](crate::ParseErrors::display_json_lines).
+    /// The `severity` is `error` for denied lints.
+    pub fn display_json_lines<'a>(&'a self, path: &'a str) -> impl 'a + fmt::Display {
+        let source = self.source.as_ref();
+        JsonLines((self.warnings.iter()).map(move |warning| warning.report(path, source)))
+    }
+
+    /// Display the warnings as a SARIF 2.1.0 log, like
+    /// [`ParseErrors::display_sarif`](crate::ParseErrors::display_sarif).
+    pub fn display_sarif<'a>(&'a self, path: &'a str) -> impl 'a + fmt::Display {
+        let source = self.source.as_ref();
+        Sarif((self.warnings.iter()).map(move |warning| warning.report(path, source)))
+    }
+}
+
+impl Warnings<Cow<'_, str>> {
+    pub fn into_owned(self) -> Warnings<String> {
+        Warnings {
+            source: self.source.into_owned(),
+            warnings: self.warnings,
+        }
+    }
+}
+
+impl<'kdl> Warnings<&'kdl str> {
+    /// Lint the source with the rules, collecting the warnings in source
+    /// order. Parse errors end linting, so only the warnings found before the
+    /// first error are collected.
+    ///
+    /// ```
+    /// # use kdl_visit::lint::{Level, Lint, Rules, Warnings};
+    /// let mut rules = Rules::new();
+    /// rules.set(Lint::DuplicateSiblingName, Level::Deny);
+    /// let warnings = Warnings::collect("a key=1 key=2\na", rules);
+    /// let found: Vec<_> = warnings
+    ///     .warnings
+    ///     .iter()
+    ///     .map(|warning| (warning.lint(), warning.level()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     found,
+    ///     [
+    ///         (Lint::DuplicateProperty, Level::Warn),
+    ///         (Lint::DuplicateSiblingName, Level::Deny),
+    ///     ]
+    /// );
+    /// assert!(warnings.is_denied());
+    /// ```
+    pub fn collect(source: &'kdl str, rules: Rules) -> Self {
+        let mut warnings = Vec::new();
+        let linter = Linter::new(source, rules, CollectWarnings(&mut warnings));
+        let _ = visit_kdl_string(source, linter);
+        warnings.sort_by_key(|warning| warning.span().start);
+        Warnings { source, warnings }
+    }
+}
+
+#[derive(Debug)]
+struct CollectWarnings<'a>(&'a mut Vec<Warning>);
+
+impl visit::LendDocument<'_> for CollectWarnings<'_> {
+    type Output = ();
+
+    fn finish(self) {}
+}
+
+impl visit::LendChildren<'_> for CollectWarnings<'_> {
+    type VisitNode<'a>
+        = CollectWarnings<'a>
+    where
+        Self: 'a;
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        CollectWarnings(self.0)
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.push(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Err(error)
+    }
+}
+
+impl visit::LendNode<'_> for CollectWarnings<'_> {
+    type VisitArgument<'a>
+        = CollectWarnings<'a>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = CollectWarnings<'a>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = CollectWarnings<'a>
+    where
+        Self: 'a;
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        CollectWarnings(self.0)
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        CollectWarnings(self.0)
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        CollectWarnings(self.0)
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.push(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Err(error)
+    }
+}
+
+impl visit::Argument<'_> for CollectWarnings<'_> {
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.push(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Err(error)
+    }
+}
+
+impl visit::Property<'_> for CollectWarnings<'_> {
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.push(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Err(error)
+    }
+}
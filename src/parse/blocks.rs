@@ -0,0 +1,126 @@
+use {
+    super::{line_start, NEWLINE_CHARS},
+    crate::{NodePath, Span},
+};
+
+/// How many nested children blocks are tracked for guessing where a missing
+/// closing brace belongs. Deeper blocks are parsed as usual, without guesses.
+const TRACKED_DEPTH: usize = 32;
+
+/// The children blocks currently open, innermost last.
+///
+/// Used to recover from a missing `}` the way rustc does: by trusting the
+/// indentation of the source over the braces, and to tell errors which nodes
+/// they occurred in.
+#[derive(Debug, Clone)]
+pub(crate) struct Blocks {
+    depth: usize,
+    open: [Block; TRACKED_DEPTH],
+    /// Indentation of the line of the most recently started node, which is
+    /// the node owning the next block to be opened.
+    node_indent: usize,
+    /// Name of the most recently started node, once it has been parsed,
+    /// which names the next block to be opened.
+    node: Option<Span>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Block {
+    /// Indentation of the line of the node owning the block.
+    pub(crate) indent: usize,
+    /// Where the first child indented no deeper than the node starts, which
+    /// is likely where the block was meant to end.
+    pub(crate) dedent: Option<usize>,
+    /// Name of the node owning the block.
+    name: Option<Span>,
+}
+
+#[allow(unreachable_pub)]
+impl Blocks {
+    pub fn new() -> Self {
+        Blocks {
+            depth: 0,
+            open: [Block::default(); TRACKED_DEPTH],
+            node_indent: 0,
+            node: None,
+        }
+    }
+
+    /// A node starts at `offset`.
+    pub fn start_node(&mut self, source: &str, offset: usize) {
+        let (indent, starts_line) = indentation(source, offset);
+        self.node_indent = indent;
+        self.node = None;
+        if let Some(block) = self.innermost() {
+            if starts_line && indent <= block.indent && block.dedent.is_none() {
+                block.dedent = Some(offset);
+            }
+        }
+    }
+
+    /// The last started node is named by `name`.
+    pub fn name_node(&mut self, name: Span) {
+        self.node = Some(name);
+    }
+
+    /// The last started node opens a block.
+    pub fn open(&mut self) {
+        if let Some(block) = self.open.get_mut(self.depth) {
+            *block = Block {
+                indent: self.node_indent,
+                dedent: None,
+                name: self.node,
+            };
+        }
+        self.depth += 1;
+    }
+
+    /// Whether any block is open.
+    pub fn any_open(&self) -> bool {
+        self.depth > 0
+    }
+
+    /// The innermost block ends, returning it if it was tracked.
+    pub fn close(&mut self) -> Option<Block> {
+        self.depth = self.depth.checked_sub(1)?;
+        self.open.get(self.depth).copied()
+    }
+
+    /// The names of the nodes owning the open blocks, outermost first.
+    pub fn path(&self, source: &str) -> NodePath {
+        let name = |span: Span| &source[span.start..span.end];
+        let outer = &self.open[..self.depth.min(TRACKED_DEPTH)];
+        let untracked = (self.depth > TRACKED_DEPTH).then_some("…");
+        NodePath::from_innermost(
+            (untracked.into_iter())
+                .chain(outer.iter().rev().filter_map(|block| block.name.map(name))),
+        )
+    }
+
+    /// Whether the `}` at `offset`, which would close `block`, is dedented
+    /// to match one of the blocks enclosing it, and so probably closes that
+    /// block instead.
+    pub fn closes_outer(&self, source: &str, offset: usize, block: Block) -> bool {
+        let (indent, starts_line) = indentation(source, offset);
+        let outer = &self.open[..self.depth.min(TRACKED_DEPTH)];
+        starts_line && indent < block.indent && outer.iter().any(|it| it.indent == indent)
+    }
+
+    fn innermost(&mut self) -> Option<&mut Block> {
+        let depth = self.depth.checked_sub(1)?;
+        self.open.get_mut(depth)
+    }
+}
+
+/// The indentation of the line containing `offset`, in characters, and
+/// whether only that indentation precedes `offset` on the line.
+fn indentation(source: &str, offset: usize) -> (usize, bool) {
+    let line_start = line_start(source, offset);
+    let line = &source[line_start..];
+    let indent = line
+        .char_indices()
+        .find(|&(_, c)| !c.is_whitespace() || NEWLINE_CHARS.contains(&c))
+        .map_or(line.len(), |(i, _)| i);
+    let chars = line[..indent].chars().count();
+    (chars, line_start + indent >= offset)
+}
@@ -1,6 +1,11 @@
-use {crate::Span, logos::Logos};
+use {
+    super::blocks::Blocks,
+    crate::{NodePath, Span},
+    logos::Logos,
+};
 
 #[derive(Logos, Debug, Clone, Copy, PartialEq, Eq)]
+#[logos(extras = Option<usize>)] // where an unclosed block comment starts
 #[logos(subpattern integer = r"[[:digit:]][[:digit:]_]*")]
 #[logos(subpattern exponent = r"[eE][+-]?(?&integer)")]
 #[logos(subpattern id_char = r#"[^\\/(){}<>;\[\]=,\t \u{A0}\u{1680}\u{2000}\u{2001}\u{2002}\u{2003}\u{2004}\u{2005}\u{2006}\u{2007}\u{2008}\u{2009}\u{200A}\u{202F}\u{205F}\u{3000}\u{FEFF}\r\n\u{85}\u{0C}\u{2028}\u{2029}"]"#)]
@@ -103,7 +108,12 @@ fn multi_line_comment(lex: &mut logos::Lexer<Token>) -> bool {
                     return true;
                 }
             }
-            _ => return false,
+            _ => {
+                // unclosed; comment out the rest of the source, as intended
+                lex.extras = Some(lex.span().start);
+                lex.bump(lex.remainder().len());
+                return true;
+            }
         }
     }
 }
@@ -161,9 +171,24 @@ fn raw_string(lex: &mut logos::Lexer<Token>) -> bool {
     false
 }
 
+/// Offsets to find the paths of the enclosing nodes at, while parsing.
+struct Probe<'a> {
+    /// The offsets, in order, each with the path there once found.
+    paths: &'a mut [(usize, NodePath)],
+    /// How many of the offsets have been passed, so their paths are final.
+    passed: usize,
+    /// The path at the current token.
+    path: NodePath,
+}
+
 pub(crate) struct Lexer<'kdl> {
     lexer: logos::Lexer<'kdl, Token>,
     lookahead: [Option<(Token, Span)>; 4],
+    blocks: Blocks,
+    /// The offsets to find the paths at, if probing.
+    probe: Option<Probe<'kdl>>,
+    /// Whether whitespace is visited split into comments and newlines.
+    split_trivia: bool,
 }
 
 #[allow(unreachable_pub)]
@@ -172,6 +197,9 @@ impl<'kdl> Lexer<'kdl> {
         let mut this = Self {
             lexer: Token::lexer(kdl),
             lookahead: [None, None, None, None],
+            blocks: Blocks::new(),
+            probe: None,
+            split_trivia: false,
         };
         debug_assert!(this.peek1().is_none());
         this.bump();
@@ -185,6 +213,79 @@ impl<'kdl> Lexer<'kdl> {
         this
     }
 
+    /// A lexer for finding the path at each offset in `paths`, which must be
+    /// sorted, to be filled in by [`Lexer::finish_probe`]. The source appears
+    /// to end soon after the last offset.
+    pub fn probe(kdl: &'kdl str, paths: &'kdl mut [(usize, NodePath)]) -> Self {
+        let mut this = Self::new(kdl);
+        debug_assert!(paths.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        this.probe = Some(Probe {
+            paths,
+            passed: 0,
+            path: NodePath::EMPTY,
+        });
+        this.update_probe();
+        this
+    }
+
+    /// Visit whitespace split into comments and newlines, as for a visitor
+    /// which [opts in](crate::visit::Document::split_trivia).
+    pub fn set_split_trivia(&mut self, split: bool) {
+        self.split_trivia = split;
+    }
+
+    pub fn split_trivia(&self) -> bool {
+        self.split_trivia
+    }
+
+    /// Fill in the paths at the offsets not passed before the end.
+    pub fn finish_probe(&mut self) {
+        if let Some(probe) = &mut self.probe {
+            for (_, path) in &mut probe.paths[probe.passed..] {
+                *path = probe.path;
+            }
+        }
+    }
+
+    /// The last started node opens a block.
+    pub fn open_block(&mut self) {
+        self.blocks.open();
+        self.update_probe();
+    }
+
+    fn update_probe(&mut self) {
+        let start = self.span1().start;
+        let Some(probe) = &mut self.probe else {
+            return;
+        };
+        while let Some((offset, path)) = probe.paths.get_mut(probe.passed) {
+            if start <= *offset {
+                break;
+            }
+            *path = probe.path;
+            probe.passed += 1;
+        }
+        if probe.passed < probe.paths.len() {
+            probe.path = self.blocks.path(self.lexer.source());
+        } else {
+            // nothing past the last offset changes the paths, so stop lexing
+            self.lexer.bump(self.lexer.remainder().len());
+        }
+    }
+
+    /// Lex anew from `offset`, which the current token runs past.
+    pub fn relex_from(&mut self, offset: usize) {
+        let probe = self.probe.take();
+        self.lexer = Token::lexer(self.source());
+        self.lexer.bump(offset);
+        self.lookahead = [None, None, None, None];
+        for _ in 0..self.lookahead.len() {
+            self.bump();
+        }
+        self.probe = probe;
+        self.update_probe();
+    }
+
     #[cfg(feature = "tracing")]
     pub fn ll3(&self) -> &[Option<(Token, Span)>] {
         &self.lookahead[..3]
@@ -214,6 +315,17 @@ impl<'kdl> Lexer<'kdl> {
         &self.source()[span.start..span.end]
     }
 
+    pub fn blocks(&mut self) -> &mut Blocks {
+        &mut self.blocks
+    }
+
+    /// The opening `/*` of a block comment left unclosed at the end of file,
+    /// if it has not been taken yet.
+    pub fn take_unclosed_comment(&mut self) -> Option<Span> {
+        let start = self.lexer.extras.take()?;
+        Some(Span::from(start..start + 2))
+    }
+
     pub fn token2(&self) -> Option<Token> {
         self.lookahead[1].map(|(token, _)| token)
     }
@@ -229,7 +341,7 @@ impl<'kdl> Lexer<'kdl> {
         loop {
             match self.lexer.next() {
                 Some(Token::Whitespace) => {
-                    let span: Span = self.lexer.span().try_into().unwrap();
+                    let span: Span = self.lexer.span().into();
                     if let Some((Token::Whitespace, range)) = &mut self.lookahead[2] {
                         debug_assert_eq!(range.end, span.start);
                         *range = Span::from(range.start..span.end);
@@ -239,7 +351,7 @@ impl<'kdl> Lexer<'kdl> {
                     }
                 }
                 Some(Token::Newline) => {
-                    let span: Span = self.lexer.span().try_into().unwrap();
+                    let span: Span = self.lexer.span().into();
                     if let Some((Token::Newline, range)) = &mut self.lookahead[2] {
                         debug_assert_eq!(range.end, span.start);
                         *range = Span::from(range.start..span.end);
@@ -248,12 +360,10 @@ impl<'kdl> Lexer<'kdl> {
                         self.lookahead[3] = Some((Token::Newline, span));
                     }
                 }
-                next => {
-                    self.lookahead[3] =
-                        next.map(|token| (token, self.lexer.span().try_into().unwrap()))
-                }
+                next => self.lookahead[3] = next.map(|token| (token, self.lexer.span().into())),
             }
             break;
         }
+        self.update_probe();
     }
 }
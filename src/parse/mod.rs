@@ -2,16 +2,59 @@ use {
     self::lexer::{Lexer, Token},
     crate::{
         visit::{self, prelude::*},
-        ParseError,
+        NodePath, ParseError, Span,
     },
     scopeguard::guard,
 };
 
+mod blocks;
 mod lexer;
 mod strings;
+mod visitor;
+
+pub(crate) use self::visitor::{ParseChildren, ParseNode};
 
 /// Parse a KDL string, calling the visitor methods as it goes.
 ///
+/// The visitor may implement either the owned visitor traits, such as
+/// [`visit::Document`], or the lending ones, such as [`visit::LendDocument`],
+/// whose child visitors borrow their parent:
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// # use visit::Argument as _;
+/// struct Count<'a>(&'a mut usize);
+///
+/// impl<'kdl> visit::LendDocument<'kdl> for Count<'_> {
+///     type Output = ();
+///     fn finish(self) {}
+/// }
+///
+/// impl<'kdl> visit::LendChildren<'kdl> for Count<'_> {
+///     type VisitNode<'a> = Count<'a> where Self: 'a;
+///     fn visit_node(&mut self) -> Count<'_> {
+///         *self.0 += 1;
+///         Count(self.0)
+///     }
+/// }
+///
+/// impl<'kdl> visit::LendNode<'kdl> for Count<'_> {
+///     type VisitArgument<'a> = () where Self: 'a;
+///     type VisitProperty<'a> = () where Self: 'a;
+///     type VisitChildren<'a> = Count<'a> where Self: 'a;
+///     fn visit_argument(&mut self) {}
+///     fn visit_property(&mut self) {}
+///     fn visit_children(&mut self) -> Count<'_> {
+///         Count(self.0)
+///     }
+/// }
+///
+/// let mut count = 0;
+/// visit_kdl_string("a {\n    b\n    c\n}\nd", Count(&mut count))?;
+/// assert_eq!(count, 4);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+///
 /// # Errors
 ///
 /// When encountering an error, the `visit_error` method is called on whichever
@@ -30,30 +73,143 @@ mod strings;
 #[cfg_attr(feature = "tracing",
     tracing::instrument(level = tracing::Level::TRACE, skip(visitor))
 )]
-pub fn visit_kdl_string<'kdl, V: visit::Document<'kdl>>(
+pub fn visit_kdl_string<'kdl, V: visit::LendDocument<'kdl>>(
     kdl: &'kdl str,
     mut visitor: V,
 ) -> Result<V::Output, ParseError> {
     let mut lexer = Lexer::new(kdl);
+    lexer.set_split_trivia(visitor.split_trivia());
     match visit_document(&mut lexer, &mut visitor) {
         Ok(()) => Ok(visitor.finish()),
         Err(error) => visitor.finish_error(error),
     }
 }
 
+/// The names of the nodes whose children `offset` is in, found by parsing
+/// the source up to it with the same recovery as when reporting errors.
+pub(crate) fn path_at(kdl: &str, offset: usize) -> NodePath {
+    let mut paths = [(offset, NodePath::EMPTY)];
+    paths_at(kdl, &mut paths);
+    paths[0].1
+}
+
+/// Fill in the path at each of the sorted offsets, as with [`path_at`], but
+/// parsing the source only once.
+pub(crate) fn paths_at(kdl: &str, paths: &mut [(usize, NodePath)]) {
+    let mut lexer = Lexer::probe(kdl, paths);
+    let _ = visit_document(&mut lexer, &mut visit::Ignore);
+    lexer.finish_probe();
+}
+
+/// Parse a KDL string, allowing the visitor to abort with its own error.
+///
+/// This behaves like [`visit_kdl_string`], except that the visitor may
+/// [`raise`] an error through `abort`. Once raised, the visitor is not called
+/// again, and the raised error is returned along with the span of the
+/// component being visited when it was raised.
+///
+/// # Errors
+///
+/// Errors from the parser are returned as [`Either::Left`], as they would be
+/// by [`visit_kdl_string`]. Errors raised by the visitor are returned as
+/// [`Either::Right`], even if the parser also encountered an error.
+///
+/// [`raise`]: visit::Abort::raise
+/// [`Either::Left`]: visit::Either::Left
+/// [`Either::Right`]: visit::Either::Right
+#[cfg_attr(feature = "tracing",
+    tracing::instrument(level = tracing::Level::TRACE, skip(abort, visitor))
+)]
+pub fn try_visit_kdl_string<'kdl, V: visit::LendDocument<'kdl>, E>(
+    kdl: &'kdl str,
+    abort: &visit::Abort<E>,
+    visitor: V,
+) -> Result<V::Output, visit::Either<ParseError, visit::Aborted<E>>> {
+    match visit_kdl_string(kdl, visit::Abortable::new(kdl, abort, visitor)) {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(aborted)) => Err(visit::Either::Right(aborted)),
+        Err(error) => Err(visit::Either::Left(error)),
+    }
+}
+
+/// Parse a KDL string with a `dyn` visitor.
+///
+/// This behaves like [`visit_kdl_string`], except that only one copy of the
+/// parser is compiled for all `dyn` visitors, rather than one per visitor
+/// type. Since the visitor is only borrowed, producing its output is left to
+/// the caller.
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string_dyn};
+/// # use visit::Document;
+/// let mut visitor = ();
+/// let result = visit_kdl_string_dyn("node 1 2 3", &mut visitor);
+/// let output = match result {
+///     Ok(()) => Ok(visitor.finish()),
+///     Err(error) => visitor.finish_error(error),
+/// };
+/// assert_eq!(output, Ok(()));
+/// ```
+///
+/// # Errors
+///
+/// As with [`visit_kdl_string`], except that the error which stopped parsing
+/// is returned directly, rather than passed to `finish_error`.
+#[cfg_attr(feature = "tracing",
+    tracing::instrument(level = tracing::Level::TRACE, skip(visitor))
+)]
+pub fn visit_kdl_string_dyn<'kdl>(
+    kdl: &'kdl str,
+    visitor: &mut dyn visit::DynDocument<'kdl>,
+) -> Result<(), ParseError> {
+    let mut lexer = Lexer::new(kdl);
+    lexer.set_split_trivia(visitor.split_trivia());
+    let mut visitor = visitor::DynRoot(visitor);
+    visit_document(
+        &mut lexer,
+        &mut (&mut visitor as &mut dyn visit::DynChildren<'kdl>),
+    )
+}
+
+/// Whether `source` is exactly one valid string literal.
+pub(crate) fn is_string_literal(source: &str) -> bool {
+    lexes_as(source, Token::String(true))
+}
+
+/// Whether `source` is exactly one number literal.
+pub(crate) fn is_number_literal(source: &str) -> bool {
+    lexes_as(source, Token::Number)
+}
+
+fn lexes_as(source: &str, token: Token) -> bool {
+    let lexer = Lexer::new(source);
+    lexer.token1() == Some(token) && lexer.span1().len() == source.len()
+}
+
 #[cfg_attr(feature = "tracing",
     tracing::instrument(level = tracing::Level::TRACE, skip_all, fields(at = ?lexer.ll3()))
 )]
 fn visit_document<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Children<'kdl>,
+    visitor: &mut impl ParseChildren<'kdl>,
 ) -> Result<(), ParseError> {
-    visit_children(lexer, visitor)?;
-    match lexer.token1() {
-        #[cfg(debug_assertions)]
-        Some(_) => unreachable!("visit_children should have consumed all tokens"),
-        _ => Ok(()),
+    loop {
+        visit_children(lexer, visitor)?;
+        if let Some(Token::CloseBrace) = lexer.token1() {
+            visitor.visit_error(ParseError::UnmatchedCloseBrace {
+                span: lexer.span1(),
+                _private: (),
+            })?;
+            visit_token(lexer, visitor.opaque());
+        } else {
+            break;
+        }
+    }
+
+    if let Some(span) = lexer.take_unclosed_comment() {
+        visitor.visit_error(ParseError::UnclosedBlockComment { span, _private: () })?;
     }
+    Ok(())
 }
 
 #[cfg_attr(feature = "tracing",
@@ -61,12 +217,20 @@ fn visit_document<'kdl>(
 )]
 fn visit_children<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Children<'kdl>,
+    visitor: &mut impl ParseChildren<'kdl>,
 ) -> Result<(), ParseError> {
     loop {
-        visit_linespace_trivia(lexer, visitor.opaque())?;
-        if !try_visit_child(lexer, visitor)? {
-            break;
+        visit_linespace_trivia(lexer, visitor)?;
+        if try_visit_child(lexer, visitor)? {
+            continue;
+        }
+        match lexer.token1() {
+            None | Some(Token::CloseBrace) => break,
+            Some(_) => {
+                let error = unexpected(lexer, strings::a_node_or_close_brace);
+                visitor.visit_error(error)?;
+                skip_past(lexer, error_end(lexer, error), visitor.opaque());
+            }
         }
     }
 
@@ -78,22 +242,26 @@ fn visit_children<'kdl>(
 )]
 fn visit_linespace_trivia<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Trivia<'kdl>,
+    visitor: &mut impl ParseChildren<'kdl>,
 ) -> Result<bool, ParseError> {
     let mut has_linespace = false;
 
     loop {
         match lexer.token1() {
             Some(Token::Newline | Token::Whitespace) => {
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visit_token(lexer, visitor.opaque());
             }
             Some(Token::SlashDash) => {
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
-                let slashdash_visitor = &mut visitor.just_trivia();
-                visit_nodespace_trivia(lexer, slashdash_visitor)?;
-                visit_node(lexer, slashdash_visitor)?;
+                visit_token(lexer, visitor.opaque());
+                visit_nodespace_trivia(lexer, &mut visitor.only_trivia())?;
+                if visitor.visit_slashdash_node() {
+                    let mut visitor = guard(&mut *visitor, |visitor| {
+                        visitor.finish_slashdash_node();
+                    });
+                    visitor.parse_node(lexer)?;
+                } else {
+                    visit_node(lexer, &mut visitor.only_trivia())?;
+                }
             }
             _ => break,
         }
@@ -108,7 +276,7 @@ fn visit_linespace_trivia<'kdl>(
 )]
 fn try_visit_child<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Children<'kdl>,
+    visitor: &mut impl ParseChildren<'kdl>,
 ) -> Result<bool, ParseError> {
     match lexer.token1() {
         Some(
@@ -120,11 +288,7 @@ fn try_visit_child<'kdl>(
             | Token::False
             | Token::Null,
         ) => {
-            let mut node_visitor = guard(visitor.visit_node(), |node_visitor| {
-                visitor.finish_node(node_visitor);
-            });
-            visit_node(lexer, &mut *node_visitor)?;
-            drop(node_visitor);
+            visitor.parse_node(lexer)?;
             Ok(true)
         }
         _ => Ok(false),
@@ -136,7 +300,7 @@ fn try_visit_child<'kdl>(
 )]
 fn visit_nodespace_trivia<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    visitor: &mut impl ParseNode<'kdl>,
 ) -> Result<bool, ParseError> {
     let mut has_nodespace = false;
 
@@ -144,15 +308,16 @@ fn visit_nodespace_trivia<'kdl>(
         match lexer.token1() {
             Some(Token::EscLine) => visit_escline_trivia(lexer, visitor)?,
             Some(Token::Whitespace) => {
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visit_token(lexer, visitor.opaque());
+            }
+            Some(Token::Error) if lexer.slice1() == "," => {
+                visitor.visit_error(unexpected(lexer, strings::a_node_or_close_brace))?;
+                visit_token(lexer, visitor.opaque());
             }
             Some(Token::SlashDash) => {
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
-                let slashdash_visitor = &mut visitor.only_trivia();
-                visit_nodespace_trivia(lexer, slashdash_visitor)?;
-                try_visit_node_entry(lexer, slashdash_visitor, true)?;
+                visit_token(lexer, visitor.opaque());
+                visit_nodespace_trivia(lexer, visitor)?;
+                visit_slashdashed_entry(lexer, visitor)?;
             }
             _ => break,
         }
@@ -162,29 +327,57 @@ fn visit_nodespace_trivia<'kdl>(
     Ok(has_nodespace)
 }
 
-const NEWLINE_CHARS: [char; 6] = ['\r', '\n', '\u{85}', '\u{0C}', '\u{2028}', '\u{2029}'];
+#[cfg_attr(feature = "tracing",
+    tracing::instrument(level = tracing::Level::TRACE, skip_all, fields(at = ?lexer.ll3()))
+)]
+fn visit_slashdashed_entry<'kdl>(
+    lexer: &mut Lexer<'kdl>,
+    visitor: &mut impl ParseNode<'kdl>,
+) -> Result<(), ParseError> {
+    let finish = match lexer.token1() {
+        Some(Token::OpenBrace) if visitor.visit_slashdash_children() => {
+            ParseNode::finish_slashdash_children
+        }
+        Some(
+            Token::OpenParen
+            | Token::BareIdentifier
+            | Token::String(_)
+            | Token::Number
+            | Token::True
+            | Token::False
+            | Token::Null,
+        ) if visitor.visit_slashdash_entry() => ParseNode::finish_slashdash_entry,
+        _ => {
+            try_visit_node_entry(lexer, &mut visitor.only_trivia(), true)?;
+            return Ok(());
+        }
+    };
+    let mut visitor = guard(visitor, finish);
+    try_visit_node_entry(lexer, &mut **visitor, true)?;
+    Ok(())
+}
+
+pub(crate) const NEWLINE_CHARS: [char; 6] =
+    ['\r', '\n', '\u{85}', '\u{0C}', '\u{2028}', '\u{2029}'];
 
 #[cfg_attr(feature = "tracing",
     tracing::instrument(level = tracing::Level::TRACE, skip_all, fields(at = ?lexer.ll3()))
 )]
 fn visit_escline_trivia<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    visitor: &mut impl ParseNode<'kdl>,
 ) -> Result<(), ParseError> {
     if let Some(Token::EscLine) = lexer.token1() {
         let span = lexer.span1();
-        visitor.visit_trivia(lexer.slice1());
-        lexer.bump();
+        visit_token(lexer, visitor.opaque());
         loop {
             match lexer.token1() {
                 Some(Token::Newline) => {
-                    visitor.visit_trivia(lexer.slice1());
-                    lexer.bump();
+                    visit_token(lexer, visitor.opaque());
                     return Ok(());
                 }
                 Some(Token::Whitespace) => {
-                    visitor.visit_trivia(lexer.slice1());
-                    lexer.bump();
+                    visit_token(lexer, visitor.opaque());
                 }
                 None => {
                     visitor.visit_error(ParseError::EscapedEof { span, _private: () })?;
@@ -199,13 +392,11 @@ fn visit_escline_trivia<'kdl>(
                                     escape: span,
                                     span: (start..lexer.span1().start).into(),
                                 })?;
-                                visitor.visit_trivia(lexer.slice1());
-                                lexer.bump();
+                                visit_token(lexer, visitor.opaque());
                                 return Ok(());
                             }
                             Some(_) if !lexer.slice1().contains(NEWLINE_CHARS) => {
-                                visitor.visit_trivia(lexer.slice1());
-                                lexer.bump();
+                                visit_token(lexer, visitor.opaque());
                             }
                             _ => {
                                 let end = if start != lexer.span1().start {
@@ -225,8 +416,7 @@ fn visit_escline_trivia<'kdl>(
                         if matches!(token, Token::Newline) {
                             return Ok(());
                         }
-                        visitor.visit_trivia(lexer.slice1());
-                        lexer.bump();
+                        visit_token(lexer, visitor.opaque());
                     }
                     return Ok(()); // eof; don't error again; it's unuseful.
                 }
@@ -242,16 +432,18 @@ fn visit_escline_trivia<'kdl>(
 )]
 fn visit_node<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    visitor: &mut impl ParseNode<'kdl>,
 ) -> Result<(), ParseError> {
+    let (source, start) = (lexer.source(), lexer.span1().start);
+    lexer.blocks().start_node(source, start);
+
     let has_type_annotation;
-    fn recover<'kdl>(lexer: &mut Lexer<'kdl>, visitor: &mut impl visit::Node<'kdl>) {
+    fn recover<'kdl>(lexer: &mut Lexer<'kdl>, visitor: &mut impl ParseNode<'kdl>) {
         while let Some(token) = lexer.token1() {
             if matches!(token, Token::CloseBrace | Token::Newline | Token::Semicolon) {
                 break;
             }
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_token(lexer, visitor.opaque());
         }
     }
 
@@ -267,8 +459,7 @@ fn visit_node<'kdl>(
                 span: lexer.span1(),
                 _private: (),
             })?;
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_token(lexer, visitor.opaque());
         }
     } else {
         has_type_annotation = false;
@@ -276,8 +467,10 @@ fn visit_node<'kdl>(
 
     match lexer.token1() {
         Some(Token::BareIdentifier | Token::String(_)) => {
+            let name = lexer.span1();
             let id = parse_identifier(lexer, visitor.opaque())?;
             visitor.visit_name(id);
+            lexer.blocks().name_node(name);
         }
         Some(Token::Number | Token::True | Token::False | Token::Null) => {
             visitor.visit_error(ParseError::BareValue {
@@ -315,14 +508,12 @@ fn visit_type_annotation<'kdl>(
     visitor: &mut impl visit::JustType<'kdl>,
 ) -> Result<bool, ParseError> {
     if let Some(Token::OpenParen) = lexer.token1() {
-        visitor.visit_trivia(lexer.slice1());
-        lexer.bump();
+        visit_token(lexer, visitor);
 
         let leading_whitespace;
         if let Some(Token::Whitespace) = lexer.token1() {
             leading_whitespace = Some(lexer.span1());
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_token(lexer, visitor);
         } else {
             leading_whitespace = None;
         }
@@ -338,8 +529,7 @@ fn visit_type_annotation<'kdl>(
                     span: lexer.span1(),
                     _private: (),
                 })?;
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visit_token(lexer, visitor);
             }
             token => {
                 if let Some(span) = leading_whitespace {
@@ -362,24 +552,18 @@ fn visit_type_annotation<'kdl>(
         let trailing_whitespace;
         if let Some(Token::Whitespace) = lexer.token1() {
             trailing_whitespace = Some(lexer.span1());
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_token(lexer, visitor);
         } else {
             trailing_whitespace = None;
         }
 
         match (lexer.token1(), (leading_whitespace, trailing_whitespace)) {
             (Some(Token::CloseParen), (None, None)) => {
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visit_token(lexer, visitor);
             }
             (Some(Token::CloseParen), (Some(span), span2) | (span2, Some(span))) => {
-                visitor.visit_error(ParseError::InvalidWhitespaceInType {
-                    span,
-                    span2: span2.map(Into::into),
-                })?;
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visitor.visit_error(ParseError::InvalidWhitespaceInType { span, span2 })?;
+                visit_token(lexer, visitor);
             }
             (token, (_, trailing_whitespace)) => {
                 if let Some(span) = trailing_whitespace {
@@ -410,7 +594,7 @@ fn visit_type_annotation<'kdl>(
 )]
 fn visit_node_entries<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    visitor: &mut impl ParseNode<'kdl>,
 ) -> Result<(), ParseError> {
     loop {
         // line-space is required before properties/arguments but not children.
@@ -428,13 +612,13 @@ fn visit_node_entries<'kdl>(
 )]
 fn try_visit_node_entry<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    visitor: &mut impl ParseNode<'kdl>,
     has_leading_nodespace: bool,
 ) -> Result<bool, ParseError> {
     macro_rules! requiring_leading_space {
-        ($visit:path, $is_property:expr) => {{
+        ($parse:ident, $is_property:expr) => {{
             let start = lexer.span1().start;
-            $visit(lexer, visitor)?;
+            visitor.$parse(lexer)?;
             let end = lexer.span1().start;
             if !has_leading_nodespace {
                 if $is_property {
@@ -453,80 +637,136 @@ fn try_visit_node_entry<'kdl>(
     }
 
     match lexer.token1() {
+        Some(Token::BareIdentifier) if lexer.slice1() == ":" => {
+            // JSON-style `"key": value`
+            visitor.visit_error(ParseError::ColonSeparator {
+                span: lexer.span1(),
+                value: None,
+                equals: None,
+            })?;
+            visit_token(lexer, visitor.opaque());
+        }
+        Some(Token::BareIdentifier)
+            if lexer.slice1().len() > 1
+                && lexer.slice1().ends_with(':')
+                && lexer.token2() == Some(Token::Whitespace)
+                && matches!(
+                    lexer.token3(),
+                    Some(
+                        Token::BareIdentifier
+                            | Token::String(true)
+                            | Token::Number
+                            | Token::True
+                            | Token::False
+                            | Token::Null
+                    )
+                ) =>
+        {
+            // YAML-style `key: value` within a node, which is lexed as one
+            // identifier ending in a colon
+            let colon = lexer.span1().end - 1;
+            visit_token(lexer, visitor.opaque());
+            let equals = Span::from(colon..lexer.span1().end);
+            visit_token(lexer, visitor.opaque());
+            let value = (lexer.token1() == Some(Token::BareIdentifier)).then(|| lexer.span1());
+            visitor.visit_error(ParseError::ColonSeparator {
+                span: Span::from(colon..colon + 1),
+                value,
+                equals: Some(equals),
+            })?;
+            visit_token(lexer, visitor.opaque());
+        }
         Some(Token::BareIdentifier | Token::String(_)) => match (lexer.token2(), lexer.token3()) {
             (Some(Token::Equals), _) | (Some(Token::Whitespace), Some(Token::Equals)) => {
-                requiring_leading_space!(visit_node_property, true);
+                requiring_leading_space!(parse_property, true);
             }
-            _ => requiring_leading_space!(visit_node_argument, false),
+            _ => requiring_leading_space!(parse_argument, false),
         },
         Some(Token::OpenParen | Token::Number | Token::True | Token::False | Token::Null) => {
-            requiring_leading_space!(visit_node_argument, false);
+            requiring_leading_space!(parse_argument, false);
         }
 
         Some(Token::OpenBrace) => {
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            let open = lexer.span1();
+            visit_token(lexer, visitor.opaque());
 
-            let mut children_visitor = guard(visitor.visit_children(), |children_visitor| {
-                visitor.finish_children(children_visitor);
-            });
-            visit_children(lexer, &mut *children_visitor)?;
-            drop(children_visitor);
+            lexer.open_block();
+            let children = visitor.parse_children(lexer);
+            let block = lexer.blocks().close();
+            children?;
 
+            let (source, end) = (lexer.source(), lexer.span1());
             match lexer.token1() {
-                Some(Token::CloseBrace) => {
-                    visitor.visit_trivia(lexer.slice1());
-                    lexer.bump();
-                    return Ok(false);
-                }
-                #[cfg(debug_assertions)]
-                Some(_) => unreachable!("visit_children should have consumed all tokens"),
-                #[allow(unreachable_patterns)]
-                Some(token) => {
-                    let err = ParseError::Generic {
-                        span: lexer.span1(),
-                        found: strings::a(token),
-                        expected: strings::a(Token::CloseBrace),
-                    };
-                    visitor.visit_error(err)?;
-                    return Err(err); // Fatal; should not happen
+                Some(Token::CloseBrace)
+                    if !block.map_or(false, |block| {
+                        lexer.blocks().closes_outer(source, end.start, block)
+                    }) =>
+                {
+                    visit_token(lexer, visitor.opaque());
                 }
-                None => {
-                    visitor.visit_error(ParseError::Generic {
-                        span: lexer.span1(),
-                        found: strings::eof,
-                        expected: strings::a(Token::CloseBrace),
+                token => {
+                    // leave a dedented `}` for the block it lines up with
+                    if token.is_none() {
+                        if let Some(span) = lexer.take_unclosed_comment() {
+                            visitor.visit_error(ParseError::UnclosedBlockComment {
+                                span,
+                                _private: (),
+                            })?;
+                        }
+                    }
+                    visitor.visit_error(ParseError::UnclosedChildren {
+                        span: open,
+                        end,
+                        dedent: block
+                            .and_then(|block| block.dedent)
+                            .map(|at| Span::from(at..at)),
                     })?;
-                    return Ok(false);
                 }
             }
+            return Ok(false);
         }
 
         None => return Ok(false),
         Some(Token::Newline | Token::Semicolon) => {
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_token(lexer, visitor.opaque());
             return Ok(false);
         }
 
-        Some(token) => {
-            visitor.visit_error(ParseError::Generic {
+        Some(Token::Equals) => {
+            visitor.visit_error(ParseError::NodeAssignment {
                 span: lexer.span1(),
-                found: strings::a(token),
-                expected: strings::a_node_or_close_brace,
+                remove: removable_separator(lexer),
             })?;
+            visit_token(lexer, visitor.opaque());
+            visit_nodespace_trivia(lexer, visitor)?;
+            return try_visit_node_entry(lexer, visitor, true);
+        }
+
+        Some(Token::CloseBrace) if lexer.blocks().any_open() => {
+            // leave the `}` to close the block, but the node is unterminated
+            visitor.visit_error(unexpected(lexer, strings::a_node_terminator))?;
+            return Ok(false);
+        }
+        Some(Token::CloseBrace) => {
+            visitor.visit_error(ParseError::UnmatchedCloseBrace {
+                span: lexer.span1(),
+                _private: (),
+            })?;
+            visit_token(lexer, visitor.opaque());
+        }
+
+        Some(_) => {
+            visitor.visit_error(unexpected(lexer, strings::a_node_or_close_brace))?;
             loop {
                 match lexer.token1() {
                     Some(Token::Newline | Token::Semicolon) => {
-                        visitor.visit_trivia(lexer.slice1());
-                        lexer.bump();
+                        visit_token(lexer, visitor.opaque());
                         return Ok(false);
                     }
                     Some(Token::Whitespace) => return Ok(true),
                     None => return Ok(false),
                     Some(_) => {
-                        visitor.visit_trivia(lexer.slice1());
-                        lexer.bump();
+                        visit_token(lexer, visitor.opaque());
                     }
                 }
             }
@@ -539,35 +779,29 @@ fn try_visit_node_entry<'kdl>(
 #[cfg_attr(feature = "tracing",
     tracing::instrument(level = tracing::Level::TRACE, skip_all, fields(at = ?lexer.ll3()))
 )]
-fn visit_node_property<'kdl>(
+fn visit_property<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    property_visitor: &mut impl visit::Property<'kdl>,
 ) -> Result<(), ParseError> {
-    let mut property_visitor = guard(visitor.visit_property(), |property_visitor| {
-        visitor.finish_property(property_visitor);
-    });
     let name = parse_identifier(lexer, property_visitor.opaque())?;
     property_visitor.visit_name(name);
 
     let leading_whitespace;
     if let Some(Token::Whitespace) = lexer.token1() {
         leading_whitespace = Some(lexer.span1());
-        property_visitor.visit_trivia(lexer.slice1());
-        lexer.bump();
+        visit_token(lexer, property_visitor.opaque());
     } else {
         leading_whitespace = None;
     }
 
     assert_eq!(lexer.token1(), Some(Token::Equals));
     let eq_span = lexer.span1();
-    property_visitor.visit_trivia(lexer.slice1());
-    lexer.bump();
+    visit_token(lexer, property_visitor.opaque());
 
     let trailing_whitespace;
     if let Some(Token::Whitespace) = lexer.token1() {
         trailing_whitespace = Some(lexer.span1());
-        property_visitor.visit_trivia(lexer.slice1());
-        lexer.bump();
+        visit_token(lexer, property_visitor.opaque());
     } else {
         trailing_whitespace = None;
     }
@@ -575,10 +809,8 @@ fn visit_node_property<'kdl>(
     match (leading_whitespace, trailing_whitespace) {
         (None, None) => (),
         (Some(span), span2) | (span2, Some(span)) => {
-            property_visitor.visit_error(ParseError::InvalidWhitespaceInProperty {
-                span,
-                span2: span2.map(Into::into),
-            })?;
+            property_visitor
+                .visit_error(ParseError::InvalidWhitespaceInProperty { span, span2 })?;
         }
     }
 
@@ -589,21 +821,16 @@ fn visit_node_property<'kdl>(
         })?;
     }
 
-    drop(property_visitor);
     Ok(())
 }
 
 #[cfg_attr(feature = "tracing",
     tracing::instrument(level = tracing::Level::TRACE, skip_all, fields(at = ?lexer.ll3()))
 )]
-fn visit_node_argument<'kdl>(
+fn visit_argument<'kdl>(
     lexer: &mut Lexer<'kdl>,
-    visitor: &mut impl visit::Node<'kdl>,
+    argument_visitor: &mut impl visit::Argument<'kdl>,
 ) -> Result<(), ParseError> {
-    let mut argument_visitor = guard(visitor.visit_argument(), |argument_visitor| {
-        visitor.finish_argument(argument_visitor);
-    });
-
     match lexer.token1() {
         Some(Token::String(_) | Token::Number | Token::True | Token::False | Token::Null) => {
             let start = lexer.span1().start;
@@ -619,15 +846,13 @@ fn visit_node_argument<'kdl>(
             {
                 let end = lexer.span1().start;
                 if matches!(lexer.token1(), Some(Token::Whitespace)) {
-                    argument_visitor.visit_trivia(lexer.slice1());
-                    lexer.bump();
+                    visit_token(lexer, argument_visitor.opaque());
                 }
                 argument_visitor.visit_error(ParseError::UnquotedPropertyName {
                     span: (start..end).into(),
                     _private: (),
                 })?;
-                argument_visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visit_token(lexer, argument_visitor.opaque());
                 try_visit_value(lexer, &mut argument_visitor.only_trivia())?;
             }
         }
@@ -639,12 +864,7 @@ fn visit_node_argument<'kdl>(
         }
 
         Some(Token::BareIdentifier) => {
-            argument_visitor.visit_error(ParseError::UnquotedValue {
-                span: lexer.span1(),
-                _private: (),
-            })?;
-            argument_visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_bare_value(lexer, argument_visitor.opaque())?;
         }
 
         got => {
@@ -652,7 +872,6 @@ fn visit_node_argument<'kdl>(
         }
     }
 
-    drop(argument_visitor);
     Ok(())
 }
 
@@ -675,8 +894,7 @@ fn try_visit_value<'kdl>(
                     span: lexer.span1(),
                     _private: (),
                 })?;
-                visitor.visit_trivia(lexer.slice1());
-                lexer.bump();
+                visit_token(lexer, visitor);
             }
             match lexer.token1() {
                 Some(
@@ -686,12 +904,7 @@ fn try_visit_value<'kdl>(
                     visitor.visit_value(value);
                 }
                 Some(Token::BareIdentifier) => {
-                    visitor.visit_error(ParseError::UnquotedValue {
-                        span: lexer.span1(),
-                        _private: (),
-                    })?;
-                    visitor.visit_trivia(lexer.slice1());
-                    lexer.bump();
+                    visit_bare_value(lexer, visitor)?;
                 }
                 got => {
                     let err = ParseError::Generic {
@@ -705,12 +918,7 @@ fn try_visit_value<'kdl>(
             }
         }
         Some(Token::BareIdentifier) => {
-            visitor.visit_error(ParseError::UnquotedValue {
-                span: lexer.span1(),
-                _private: (),
-            })?;
-            visitor.visit_trivia(lexer.slice1());
-            lexer.bump();
+            visit_bare_value(lexer, visitor)?;
         }
         _ => return Ok(false),
     }
@@ -718,6 +926,266 @@ fn try_visit_value<'kdl>(
     Ok(true)
 }
 
+/// Report a bare identifier used as a value and move past it, recognizing
+/// strings quoted the way other languages quote them and YAML-style
+/// `key: value` pairs.
+fn visit_bare_value<'kdl>(
+    lexer: &mut Lexer<'kdl>,
+    visitor: &mut impl visit::Trivia<'kdl>,
+) -> Result<(), ParseError> {
+    let (source, span) = (lexer.source(), lexer.span1());
+    let error = if let Some(error) = misquoted_string(source, span.start) {
+        error
+    } else if let Some(colon) = key_colon(source, span.start) {
+        ParseError::ColonSeparator {
+            span: colon,
+            value: Some(span),
+            equals: None,
+        }
+    } else {
+        ParseError::UnquotedValue { span, _private: () }
+    };
+    visitor.visit_error(error)?;
+    skip_past(lexer, error_end(lexer, error), visitor);
+    Ok(())
+}
+
+/// A string starting at `start` which is quoted with single or curly quotes
+/// rather than double quotes, if it can be fixed by just replacing them.
+///
+/// A string opened with a curly quote may be closed with a straight one, as
+/// happens when only some quotes are replaced by a word processor.
+fn misquoted_string(source: &str, start: usize) -> Option<ParseError> {
+    let rest = &source[start..];
+    let quote = rest.chars().next()?;
+    let closes: &[char] = match quote {
+        '\'' => &['\''],
+        '\u{2018}' | '\u{2019}' => &['\u{2018}', '\u{2019}', '\''],
+        '\u{201C}' | '\u{201D}' | '\u{201E}' => &['\u{201C}', '\u{201D}', '"'],
+        _ => return None,
+    };
+    let line = &rest[quote.len_utf8()..];
+    let line = &line[..line.find(NEWLINE_CHARS).unwrap_or(line.len())];
+    // the first closing quote which ends the value
+    let ends_value =
+        |c: Option<char>| c.map_or(true, |c| c.is_whitespace() || matches!(c, ';' | '}' | '/'));
+    let (content, close) = line
+        .match_indices(closes)
+        .find(|&(i, close)| ends_value(line[i + close.len()..].chars().next()))?;
+    if line[..content].contains(['"', '\\']) {
+        return None;
+    }
+    let open = Span::from(start..start + quote.len_utf8());
+    let close = Span::from(open.end + content..open.end + content + close.len());
+    let span = Span::from(open.start..close.end);
+    Some(match quote {
+        '\'' => ParseError::SingleQuotedString { span, _private: () },
+        _ => ParseError::SmartQuotedString { span, open, close },
+    })
+}
+
+/// The colon ending a node name before the value at `start`, as in YAML's
+/// `key: value`.
+fn key_colon(source: &str, start: usize) -> Option<Span> {
+    let before = &source[..start];
+    let key = before.trim_end_matches(|c: char| c.is_whitespace() && !NEWLINE_CHARS.contains(&c));
+    let name = key[line_start(key, key.len())..].trim_start();
+    let spaced = key.len() < before.len();
+    let attached = name.len() > 1 && !name.ends_with("\":");
+    if !spaced || !attached || !name.ends_with(':') || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(Span::from(key.len() - 1..key.len()))
+}
+
+/// The error for an unexpected token, recognizing common mistakes.
+fn unexpected(lexer: &Lexer<'_>, expected: &'static str) -> ParseError {
+    let (source, span) = (lexer.source(), lexer.span1());
+    match lexer.token1() {
+        Some(Token::Reserved) => {
+            section_header(source, span.start).unwrap_or_else(|| ParseError::ReservedCharacter {
+                span,
+                character: lexer.slice1().chars().next().unwrap_or_default(),
+            })
+        }
+        Some(Token::Error) if lexer.slice1() == "," => ParseError::CommaSeparator {
+            span,
+            remove: removable_separator(lexer),
+        },
+        token => ParseError::Generic {
+            span,
+            found: token.map(strings::a).unwrap_or(strings::eof),
+            expected,
+        },
+    }
+}
+
+/// The separator at the current token with the whitespace after it, if there
+/// is whitespace before it, so that removing it leaves one side's whitespace
+/// between the values. `None` if neither side of it is separated.
+fn removable_separator(lexer: &Lexer<'_>) -> Option<Span> {
+    let (source, span) = (lexer.source(), lexer.span1());
+    let is_space = |c: char| c.is_whitespace() && !NEWLINE_CHARS.contains(&c);
+    let after = source[span.end..].trim_start_matches(is_space);
+    let spaced_after = matches!(
+        lexer.token2(),
+        None | Some(Token::Whitespace | Token::Newline | Token::Semicolon | Token::CloseBrace)
+    );
+    if source[..span.start].ends_with(is_space) {
+        Some(Span::from(span.start..source.len() - after.len()))
+    } else if spaced_after {
+        Some(span)
+    } else {
+        None
+    }
+}
+
+/// A TOML or INI section header on its own line starting at `start`, such as
+/// `[section]` or `[[array.of.tables]]`.
+fn section_header(source: &str, start: usize) -> Option<ParseError> {
+    if !source[line_start(source, start)..start].trim().is_empty() {
+        return None;
+    }
+    let rest = &source[start..];
+    let line = rest[..rest.find(NEWLINE_CHARS).unwrap_or(rest.len())].trim_end();
+    let name = line.trim_start_matches('[').trim_end_matches(']');
+    let brackets = line.len() - name.len();
+    if !line.starts_with('[') || !line.ends_with(']') || brackets % 2 != 0 {
+        return None;
+    }
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    let name_start = start + brackets / 2;
+
+    // the section ends before the next one, or at the end of file
+    let header_end = start + line.len();
+    let mut end = header_end;
+    for next in source[header_end..].split_inclusive(NEWLINE_CHARS) {
+        if end != header_end && next.trim_start().starts_with('[') {
+            break;
+        }
+        end += next.len();
+    }
+    let end = source[..end].trim_end_matches(NEWLINE_CHARS).len();
+
+    Some(ParseError::SectionHeader {
+        span: Span::from(start..start + line.len()),
+        name: Span::from(name_start..name_start + name.len()),
+        end,
+    })
+}
+
+/// Where the source covered by an error, which should be skipped, ends.
+fn error_end(lexer: &Lexer<'_>, error: ParseError) -> usize {
+    match error {
+        ParseError::SectionHeader { span, .. }
+        | ParseError::SingleQuotedString { span, .. }
+        | ParseError::SmartQuotedString { span, .. } => span.end,
+        _ => lexer.span1().end,
+    }
+}
+
+/// Visit tokens as trivia until reaching `end`.
+///
+/// A token running past `end`, such as a string opened by a straight quote
+/// closing a curly quoted one, is cut short there.
+fn skip_past<'kdl>(lexer: &mut Lexer<'kdl>, end: usize, visitor: &mut impl visit::Trivia<'kdl>) {
+    while lexer.token1().is_some() && lexer.span1().start < end {
+        if lexer.span1().end > end {
+            visitor.visit_trivia(&lexer.source()[lexer.span1().start..end]);
+            lexer.relex_from(end);
+            break;
+        }
+        visit_token(lexer, visitor);
+    }
+}
+
+/// Where the line containing `offset` starts.
+pub(crate) fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind(NEWLINE_CHARS).map_or(0, |i| {
+        i + source[i..].chars().next().map_or(0, char::len_utf8)
+    })
+}
+
+/// Visit the current token as trivia and move past it.
+///
+/// If the visitor splits trivia, whitespace is split into comments and the
+/// whitespace around them, and runs of newlines are split into single
+/// newlines.
+fn visit_token<'kdl>(lexer: &mut Lexer<'kdl>, visitor: &mut impl visit::Trivia<'kdl>) {
+    let source = lexer.slice1();
+    match lexer.token1() {
+        _ if !lexer.split_trivia() => visitor.visit_trivia(source),
+        Some(Token::Whitespace) => visit_whitespace(source, lexer.span1().start, visitor),
+        Some(Token::Newline) => {
+            let mut rest = source;
+            while let Some(c) = rest.chars().next() {
+                let len = if rest.starts_with("\r\n") {
+                    2
+                } else {
+                    c.len_utf8()
+                };
+                let (newline, tail) = rest.split_at(len);
+                visitor.visit_newline(newline);
+                rest = tail;
+            }
+        }
+        Some(Token::EscLine) => visitor.visit_escline(source),
+        _ => visitor.visit_trivia(source),
+    }
+    lexer.bump();
+}
+
+fn visit_whitespace<'kdl>(source: &'kdl str, start: usize, visitor: &mut impl visit::Trivia<'kdl>) {
+    let mut rest = source;
+    let mut offset = start;
+    while !rest.is_empty() {
+        let len = if rest.starts_with("//") {
+            rest.len()
+        } else if rest.starts_with("/*") {
+            block_comment_len(rest)
+        } else {
+            match rest.find('/') {
+                Some(0) | None => rest.len(),
+                Some(len) => len,
+            }
+        };
+        let (text, tail) = rest.split_at(len);
+        if text.starts_with('/') {
+            visitor.visit_comment(visit::Comment {
+                source: text,
+                span: (offset..offset + len).into(),
+            });
+        } else {
+            visitor.visit_trivia(text);
+        }
+        offset += len;
+        rest = tail;
+    }
+}
+
+fn block_comment_len(source: &str) -> usize {
+    let bytes = source.as_bytes();
+    let mut depth = 0_usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    source.len()
+}
+
 fn parse_identifier<'kdl>(
     lexer: &mut Lexer<'kdl>,
     visitor: &mut impl visit::Trivia<'kdl>,
@@ -27,6 +27,7 @@ pub(super) fn a(token: Token) -> &'static str {
 
 pub(super) const a_node_name: &str = "a node name (identifier)";
 pub(super) const a_node_or_close_brace: &str = "a node or a closing curly brace";
+pub(super) const a_node_terminator: &str = "a newline or semicolon";
 pub(super) const a_type_name: &str = "a type name (identifier)";
 pub(super) const a_value: &str = "a value (string, number, boolean, or null)";
 pub(super) const eof: &str = "the end of file";
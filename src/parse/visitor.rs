@@ -0,0 +1,377 @@
+//! The parser's view of the visitor traits.
+//!
+//! The parser visits child components through these traits rather than the
+//! visitor traits directly, so that it can drive both statically typed
+//! visitors (which return their child visitors) and `dyn` visitors (which
+//! take a continuation) without being instantiated for each `dyn` visitor.
+
+use {
+    super::Lexer,
+    crate::{
+        lint::Warning,
+        visit::{
+            self, DynChildren, DynDocument, DynNode, LendChildren, LendNode, ParseChildrenExt as _,
+            ParseNodeExt as _,
+        },
+        ParseError,
+    },
+};
+
+pub(crate) trait ParseChildren<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str);
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>);
+    fn visit_newline(&mut self, newline: &'kdl str);
+    fn visit_escline(&mut self, escline: &'kdl str);
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError>;
+    fn visit_slashdash_node(&mut self) -> bool;
+    fn finish_slashdash_node(&mut self);
+    fn parse_node(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError>;
+}
+
+pub(crate) trait ParseNode<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str);
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>);
+    fn visit_newline(&mut self, newline: &'kdl str);
+    fn visit_escline(&mut self, escline: &'kdl str);
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>);
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>);
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError>;
+    fn parse_argument(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError>;
+    fn parse_property(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError>;
+    fn parse_children(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError>;
+    fn visit_slashdash_entry(&mut self) -> bool;
+    fn finish_slashdash_entry(&mut self);
+    fn visit_slashdash_children(&mut self) -> bool;
+    fn finish_slashdash_children(&mut self);
+}
+
+impl<'kdl, V: LendChildren<'kdl>> ParseChildren<'kdl> for V {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        LendChildren::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        LendChildren::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        LendChildren::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        LendChildren::visit_escline(self, escline);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        LendChildren::visit_error(self, error)
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        LendChildren::visit_slashdash_node(self)
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        LendChildren::finish_slashdash_node(self);
+    }
+
+    fn parse_node(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        super::visit_node(lexer, &mut LendChildren::visit_node(self))
+    }
+}
+
+impl<'kdl, V: LendNode<'kdl>> ParseNode<'kdl> for V {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        LendNode::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        LendNode::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        LendNode::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        LendNode::visit_escline(self, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        LendNode::visit_type(self, annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        LendNode::visit_name(self, name);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        LendNode::visit_error(self, error)
+    }
+
+    fn parse_argument(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        super::visit_argument(lexer, &mut LendNode::visit_argument(self))
+    }
+
+    fn parse_property(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        super::visit_property(lexer, &mut LendNode::visit_property(self))
+    }
+
+    fn parse_children(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        super::visit_children(lexer, &mut LendNode::visit_children(self))
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        LendNode::visit_slashdash_entry(self)
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        LendNode::finish_slashdash_entry(self);
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        LendNode::visit_slashdash_children(self)
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        LendNode::finish_slashdash_children(self);
+    }
+}
+
+/// Upcasts a `dyn DynDocument` to `dyn DynChildren`.
+pub(super) struct DynRoot<'a, 'kdl>(pub(super) &'a mut dyn DynDocument<'kdl>);
+
+impl<'kdl> DynChildren<'kdl> for DynRoot<'_, 'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.0.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.0.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.0.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.0.visit_escline(escline);
+    }
+
+    fn visit_node(
+        &mut self,
+        node: &mut dyn FnMut(&mut dyn DynNode<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        self.0.visit_node(node)
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.0.visit_slashdash_node()
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.0.finish_slashdash_node();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.0.visit_error(error)
+    }
+}
+
+// If a `dyn` visitor doesn't call its continuation, the component still needs
+// to be parsed, so it is parsed with a `Skipped` visitor instead.
+
+/// Ignores a component which a `dyn` visitor didn't visit, except for its
+/// errors, which are given to that visitor to decide whether to continue.
+struct Skipped<'a, 'kdl>(&'a mut dyn visit::Trivia<'kdl>);
+
+impl<'kdl> Skipped<'_, 'kdl> {
+    fn child(&mut self) -> Skipped<'_, 'kdl> {
+        Skipped(&mut *self.0)
+    }
+}
+
+impl<'kdl> LendChildren<'kdl> for Skipped<'_, 'kdl> {
+    type VisitNode<'a>
+        = Skipped<'a, 'kdl>
+    where
+        Self: 'a;
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.0.visit_error(error)
+    }
+}
+
+impl<'kdl> LendNode<'kdl> for Skipped<'_, 'kdl> {
+    type VisitArgument<'a>
+        = Skipped<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = Skipped<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = Skipped<'a, 'kdl>
+    where
+        Self: 'a;
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child()
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child()
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.0.visit_error(error)
+    }
+}
+
+impl<'kdl> visit::Argument<'kdl> for Skipped<'_, 'kdl> {
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.0.visit_error(error)
+    }
+}
+
+impl<'kdl> visit::Property<'kdl> for Skipped<'_, 'kdl> {
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.0.visit_error(error)
+    }
+}
+
+impl<'kdl> ParseChildren<'kdl> for &mut (dyn DynChildren<'kdl> + '_) {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        DynChildren::visit_trivia(&mut **self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        DynChildren::visit_comment(&mut **self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        DynChildren::visit_newline(&mut **self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        DynChildren::visit_escline(&mut **self, escline);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        DynChildren::visit_error(&mut **self, error)
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        DynChildren::visit_slashdash_node(&mut **self)
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        DynChildren::finish_slashdash_node(&mut **self);
+    }
+
+    fn parse_node(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        let mut parsed = false;
+        DynChildren::visit_node(&mut **self, &mut |mut node_visitor| {
+            parsed = true;
+            super::visit_node(lexer, &mut node_visitor)
+        })?;
+        if !parsed {
+            super::visit_node(lexer, &mut Skipped(self.opaque()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'kdl> ParseNode<'kdl> for &mut (dyn DynNode<'kdl> + '_) {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        DynNode::visit_trivia(&mut **self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        DynNode::visit_comment(&mut **self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        DynNode::visit_newline(&mut **self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        DynNode::visit_escline(&mut **self, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        DynNode::visit_type(&mut **self, annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        DynNode::visit_name(&mut **self, name);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        DynNode::visit_error(&mut **self, error)
+    }
+
+    fn parse_argument(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        let mut parsed = false;
+        DynNode::visit_argument(&mut **self, &mut |mut argument_visitor| {
+            parsed = true;
+            super::visit_argument(lexer, &mut argument_visitor)
+        })?;
+        if !parsed {
+            super::visit_argument(lexer, &mut Skipped(self.opaque()))?;
+        }
+        Ok(())
+    }
+
+    fn parse_property(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        let mut parsed = false;
+        DynNode::visit_property(&mut **self, &mut |mut property_visitor| {
+            parsed = true;
+            super::visit_property(lexer, &mut property_visitor)
+        })?;
+        if !parsed {
+            super::visit_property(lexer, &mut Skipped(self.opaque()))?;
+        }
+        Ok(())
+    }
+
+    fn parse_children(&mut self, lexer: &mut Lexer<'kdl>) -> Result<(), ParseError> {
+        let mut parsed = false;
+        DynNode::visit_children(&mut **self, &mut |mut children_visitor| {
+            parsed = true;
+            super::visit_children(lexer, &mut children_visitor)
+        })?;
+        if !parsed {
+            super::visit_children(lexer, &mut Skipped(self.opaque()))?;
+        }
+        Ok(())
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        DynNode::visit_slashdash_entry(&mut **self)
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        DynNode::finish_slashdash_entry(&mut **self);
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        DynNode::visit_slashdash_children(&mut **self)
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        DynNode::finish_slashdash_children(&mut **self);
+    }
+}
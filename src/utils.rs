@@ -95,87 +95,6 @@ pub(crate) fn unescape(src: &str) -> impl '_ + fmt::Display {
     })
 }
 
-// pub(crate) fn valid_bareword(s: &str) -> bool {
-//     fn looks_like_number(s: &str) -> bool {
-//         s.starts_with(|c: char| c.is_ascii_digit())
-//             || (s.starts_with(['+', '-'])
-//                 && s.chars().nth(1).map_or(false, |c: char| c.is_ascii_digit()))
-//     }
-
-//     fn looks_like_string(s: &str) -> bool {
-//         s.starts_with(r#"r""#) || s.starts_with(r#"r#"#)
-//     }
-
-//     fn is_keyword(s: &str) -> bool {
-//         matches!(s, "true" | "false" | "null")
-//     }
-
-//     !(s.contains([
-//         '\\', '/', '(', ')', '{', '}', '<', '>', ';', '[', ']', '=', ',', '"',
-//     ]) || is_keyword(s)
-//         || looks_like_string(s)
-//         || looks_like_number(s))
-// }
-
-// pub(crate) fn bareword(s: &str) -> impl '_ + fmt::Display {
-//     display!(|f| {
-//         if valid_bareword(s) {
-//             write!(f, "{s}")
-//         } else {
-//             write!(f, "{}", escape(s))
-//         }
-//     })
-// }
-
-// pub(crate) fn escape(src: &str) -> impl '_ + fmt::Display {
-//     #[allow(unreachable_patterns)] // clarity
-//     fn should_escape(c: char) -> bool {
-//         match c {
-//             // KDL Escape Sequences should always be escaped
-//             '\n' | '\r' | '\t' | '\\' | '/' | '"' | '\u{8}' | '\u{C}' => true,
-//             // ASCII printable characters should never be escaped
-//             '\u{20}'..='\u{7e}' => false,
-//             // Other KDL Whitespace should never be escaped
-//             // '\t' => true (above)
-//             ' '
-//             | '\u{A0}'
-//             | '\u{1600}'
-//             | '\u{2000}'..='\u{200A}'
-//             | '\u{202F}'
-//             | '\u{205F}'
-//             | '\u{3000}' => false,
-//             // KDL Newline should always be escaped
-//             '\r' | '\n' | '\u{85}' | '\u{C}' | '\u{2028}' | '\u{2029}' => true,
-//             // Aproximate "printable" unicode characters to avoid overescaping
-//             c if c.is_alphanumeric() => false,
-//             _ => true,
-//         }
-//     }
-
-//     display!(|f| {
-//         let mut src = src;
-//         write!(f, "\"")?;
-//         while let Some(next_escaped) = src.find(should_escape) {
-//             write!(f, "{}", &src[..next_escaped])?;
-//             src = &src[next_escaped..];
-//             let escaped = src.chars().next().unwrap();
-//             match escaped {
-//                 '\r' => write!(f, r"\r")?,
-//                 '\n' => write!(f, r"\n")?,
-//                 '\t' => write!(f, r"\t")?,
-//                 '\\' => write!(f, r"\\")?,
-//                 '/' => write!(f, r"\/")?,
-//                 '"' => write!(f, r#"\""#)?,
-//                 '\u{8}' => write!(f, r"\b")?,
-//                 '\u{C}' => write!(f, r"\f")?,
-//                 c => write!(f, r"\u{:04X}", c as u32)?,
-//             }
-//             src = &src[escaped.len_utf8()..];
-//         }
-//         write!(f, "{}\"", src)
-//     })
-// }
-
 // pub(crate) fn escape_raw(src: &str) -> impl '_ + fmt::Display {
 //     let needs_hashes = src.contains('"');
 //     let hash_count = if needs_hashes {
@@ -0,0 +1,513 @@
+use {
+    crate::{lint::Warning, visit, ParseError, Span, ERROR_STRING},
+    core::{cell::Cell, fmt},
+};
+
+/// A channel for visitors to abort parsing with their own error.
+///
+/// Visitors hold a shared reference to the channel and [`raise`] an error
+/// when they find a problem, e.g. an unknown node. When parsing with
+/// [`try_visit_kdl_string`], the visitor is not called again after an error
+/// is raised, and the error is returned with the span of the component which
+/// was being visited when it was raised. Lending child visitors are still
+/// dropped, which finishes them.
+///
+/// ```
+/// # use kdl_visit::{try_visit_kdl_string, visit};
+/// # use visit::prelude::*;
+/// struct Config<'a>(&'a visit::Abort<&'static str>);
+///
+/// impl<'kdl> visit::Document<'kdl> for Config<'_> {
+///     type Output = ();
+///     fn finish(self) {}
+/// }
+///
+/// impl<'kdl, 'a> visit::Children<'kdl> for Config<'a> {
+///     type VisitNode = Config<'a>;
+///     fn visit_node(&mut self) -> Self::VisitNode {
+///         Config(self.0)
+///     }
+/// }
+///
+/// impl<'kdl> visit::Node<'kdl> for Config<'_> {
+///     type VisitArgument = ();
+///     type VisitProperty = ();
+///     type VisitChildren = ();
+///     fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+///         if name.source() != "known" {
+///             self.0.raise("unknown node");
+///         }
+///     }
+///     fn visit_argument(&mut self) {}
+///     fn visit_property(&mut self) {}
+///     fn visit_children(&mut self) {}
+/// }
+///
+/// let abort = visit::Abort::new();
+/// let result = try_visit_kdl_string("known 1\nunknown 2", &abort, Config(&abort));
+/// let error = result.unwrap_err();
+/// let aborted = match error {
+///     visit::Either::Right(aborted) => aborted,
+///     visit::Either::Left(error) => panic!("unexpected parse error: {error}"),
+/// };
+/// assert_eq!(aborted.error, "unknown node");
+/// assert_eq!(aborted.span, (8..15).into());
+/// ```
+///
+/// [`raise`]: Abort::raise
+/// [`try_visit_kdl_string`]: crate::try_visit_kdl_string
+pub struct Abort<E> {
+    raised: Cell<bool>,
+    error: Cell<Option<E>>,
+    span: Cell<Option<Span>>,
+}
+
+impl<E> Abort<E> {
+    pub const fn new() -> Self {
+        Abort {
+            raised: Cell::new(false),
+            error: Cell::new(None),
+            span: Cell::new(None),
+        }
+    }
+
+    /// Abort parsing with the given error.
+    ///
+    /// Only the first raised error is kept; later errors are discarded.
+    pub fn raise(&self, error: E) {
+        if !self.raised.get() {
+            self.error.set(Some(error));
+            self.raised.set(true);
+        }
+    }
+
+    /// Whether an error has been raised.
+    pub fn is_raised(&self) -> bool {
+        self.raised.get()
+    }
+
+    /// Take the raised error, resetting the channel.
+    pub fn take(&self) -> Option<E> {
+        self.raised.set(false);
+        self.span.set(None);
+        self.error.take()
+    }
+
+    fn take_aborted(&self, fallback: Span) -> Option<Aborted<E>> {
+        let span = self.span.get().unwrap_or(fallback);
+        self.take().map(|error| Aborted { span, error })
+    }
+}
+
+impl<E> Default for Abort<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> fmt::Debug for Abort<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Abort")
+            .field("raised", &self.raised.get())
+            .field("span", &self.span.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An error raised by a visitor through [`Abort`], with the span of the
+/// component being visited when it was raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Aborted<E> {
+    pub span: Span,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for Aborted<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error> std::error::Error for Aborted<E> {}
+
+#[cfg(feature = "miette")]
+impl<E: std::error::Error> miette::Diagnostic for Aborted<E> {
+    fn labels(&self) -> Option<std::boxed::Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(std::boxed::Box::new(core::iter::once(
+            miette::LabeledSpan::underline(self.span),
+        )))
+    }
+}
+
+/// Wraps a visitor to stop calling it once an error is raised through the
+/// [`Abort`] channel, and to locate the component it was raised in.
+///
+/// Child components borrow the cursor of their parent, and hand it back when
+/// they are dropped. Dropping a lending child visitor finishes it, so child
+/// visitors are still dropped after an abort, but not otherwise called.
+pub(crate) struct Abortable<'a, 'kdl, V, E> {
+    visitor: Option<V>,
+    abort: &'a Abort<E>,
+    source: &'kdl str,
+    start: usize,
+    cursor: usize,
+    parent: Option<&'a mut usize>,
+}
+
+impl<'a, 'kdl, V, E> Abortable<'a, 'kdl, V, E> {
+    pub(crate) fn new(source: &'kdl str, abort: &'a Abort<E>, visitor: V) -> Self {
+        Abortable {
+            visitor: Some(visitor),
+            abort,
+            source,
+            start: 0,
+            cursor: 0,
+            parent: None,
+        }
+    }
+
+    /// Call the visitor, unless an error has been raised.
+    fn call<R>(&mut self, span: Span, f: impl FnOnce(&mut V) -> R) -> Option<R> {
+        if self.abort.is_raised() {
+            self.visitor = None;
+        }
+        let result = f(self.visitor.as_mut()?);
+        blame(self.abort, span);
+        Some(result)
+    }
+
+    fn locate(&self, text: &str) -> Option<usize> {
+        let start = (text.as_ptr() as usize).checked_sub(self.source.as_ptr() as usize)?;
+        let end = start.checked_add(text.len())?;
+        (end <= self.source.len()).then_some(start)
+    }
+
+    /// Advance past source text, returning its span.
+    fn advance(&mut self, text: &'kdl str) -> Span {
+        let start = if let Some(start) = self.locate(text) {
+            start
+        } else if text == ERROR_STRING {
+            // The source of a broken string was already visited as trivia.
+            return (self.cursor..self.cursor).into();
+        } else {
+            // Keywords are not slices of the source.
+            self.cursor
+        };
+        self.cursor = start + text.len();
+        (start..self.cursor).into()
+    }
+
+    fn here(&self) -> Span {
+        (self.cursor..self.cursor).into()
+    }
+
+    fn child<'b, C>(&'b mut self, visit: impl FnOnce(&'b mut V) -> C) -> Abortable<'b, 'kdl, C, E> {
+        if self.abort.is_raised() {
+            self.visitor = None;
+        }
+        let here = (self.cursor..self.cursor).into();
+        let visitor = self.visitor.as_mut().map(visit);
+        blame(self.abort, here);
+        Abortable {
+            visitor,
+            abort: self.abort,
+            source: self.source,
+            start: self.cursor,
+            cursor: self.cursor,
+            parent: Some(&mut self.cursor),
+        }
+    }
+
+    fn visit_error(
+        &mut self,
+        error: ParseError,
+        visit: impl FnOnce(&mut V, ParseError) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        match self.call(self.here(), |v| visit(v, error)) {
+            Some(result) if !self.abort.is_raised() => result,
+            _ => Err(error),
+        }
+    }
+}
+
+/// Locate a newly raised error in the given span.
+fn blame<E>(abort: &Abort<E>, span: Span) {
+    if abort.is_raised() && abort.span.get().is_none() {
+        abort.span.set(Some(span));
+    }
+}
+
+impl<V, E> Drop for Abortable<'_, '_, V, E> {
+    fn drop(&mut self) {
+        // Dropping a child visitor finishes it, which may raise an error.
+        if self.visitor.take().is_some() {
+            blame(self.abort, (self.start..self.cursor).into());
+        }
+        if let Some(cursor) = &mut self.parent {
+            **cursor = self.cursor;
+        }
+    }
+}
+
+impl<'kdl, V, E> visit::LendDocument<'kdl> for Abortable<'_, 'kdl, V, E>
+where
+    V: visit::LendDocument<'kdl>,
+{
+    type Output = Result<V::Output, Aborted<E>>;
+
+    fn finish(mut self) -> Self::Output {
+        match self.abort.take_aborted(self.here()) {
+            Some(aborted) => Err(aborted),
+            None => Ok(self
+                .visitor
+                .take()
+                .expect("kdl visitor should only be dropped after an abort")
+                .finish()),
+        }
+    }
+
+    fn finish_error(mut self, error: ParseError) -> Result<Self::Output, ParseError> {
+        match self.abort.take_aborted(self.here()) {
+            Some(aborted) => Ok(Err(aborted)),
+            None => self
+                .visitor
+                .take()
+                .expect("kdl visitor should only be dropped after an abort")
+                .finish_error(error)
+                .map(Ok),
+        }
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.visitor.as_ref().map_or(false, V::split_trivia)
+    }
+}
+
+impl<'kdl, V, E> visit::LendChildren<'kdl> for Abortable<'_, 'kdl, V, E>
+where
+    V: visit::LendChildren<'kdl>,
+{
+    type VisitNode<'b>
+        = Abortable<'b, 'kdl, V::VisitNode<'b>, E>
+    where
+        Self: 'b;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        let span = self.advance(trivia);
+        self.call(span, |v| v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        let span = self.advance(comment.source());
+        self.call(span, |v| v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        let span = self.advance(newline);
+        self.call(span, |v| v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        let span = self.advance(escline);
+        self.call(span, |v| v.visit_escline(escline));
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child(|v| v.visit_node())
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.call(self.here(), |v| v.visit_slashdash_node())
+            .unwrap_or(false)
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.call(self.here(), |v| v.finish_slashdash_node());
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.call(self.here(), |v| v.visit_warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Abortable::visit_error(self, error, |v, error| v.visit_error(error))
+    }
+}
+
+impl<'kdl, V, E> visit::LendNode<'kdl> for Abortable<'_, 'kdl, V, E>
+where
+    V: visit::LendNode<'kdl>,
+{
+    type VisitArgument<'b>
+        = Abortable<'b, 'kdl, V::VisitArgument<'b>, E>
+    where
+        Self: 'b;
+    type VisitProperty<'b>
+        = Abortable<'b, 'kdl, V::VisitProperty<'b>, E>
+    where
+        Self: 'b;
+    type VisitChildren<'b>
+        = Abortable<'b, 'kdl, V::VisitChildren<'b>, E>
+    where
+        Self: 'b;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        let span = self.advance(trivia);
+        self.call(span, |v| v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        let span = self.advance(comment.source());
+        self.call(span, |v| v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        let span = self.advance(newline);
+        self.call(span, |v| v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        let span = self.advance(escline);
+        self.call(span, |v| v.visit_escline(escline));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        let span = self.advance(annotation.source());
+        self.call(span, |v| v.visit_type(annotation));
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        let span = self.advance(name.source());
+        self.call(span, |v| v.visit_name(name));
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child(|v| v.visit_argument())
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child(|v| v.visit_property())
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child(|v| v.visit_children())
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.call(self.here(), |v| v.visit_slashdash_entry())
+            .unwrap_or(false)
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.call(self.here(), |v| v.finish_slashdash_entry());
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        self.call(self.here(), |v| v.visit_slashdash_children())
+            .unwrap_or(false)
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        self.call(self.here(), |v| v.finish_slashdash_children());
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.call(self.here(), |v| v.visit_warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Abortable::visit_error(self, error, |v, error| v.visit_error(error))
+    }
+}
+
+impl<'kdl, V, E> visit::Property<'kdl> for Abortable<'_, 'kdl, V, E>
+where
+    V: visit::Property<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        let span = self.advance(trivia);
+        self.call(span, |v| v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        let span = self.advance(comment.source());
+        self.call(span, |v| v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        let span = self.advance(newline);
+        self.call(span, |v| v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        let span = self.advance(escline);
+        self.call(span, |v| v.visit_escline(escline));
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        let span = self.advance(name.source());
+        self.call(span, |v| v.visit_name(name));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        let span = self.advance(annotation.source());
+        self.call(span, |v| v.visit_type(annotation));
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        let span = self.advance(value.source());
+        self.call(span, |v| v.visit_value(value));
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.call(self.here(), |v| v.visit_warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Abortable::visit_error(self, error, |v, error| v.visit_error(error))
+    }
+}
+
+impl<'kdl, V, E> visit::Argument<'kdl> for Abortable<'_, 'kdl, V, E>
+where
+    V: visit::Argument<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        let span = self.advance(trivia);
+        self.call(span, |v| v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        let span = self.advance(comment.source());
+        self.call(span, |v| v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        let span = self.advance(newline);
+        self.call(span, |v| v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        let span = self.advance(escline);
+        self.call(span, |v| v.visit_escline(escline));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        let span = self.advance(annotation.source());
+        self.call(span, |v| v.visit_type(annotation));
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        let span = self.advance(value.source());
+        self.call(span, |v| v.visit_value(value));
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.call(self.here(), |v| v.visit_warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Abortable::visit_error(self, error, |v, error| v.visit_error(error))
+    }
+}
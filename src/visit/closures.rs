@@ -0,0 +1,332 @@
+use {
+    crate::{visit, ParseError},
+    alloc::vec::Vec,
+    core::fmt,
+};
+
+type OnNode<'kdl> =
+    fn(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>);
+type OnArgument<'kdl> = fn(&[visit::Identifier<'kdl>], visit::Value<'kdl>);
+type OnProperty<'kdl> = fn(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>);
+type OnError<'kdl> = fn(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>;
+
+/// Build an ad-hoc visitor out of closures.
+///
+/// Each closure is given the path to the visited element: the names of the
+/// nodes it is (transitively) inside of, outermost first. For a node, that's
+/// its ancestors; for an argument or property, that's its node and the node's
+/// ancestors.
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// let kdl = "package { dependency \"serde\"; dependency \"miette\"; }";
+/// let mut dependencies = vec![];
+/// let visitor = visit::fn_visitor().on_argument(|path, value| {
+///     if path.last().map(|name| name.source()) == Some("dependency") {
+///         dependencies.push(value.source());
+///     }
+/// });
+/// visit_kdl_string(kdl, visitor).unwrap();
+/// assert_eq!(dependencies, [r#""serde""#, r#""miette""#]);
+/// ```
+pub fn fn_visitor<'kdl>(
+) -> FnVisitor<'kdl, OnNode<'kdl>, OnArgument<'kdl>, OnProperty<'kdl>, OnError<'kdl>> {
+    FnVisitor {
+        state: State {
+            on_node: |_, _, _| {},
+            on_argument: |_, _| {},
+            on_property: |_, _, _| {},
+            on_error: |_, error| Err(error),
+            path: Vec::new(),
+            recovered: false,
+        },
+    }
+}
+
+/// A visitor built out of closures. See [`fn_visitor`].
+pub struct FnVisitor<'kdl, N, A, P, E> {
+    state: State<'kdl, N, A, P, E>,
+}
+
+/// The child visitor of [`FnVisitor`], which borrows its closures.
+pub struct FnChild<'a, 'kdl, N, A, P, E> {
+    state: &'a mut State<'kdl, N, A, P, E>,
+    /// Whether this node visitor pushed its name onto the path.
+    named: bool,
+    /// The pending name of a property.
+    name: Option<visit::Identifier<'kdl>>,
+    /// The pending type annotation of a node.
+    ty: Option<visit::Identifier<'kdl>>,
+}
+
+struct State<'kdl, N, A, P, E> {
+    on_node: N,
+    on_argument: A,
+    on_property: P,
+    on_error: E,
+    path: Vec<visit::Identifier<'kdl>>,
+    /// Whether `on_error` recovered from the most recent error.
+    recovered: bool,
+}
+
+impl<'kdl, N, A, P, E> FnVisitor<'kdl, N, A, P, E> {
+    /// Call `f(path, name, type)` for every node.
+    pub fn on_node<F>(self, f: F) -> FnVisitor<'kdl, F, A, P, E>
+    where
+        F: FnMut(
+            &[visit::Identifier<'kdl>],
+            visit::Identifier<'kdl>,
+            Option<visit::Identifier<'kdl>>,
+        ),
+    {
+        self.map(|state| State {
+            on_node: f,
+            on_argument: state.on_argument,
+            on_property: state.on_property,
+            on_error: state.on_error,
+            path: state.path,
+            recovered: state.recovered,
+        })
+    }
+
+    /// Call `f(path, value)` for every argument.
+    pub fn on_argument<F>(self, f: F) -> FnVisitor<'kdl, N, F, P, E>
+    where
+        F: FnMut(&[visit::Identifier<'kdl>], visit::Value<'kdl>),
+    {
+        self.map(|state| State {
+            on_node: state.on_node,
+            on_argument: f,
+            on_property: state.on_property,
+            on_error: state.on_error,
+            path: state.path,
+            recovered: state.recovered,
+        })
+    }
+
+    /// Call `f(path, name, value)` for every property.
+    pub fn on_property<F>(self, f: F) -> FnVisitor<'kdl, N, A, F, E>
+    where
+        F: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>),
+    {
+        self.map(|state| State {
+            on_node: state.on_node,
+            on_argument: state.on_argument,
+            on_property: f,
+            on_error: state.on_error,
+            path: state.path,
+            recovered: state.recovered,
+        })
+    }
+
+    /// Call `f(path, error)` for every error. Parsing continues if it returns
+    /// `Ok`; by default, parsing stops at the first error.
+    ///
+    /// If `f` recovers from every error, visiting the document succeeds.
+    pub fn on_error<F>(self, f: F) -> FnVisitor<'kdl, N, A, P, F>
+    where
+        F: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+    {
+        self.map(|state| State {
+            on_node: state.on_node,
+            on_argument: state.on_argument,
+            on_property: state.on_property,
+            on_error: f,
+            path: state.path,
+            recovered: state.recovered,
+        })
+    }
+
+    fn map<N2, A2, P2, E2>(
+        self,
+        f: impl FnOnce(State<'kdl, N, A, P, E>) -> State<'kdl, N2, A2, P2, E2>,
+    ) -> FnVisitor<'kdl, N2, A2, P2, E2> {
+        FnVisitor {
+            state: f(self.state),
+        }
+    }
+}
+
+impl<'kdl, N, A, P, E> State<'kdl, N, A, P, E> {
+    fn child(&mut self) -> FnChild<'_, 'kdl, N, A, P, E> {
+        FnChild {
+            state: self,
+            named: false,
+            name: None,
+            ty: None,
+        }
+    }
+}
+
+impl<'kdl, N, A, P, E> State<'kdl, N, A, P, E>
+where
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    fn error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        let result = (self.on_error)(&self.path, error);
+        self.recovered = result.is_ok();
+        result
+    }
+}
+
+impl<N, A, P, E> Drop for FnChild<'_, '_, N, A, P, E> {
+    fn drop(&mut self) {
+        if self.named {
+            self.state.path.pop();
+        }
+    }
+}
+
+impl<N, A, P, E> fmt::Debug for FnVisitor<'_, N, A, P, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnVisitor").finish_non_exhaustive()
+    }
+}
+
+impl<N, A, P, E> fmt::Debug for FnChild<'_, '_, N, A, P, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnChild")
+            .field("path", &self.state.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'kdl, N, A, P, E> visit::LendDocument<'kdl> for FnVisitor<'kdl, N, A, P, E>
+where
+    N: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>),
+    A: FnMut(&[visit::Identifier<'kdl>], visit::Value<'kdl>),
+    P: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>),
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    type Output = ();
+    fn finish(self) {}
+    fn finish_error(self, error: ParseError) -> Result<(), ParseError> {
+        if self.state.recovered {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
+
+impl<'kdl, N, A, P, E> visit::LendChildren<'kdl> for FnVisitor<'kdl, N, A, P, E>
+where
+    N: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>),
+    A: FnMut(&[visit::Identifier<'kdl>], visit::Value<'kdl>),
+    P: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>),
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    type VisitNode<'a>
+        = FnChild<'a, 'kdl, N, A, P, E>
+    where
+        Self: 'a;
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.state.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.state.error(error)
+    }
+}
+
+impl<'kdl, N, A, P, E> visit::LendChildren<'kdl> for FnChild<'_, 'kdl, N, A, P, E>
+where
+    N: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>),
+    A: FnMut(&[visit::Identifier<'kdl>], visit::Value<'kdl>),
+    P: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>),
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    type VisitNode<'a>
+        = FnChild<'a, 'kdl, N, A, P, E>
+    where
+        Self: 'a;
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.state.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.state.error(error)
+    }
+}
+
+impl<'kdl, N, A, P, E> visit::LendNode<'kdl> for FnChild<'_, 'kdl, N, A, P, E>
+where
+    N: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>),
+    A: FnMut(&[visit::Identifier<'kdl>], visit::Value<'kdl>),
+    P: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>),
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    type VisitArgument<'a>
+        = FnChild<'a, 'kdl, N, A, P, E>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = FnChild<'a, 'kdl, N, A, P, E>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = FnChild<'a, 'kdl, N, A, P, E>
+    where
+        Self: 'a;
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.ty = Some(annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        (self.state.on_node)(&self.state.path, name, self.ty);
+        self.state.path.push(name);
+        self.named = true;
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.state.child()
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.state.child()
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.state.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.state.error(error)
+    }
+}
+impl<'kdl, N, A, P, E> visit::Property<'kdl> for FnChild<'_, 'kdl, N, A, P, E>
+where
+    P: FnMut(&[visit::Identifier<'kdl>], visit::Identifier<'kdl>, visit::Value<'kdl>),
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.name = Some(name);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        if let Some(name) = self.name {
+            (self.state.on_property)(&self.state.path, name, value);
+        }
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.state.error(error)
+    }
+}
+
+impl<'kdl, N, A, P, E> visit::Argument<'kdl> for FnChild<'_, 'kdl, N, A, P, E>
+where
+    A: FnMut(&[visit::Identifier<'kdl>], visit::Value<'kdl>),
+    E: FnMut(&[visit::Identifier<'kdl>], ParseError) -> Result<(), ParseError>,
+{
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        (self.state.on_argument)(&self.state.path, value);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.state.error(error)
+    }
+}
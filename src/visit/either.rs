@@ -0,0 +1,274 @@
+use {
+    crate::{lint::Warning, visit, ParseError},
+    core::fmt,
+};
+
+/// A visitor which is one of two visitors, chosen at runtime.
+///
+/// This lets a visitor hand a subtree to a different visitor depending on
+/// what it has seen so far. Child visitors are created by, and borrow, the
+/// same side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L: fmt::Display, R: fmt::Display> fmt::Display for Either<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Either::Left(l) => l.fmt(f),
+            Either::Right(r) => r.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<L: std::error::Error, R: std::error::Error> std::error::Error for Either<L, R> {}
+
+macro_rules! either {
+    ($self:expr, $v:ident => $e:expr) => {
+        match $self {
+            Either::Left($v) => $e,
+            Either::Right($v) => $e,
+        }
+    };
+}
+
+macro_rules! either_child {
+    ($self:expr, $v:ident => $e:expr) => {
+        match $self {
+            Either::Left($v) => Either::Left($e),
+            Either::Right($v) => Either::Right($e),
+        }
+    };
+}
+
+impl<'kdl, L, R> visit::LendDocument<'kdl> for Either<L, R>
+where
+    L: visit::LendDocument<'kdl>,
+    R: visit::LendDocument<'kdl>,
+{
+    type Output = Either<L::Output, R::Output>;
+
+    fn finish(self) -> Self::Output {
+        either_child!(self, v => v.finish())
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        match self {
+            Either::Left(v) => v.finish_error(error).map(Either::Left),
+            Either::Right(v) => v.finish_error(error).map(Either::Right),
+        }
+    }
+
+    fn split_trivia(&self) -> bool {
+        either!(self, v => v.split_trivia())
+    }
+}
+
+impl<'kdl, L, R> visit::LendChildren<'kdl> for Either<L, R>
+where
+    L: visit::LendChildren<'kdl>,
+    R: visit::LendChildren<'kdl>,
+{
+    type VisitNode<'a>
+        = Either<L::VisitNode<'a>, R::VisitNode<'a>>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        either!(self, v => v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        either!(self, v => v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        either!(self, v => v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        either!(self, v => v.visit_escline(escline));
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        either_child!(self, v => v.visit_node())
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        either!(self, v => v.visit_slashdash_node())
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        either!(self, v => v.finish_slashdash_node());
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        either!(self, v => v.visit_warning(warning))
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        either!(self, v => v.visit_error(error))
+    }
+}
+
+impl<'kdl, L, R> visit::LendNode<'kdl> for Either<L, R>
+where
+    L: visit::LendNode<'kdl>,
+    R: visit::LendNode<'kdl>,
+{
+    type VisitArgument<'a>
+        = Either<L::VisitArgument<'a>, R::VisitArgument<'a>>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = Either<L::VisitProperty<'a>, R::VisitProperty<'a>>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = Either<L::VisitChildren<'a>, R::VisitChildren<'a>>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        either!(self, v => v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        either!(self, v => v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        either!(self, v => v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        either!(self, v => v.visit_escline(escline));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        either!(self, v => v.visit_type(annotation));
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        either!(self, v => v.visit_name(name));
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        either_child!(self, v => v.visit_argument())
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        either_child!(self, v => v.visit_property())
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        either_child!(self, v => v.visit_children())
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        either!(self, v => v.visit_slashdash_entry())
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        either!(self, v => v.finish_slashdash_entry());
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        either!(self, v => v.visit_slashdash_children())
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        either!(self, v => v.finish_slashdash_children());
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        either!(self, v => v.visit_warning(warning))
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        either!(self, v => v.visit_error(error))
+    }
+}
+
+impl<'kdl, L, R> visit::Property<'kdl> for Either<L, R>
+where
+    L: visit::Property<'kdl>,
+    R: visit::Property<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        either!(self, v => v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        either!(self, v => v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        either!(self, v => v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        either!(self, v => v.visit_escline(escline));
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        either!(self, v => v.visit_name(name));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        either!(self, v => v.visit_type(annotation));
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        either!(self, v => v.visit_value(value));
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        either!(self, v => v.visit_warning(warning))
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        either!(self, v => v.visit_error(error))
+    }
+}
+
+impl<'kdl, L, R> visit::Argument<'kdl> for Either<L, R>
+where
+    L: visit::Argument<'kdl>,
+    R: visit::Argument<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        either!(self, v => v.visit_trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        either!(self, v => v.visit_comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        either!(self, v => v.visit_newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        either!(self, v => v.visit_escline(escline));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        either!(self, v => v.visit_type(annotation));
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        either!(self, v => v.visit_value(value));
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        either!(self, v => v.visit_warning(warning))
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        either!(self, v => v.visit_error(error))
+    }
+}
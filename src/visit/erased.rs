@@ -0,0 +1,368 @@
+//! Object-safe versions of the visitor traits.
+//!
+//! Every visitor, owned or lending, implements these traits, so any visitor
+//! can be used as e.g. `&mut dyn DynDocument`. Child visitors are visited in
+//! continuation-passing style: rather than returning the child visitor,
+//! `visit_node` and friends call the given function with it, then finish it.
+
+use crate::{
+    lint::Warning,
+    visit::{self, LendChildren, LendDocument, LendNode},
+    ParseError,
+};
+
+/// Object-safe version of [`visit::Document`].
+///
+/// The output of the visitor is retrieved by the owner of the concrete
+/// visitor, using [`visit::Document::finish`] or
+/// [`visit::Document::finish_error`] as appropriate.
+pub trait DynDocument<'kdl>: DynChildren<'kdl> {
+    /// See [`visit::Document::split_trivia`].
+    fn split_trivia(&self) -> bool;
+}
+
+/// Object-safe version of [`visit::Children`].
+pub trait DynChildren<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str);
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>);
+    fn visit_newline(&mut self, newline: &'kdl str);
+    fn visit_escline(&mut self, escline: &'kdl str);
+    /// Create a node visitor, call `node` with it, then finish it.
+    ///
+    /// `node` should be called exactly once, and its result returned.
+    fn visit_node(
+        &mut self,
+        node: &mut dyn FnMut(&mut dyn DynNode<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError>;
+    fn visit_slashdash_node(&mut self) -> bool;
+    fn finish_slashdash_node(&mut self);
+    fn visit_warning(&mut self, warning: Warning);
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError>;
+}
+
+/// Object-safe version of [`visit::Node`].
+pub trait DynNode<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str);
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>);
+    fn visit_newline(&mut self, newline: &'kdl str);
+    fn visit_escline(&mut self, escline: &'kdl str);
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>);
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>);
+    /// Create an argument visitor, call `argument` with it, then finish it.
+    ///
+    /// `argument` should be called exactly once, and its result returned.
+    fn visit_argument(
+        &mut self,
+        argument: &mut dyn FnMut(&mut dyn DynArgument<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError>;
+    /// Create a property visitor, call `property` with it, then finish it.
+    ///
+    /// `property` should be called exactly once, and its result returned.
+    fn visit_property(
+        &mut self,
+        property: &mut dyn FnMut(&mut dyn DynProperty<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError>;
+    /// Create a children visitor, call `children` with it, then finish it.
+    ///
+    /// `children` should be called exactly once, and its result returned.
+    fn visit_children(
+        &mut self,
+        children: &mut dyn FnMut(&mut dyn DynChildren<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError>;
+    fn visit_slashdash_entry(&mut self) -> bool;
+    fn finish_slashdash_entry(&mut self);
+    fn visit_slashdash_children(&mut self) -> bool;
+    fn finish_slashdash_children(&mut self);
+    fn visit_warning(&mut self, warning: Warning);
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError>;
+}
+
+/// Object-safe version of [`visit::Property`].
+pub trait DynProperty<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str);
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>);
+    fn visit_newline(&mut self, newline: &'kdl str);
+    fn visit_escline(&mut self, escline: &'kdl str);
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>);
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>);
+    fn visit_value(&mut self, value: visit::Value<'kdl>);
+    fn visit_warning(&mut self, warning: Warning);
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError>;
+}
+
+/// Object-safe version of [`visit::Argument`].
+pub trait DynArgument<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str);
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>);
+    fn visit_newline(&mut self, newline: &'kdl str);
+    fn visit_escline(&mut self, escline: &'kdl str);
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>);
+    fn visit_value(&mut self, value: visit::Value<'kdl>);
+    fn visit_warning(&mut self, warning: Warning);
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError>;
+}
+
+impl<'kdl, V: LendDocument<'kdl>> DynDocument<'kdl> for V {
+    fn split_trivia(&self) -> bool {
+        LendDocument::split_trivia(self)
+    }
+}
+
+impl<'kdl, V: LendChildren<'kdl>> DynChildren<'kdl> for V {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        LendChildren::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        LendChildren::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        LendChildren::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        LendChildren::visit_escline(self, escline);
+    }
+
+    fn visit_node(
+        &mut self,
+        node: &mut dyn FnMut(&mut dyn DynNode<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        node(&mut LendChildren::visit_node(self))
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        LendChildren::visit_slashdash_node(self)
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        LendChildren::finish_slashdash_node(self);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        LendChildren::visit_warning(self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        LendChildren::visit_error(self, error)
+    }
+}
+
+impl<'kdl, V: LendNode<'kdl>> DynNode<'kdl> for V {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        LendNode::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        LendNode::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        LendNode::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        LendNode::visit_escline(self, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        LendNode::visit_type(self, annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        LendNode::visit_name(self, name);
+    }
+
+    fn visit_argument(
+        &mut self,
+        argument: &mut dyn FnMut(&mut dyn DynArgument<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        argument(&mut LendNode::visit_argument(self))
+    }
+
+    fn visit_property(
+        &mut self,
+        property: &mut dyn FnMut(&mut dyn DynProperty<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        property(&mut LendNode::visit_property(self))
+    }
+
+    fn visit_children(
+        &mut self,
+        children: &mut dyn FnMut(&mut dyn DynChildren<'kdl>) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        children(&mut LendNode::visit_children(self))
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        LendNode::visit_slashdash_entry(self)
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        LendNode::finish_slashdash_entry(self);
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        LendNode::visit_slashdash_children(self)
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        LendNode::finish_slashdash_children(self);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        LendNode::visit_warning(self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        LendNode::visit_error(self, error)
+    }
+}
+
+impl<'kdl, V: visit::Property<'kdl>> DynProperty<'kdl> for V {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        visit::Property::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        visit::Property::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        visit::Property::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        visit::Property::visit_escline(self, escline);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        visit::Property::visit_name(self, name);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        visit::Property::visit_type(self, annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        visit::Property::visit_value(self, value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        visit::Property::visit_warning(self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        visit::Property::visit_error(self, error)
+    }
+}
+
+impl<'kdl, V: visit::Argument<'kdl>> DynArgument<'kdl> for V {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        visit::Argument::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        visit::Argument::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        visit::Argument::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        visit::Argument::visit_escline(self, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        visit::Argument::visit_type(self, annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        visit::Argument::visit_value(self, value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        visit::Argument::visit_warning(self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        visit::Argument::visit_error(self, error)
+    }
+}
+
+// Property and argument visitors have no children, so their trait objects can
+// implement the static visitor traits directly.
+
+impl<'kdl> visit::Property<'kdl> for &mut (dyn DynProperty<'kdl> + '_) {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        DynProperty::visit_trivia(&mut **self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        DynProperty::visit_comment(&mut **self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        DynProperty::visit_newline(&mut **self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        DynProperty::visit_escline(&mut **self, escline);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        DynProperty::visit_name(&mut **self, name);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        DynProperty::visit_type(&mut **self, annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        DynProperty::visit_value(&mut **self, value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        DynProperty::visit_warning(&mut **self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        DynProperty::visit_error(&mut **self, error)
+    }
+}
+
+impl<'kdl> visit::Argument<'kdl> for &mut (dyn DynArgument<'kdl> + '_) {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        DynArgument::visit_trivia(&mut **self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        DynArgument::visit_comment(&mut **self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        DynArgument::visit_newline(&mut **self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        DynArgument::visit_escline(&mut **self, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        DynArgument::visit_type(&mut **self, annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        DynArgument::visit_value(&mut **self, value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        DynArgument::visit_warning(&mut **self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        DynArgument::visit_error(&mut **self, error)
+    }
+}
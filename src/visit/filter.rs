@@ -0,0 +1,361 @@
+use crate::{lint::Warning, visit, ParseError};
+
+/// A visitor which only forwards nodes matching a predicate.
+///
+/// The predicate is called with each node's name and type annotation, and
+/// nodes for which it returns `false` are skipped, along with their entries
+/// and children. Children of forwarded nodes are forwarded as is; wrap the
+/// children visitor in another `FilterNodes` to filter them as well.
+///
+/// Nothing is allocated; the few components visited before a node's name is
+/// known are held until the predicate decides. Errors in skipped nodes are
+/// still given to the wrapped visitor.
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// # use visit::prelude::*;
+/// #[derive(Default)]
+/// struct Names<'kdl>(Vec<&'kdl str>);
+///
+/// impl<'kdl> visit::Document<'kdl> for Names<'kdl> {
+///     type Output = Vec<&'kdl str>;
+///     fn finish(self) -> Self::Output {
+///         self.0
+///     }
+/// }
+///
+/// impl<'kdl> visit::Children<'kdl> for Names<'kdl> {
+///     type VisitNode = Names<'kdl>;
+///     fn visit_node(&mut self) -> Self::VisitNode {
+///         Names::default()
+///     }
+///     fn finish_node(&mut self, node: Self::VisitNode) {
+///         self.0.extend(node.0);
+///     }
+/// }
+///
+/// impl<'kdl> visit::Node<'kdl> for Names<'kdl> {
+///     type VisitArgument = ();
+///     type VisitProperty = ();
+///     type VisitChildren = Names<'kdl>;
+///     fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+///         self.0.push(name.source());
+///     }
+///     fn visit_argument(&mut self) {}
+///     fn visit_property(&mut self) {}
+///     fn visit_children(&mut self) -> Self::VisitChildren {
+///         Names::default()
+///     }
+///     fn finish_children(&mut self, children: Self::VisitChildren) {
+///         self.0.extend(children.0);
+///     }
+/// }
+///
+/// let kdl = "keep { child; }\ndrop { child; }\n(keep)also\n";
+/// let filter = |name: visit::Identifier<'_>, ty: Option<visit::Identifier<'_>>| {
+///     name.source() == "keep" || ty.map_or(false, |ty| ty.source() == "keep")
+/// };
+/// let names = visit_kdl_string(kdl, visit::FilterNodes::new(Names::default(), filter))?;
+/// assert_eq!(names, ["keep", "child", "also"]);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FilterNodes<V, F> {
+    visitor: V,
+    filter: F,
+}
+
+impl<V, F> FilterNodes<V, F> {
+    pub fn new(visitor: V, filter: F) -> Self {
+        FilterNodes { visitor, filter }
+    }
+
+    pub fn into_inner(self) -> V {
+        self.visitor
+    }
+}
+
+impl<'kdl, V, F> visit::LendDocument<'kdl> for FilterNodes<V, F>
+where
+    V: visit::LendDocument<'kdl>,
+    F: FnMut(visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>) -> bool,
+{
+    type Output = V::Output;
+
+    fn finish(self) -> Self::Output {
+        self.visitor.finish()
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        self.visitor.finish_error(error)
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.visitor.split_trivia()
+    }
+}
+
+impl<'kdl, V, F> visit::LendChildren<'kdl> for FilterNodes<V, F>
+where
+    V: visit::LendChildren<'kdl>,
+    F: FnMut(visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>) -> bool,
+{
+    type VisitNode<'a>
+        = FilterNode<'a, 'kdl, V, F>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        FilterNode {
+            parent: Some(&mut self.visitor),
+            filter: &mut self.filter,
+            node: None,
+            skip: false,
+            pending: [None; PENDING],
+        }
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.visitor.visit_slashdash_node()
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.visitor.finish_slashdash_node();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+/// Components visited before a node's name is known. A well-formed node has at
+/// most six (a type annotation with whitespace) plus a few warnings; only nodes
+/// which fail before their name have more, and those are always skipped.
+const PENDING: usize = 12;
+
+#[derive(Debug, Clone, Copy)]
+enum Pending<'kdl> {
+    Trivia(&'kdl str),
+    Comment(visit::Comment<'kdl>),
+    Newline(&'kdl str),
+    Escline(&'kdl str),
+    Type(visit::Identifier<'kdl>),
+    Warning(Warning),
+}
+
+/// The node visitor of [`FilterNodes`].
+///
+/// It borrows the parent children visitor until the node is kept, and then
+/// lends it to the node visitor.
+pub struct FilterNode<'a, 'kdl, V: visit::LendChildren<'kdl>, F> {
+    parent: Option<&'a mut V>,
+    filter: &'a mut F,
+    node: Option<V::VisitNode<'a>>,
+    skip: bool,
+    pending: [Option<Pending<'kdl>>; PENDING],
+}
+
+impl<'a, 'kdl, V, F> core::fmt::Debug for FilterNode<'a, 'kdl, V, F>
+where
+    V: visit::LendChildren<'kdl> + core::fmt::Debug,
+    V::VisitNode<'a>: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FilterNode")
+            .field("parent", &self.parent)
+            .field("node", &self.node)
+            .field("skip", &self.skip)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'kdl, V: visit::LendChildren<'kdl>, F> FilterNode<'_, 'kdl, V, F> {
+    fn pend(&mut self, event: Pending<'kdl>) {
+        if let Some(slot) = self.pending.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(event);
+        }
+    }
+}
+
+fn skip<'a, 'kdl, V: visit::LendChildren<'kdl>>(
+    parent: &'a mut Option<&mut V>,
+) -> visit::Skip<'a, V> {
+    visit::Skip::new(parent.as_deref_mut(), |parent, error| {
+        parent.visit_error(error)
+    })
+}
+
+macro_rules! filter_child {
+    ($visit:ident, $Visit:ident) => {
+        fn $visit(&mut self) -> Self::$Visit<'_> {
+            match (&mut self.node, &mut self.parent) {
+                (Some(node), _) => visit::Either::Left(node.$visit()),
+                (None, parent) => visit::Either::Right(skip(parent)),
+            }
+        }
+    };
+}
+
+impl<'a, 'kdl, V, F> visit::LendNode<'kdl> for FilterNode<'a, 'kdl, V, F>
+where
+    V: visit::LendChildren<'kdl>,
+    F: FnMut(visit::Identifier<'kdl>, Option<visit::Identifier<'kdl>>) -> bool,
+{
+    type VisitArgument<'b>
+        = visit::Either<
+        <V::VisitNode<'a> as visit::LendNode<'kdl>>::VisitArgument<'b>,
+        visit::Skip<'b, V>,
+    >
+    where
+        Self: 'b;
+    type VisitProperty<'b>
+        = visit::Either<
+        <V::VisitNode<'a> as visit::LendNode<'kdl>>::VisitProperty<'b>,
+        visit::Skip<'b, V>,
+    >
+    where
+        Self: 'b;
+    type VisitChildren<'b>
+        = visit::Either<
+        <V::VisitNode<'a> as visit::LendNode<'kdl>>::VisitChildren<'b>,
+        visit::Skip<'b, V>,
+    >
+    where
+        Self: 'b;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        match &mut self.node {
+            Some(node) => node.visit_trivia(trivia),
+            None if !self.skip => self.pend(Pending::Trivia(trivia)),
+            None => {}
+        }
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        match &mut self.node {
+            Some(node) => node.visit_comment(comment),
+            None if !self.skip => self.pend(Pending::Comment(comment)),
+            None => {}
+        }
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        match &mut self.node {
+            Some(node) => node.visit_newline(newline),
+            None if !self.skip => self.pend(Pending::Newline(newline)),
+            None => {}
+        }
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        match &mut self.node {
+            Some(node) => node.visit_escline(escline),
+            None if !self.skip => self.pend(Pending::Escline(escline)),
+            None => {}
+        }
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        match &mut self.node {
+            Some(node) => node.visit_type(annotation),
+            None if !self.skip => self.pend(Pending::Type(annotation)),
+            None => {}
+        }
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        if self.node.is_some() || self.skip {
+            return;
+        }
+        let ty = self.pending.iter().find_map(|event| match event {
+            Some(Pending::Type(annotation)) => Some(*annotation),
+            _ => None,
+        });
+        if !(self.filter)(name, ty) {
+            self.skip = true;
+            return;
+        }
+        let Some(parent) = self.parent.take() else {
+            return;
+        };
+        let mut node = parent.visit_node();
+        for event in self.pending.iter().flatten() {
+            match *event {
+                Pending::Trivia(trivia) => node.visit_trivia(trivia),
+                Pending::Comment(comment) => node.visit_comment(comment),
+                Pending::Newline(newline) => node.visit_newline(newline),
+                Pending::Escline(escline) => node.visit_escline(escline),
+                Pending::Type(annotation) => node.visit_type(annotation),
+                Pending::Warning(warning) => node.visit_warning(warning),
+            }
+        }
+        node.visit_name(name);
+        self.node = Some(node);
+    }
+
+    filter_child!(visit_argument, VisitArgument);
+    filter_child!(visit_property, VisitProperty);
+    filter_child!(visit_children, VisitChildren);
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        match &mut self.node {
+            Some(node) => node.visit_slashdash_entry(),
+            None => false,
+        }
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        if let Some(node) = &mut self.node {
+            node.finish_slashdash_entry();
+        }
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        match &mut self.node {
+            Some(node) => node.visit_slashdash_children(),
+            None => false,
+        }
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        if let Some(node) = &mut self.node {
+            node.finish_slashdash_children();
+        }
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        match &mut self.node {
+            Some(node) => node.visit_warning(warning),
+            None if !self.skip => self.pend(Pending::Warning(warning)),
+            None => {}
+        }
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        match (&mut self.node, &mut self.parent) {
+            (Some(node), _) => node.visit_error(error),
+            (None, Some(parent)) => parent.visit_error(error),
+            (None, None) => Err(error),
+        }
+    }
+}
@@ -1,51 +1,79 @@
-use {crate::visit, ref_cast::RefCast};
-
-pub(crate) trait Trivia<'kdl> {
+use {
+    crate::{
+        parse::{ParseChildren, ParseNode},
+        visit::{self, Argument, Property},
+    },
+    ref_cast::RefCast,
+};
+
+/// A visitor of trivia only, such as a visitor viewed through
+/// [`ChildrenExt::opaque`] and friends.
+///
+/// Comments, newlines and line continuations are trivia unless overridden.
+pub trait Trivia<'kdl> {
     fn visit_trivia(&mut self, trivia: &'kdl str);
-    fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError>;
-
-    fn just_trivia(&mut self) -> TriviaVisitor<'_, 'kdl>
-    where
-        Self: Sized,
-    {
-        TriviaVisitor(Some(self))
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
     }
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+    fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError>;
 }
 
-pub(crate) trait JustType<'kdl>: Trivia<'kdl> {
+/// A visitor of trivia and type annotations only.
+pub trait JustType<'kdl>: Trivia<'kdl> {
     fn visit_type(&mut self, _: visit::Identifier<'kdl>) {}
 }
 
-pub(crate) trait JustValue<'kdl>: JustType<'kdl> {
+/// A visitor of trivia, type annotations and values only.
+pub trait JustValue<'kdl>: JustType<'kdl> {
     fn visit_value(&mut self, _: visit::Value<'kdl>) {}
 }
 
 macro_rules! define_visitor_structs {
     {
         $(
-            struct $Visitor:ident(impl visit::$Visit:ident)
-                from visit::$Extension:ident
+            struct $Visitor:ident(impl $Visit:ident)
+                from $vis:vis visit::$Extension:ident
                 $(as $(visit::$Helper:tt),*)?;
         )*
     } => {
         $(
-            #[derive(RefCast)]
+            /// A visitor viewed as only visiting trivia, as returned by `opaque`.
+            #[derive(Debug, RefCast)]
             #[repr(transparent)]
-            pub(crate) struct $Visitor<V: ?Sized>(V);
-            impl<'kdl, V: ?Sized + visit::$Visit<'kdl>> visit::Trivia<'kdl> for $Visitor<V> {
+            pub struct $Visitor<V: ?Sized>(V);
+            impl<'kdl, V: ?Sized + $Visit<'kdl>> visit::Trivia<'kdl> for $Visitor<V> {
                 fn visit_trivia(&mut self, trivia: &'kdl str) {
                     self.0.visit_trivia(trivia);
                 }
+                fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+                    self.0.visit_comment(comment);
+                }
+                fn visit_newline(&mut self, newline: &'kdl str) {
+                    self.0.visit_newline(newline);
+                }
+                fn visit_escline(&mut self, escline: &'kdl str) {
+                    self.0.visit_escline(escline);
+                }
                 fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError> {
                     self.0.visit_error(error)
                 }
             }
 
-            impl<'kdl, V: ?Sized + visit::$Visit<'kdl>> $Extension<'kdl> for V {}
-            pub(crate) trait $Extension<'kdl>: visit::$Visit<'kdl> {
+            impl<'kdl, V: ?Sized + $Visit<'kdl>> $Extension<'kdl> for V {}
+            $vis trait $Extension<'kdl>: $Visit<'kdl> {
+                /// View this visitor as a [`Trivia`] visitor, hiding its
+                /// other methods.
                 fn opaque(&mut self) -> &mut $Visitor<Self> {
                     $Visitor::ref_cast_mut(self)
                 }
+                /// A visitor which hands everything it visits, including
+                /// whole child components, to this visitor as trivia.
                 fn only_trivia(&mut self) -> TriviaVisitor<'_, 'kdl>
                 where
                     Self: Sized,
@@ -54,18 +82,18 @@ macro_rules! define_visitor_structs {
                 }
             }
 
-            $($(define_visitor_structs!(@extend $Visitor(visit::$Visit) as visit::$Helper);)*)?
+            $($(define_visitor_structs!(@extend $Visitor($Visit) as visit::$Helper);)*)?
         )*
     };
-    (@extend $Visitor:ident(visit::$Visit:ident) as visit::JustType) => {
-        impl<'kdl, V: ?Sized + visit::$Visit<'kdl>> visit::JustType<'kdl> for $Visitor<V> {
+    (@extend $Visitor:ident($Visit:ident) as visit::JustType) => {
+        impl<'kdl, V: ?Sized + $Visit<'kdl>> visit::JustType<'kdl> for $Visitor<V> {
             fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
                 self.0.visit_type(annotation);
             }
         }
     };
-    (@extend $Visitor:ident(visit::$Visit:ident) as visit::JustValue) => {
-        impl<'kdl, V: ?Sized + visit::$Visit<'kdl>> visit::JustValue<'kdl> for $Visitor<V> {
+    (@extend $Visitor:ident($Visit:ident) as visit::JustValue) => {
+        impl<'kdl, V: ?Sized + $Visit<'kdl>> visit::JustValue<'kdl> for $Visitor<V> {
             fn visit_value(&mut self, value: visit::Value<'kdl>) {
                 self.0.visit_value(value);
             }
@@ -74,36 +102,79 @@ macro_rules! define_visitor_structs {
 }
 
 define_visitor_structs! {
-    struct ChildrenVisitor(impl visit::Children) from visit::ChildrenExt;
-    struct NodeVisitor    (impl visit::Node    ) from visit::NodeExt     as visit::JustType;
-    struct PropertyVisitor(impl visit::Property) from visit::PropertyExt as visit::JustType, visit::JustValue;
-    struct ArgumentVisitor(impl visit::Argument) from visit::ArgumentExt as visit::JustType, visit::JustValue;
+    struct ChildrenVisitor(impl ParseChildren) from pub(crate) visit::ParseChildrenExt;
+    struct NodeVisitor    (impl ParseNode    ) from pub(crate) visit::ParseNodeExt     as visit::JustType;
+    struct PropertyVisitor(impl Property     ) from pub        visit::PropertyExt      as visit::JustType, visit::JustValue;
+    struct ArgumentVisitor(impl Argument     ) from pub        visit::ArgumentExt      as visit::JustType, visit::JustValue;
+}
+
+impl<'kdl, V: ?Sized + visit::LendChildren<'kdl>> ChildrenExt<'kdl> for V {}
+/// Adapters for handing the components visited by a children visitor to
+/// another visitor.
+pub trait ChildrenExt<'kdl>: visit::LendChildren<'kdl> {
+    /// View this visitor as a [`Trivia`] visitor, hiding its other methods.
+    fn opaque(&mut self) -> &mut ChildrenVisitor<Self> {
+        ChildrenVisitor::ref_cast_mut(self)
+    }
+    /// A visitor which hands everything it visits, including whole child
+    /// components, to this visitor as trivia.
+    fn only_trivia(&mut self) -> TriviaVisitor<'_, 'kdl>
+    where
+        Self: Sized,
+    {
+        TriviaVisitor::new(self.opaque())
+    }
+}
+
+impl<'kdl, V: ?Sized + visit::LendNode<'kdl>> NodeExt<'kdl> for V {}
+/// Adapters for handing the components visited by a node visitor to another
+/// visitor.
+pub trait NodeExt<'kdl>: visit::LendNode<'kdl> {
+    /// View this visitor as a [`JustType`] visitor, hiding its other methods.
+    fn opaque(&mut self) -> &mut NodeVisitor<Self> {
+        NodeVisitor::ref_cast_mut(self)
+    }
+    /// A visitor which hands everything it visits, including whole child
+    /// components, to this visitor as trivia.
+    fn only_trivia(&mut self) -> TriviaVisitor<'_, 'kdl>
+    where
+        Self: Sized,
+    {
+        TriviaVisitor::new(self.opaque())
+    }
 }
 
 // This holds &dyn VisitTrivia to avoid recursive TriviaVisitor monomorphizing.
 // It would be nice to monomorphize, but this would require *type* specializing
 // TriviaVisitor::only_trivia to return Self instead of TriviaVisitor<Self>.
-#[allow(unreachable_pub)]
-pub struct TriviaVisitor<'a, 'kdl>(Option<&'a mut dyn Trivia<'kdl>>);
+/// A visitor which hands everything it visits to a [`Trivia`] visitor as
+/// trivia, as returned by [`ChildrenExt::only_trivia`] and friends.
+///
+/// Being a lending visitor, it can be handed out by the
+/// [lending visitor traits](visit::LendChildren) as well as used directly.
+pub struct TriviaVisitor<'a, 'kdl>(&'a mut dyn Trivia<'kdl>);
+
+impl core::fmt::Debug for TriviaVisitor<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TriviaVisitor").finish_non_exhaustive()
+    }
+}
 
 impl<'a, 'kdl> TriviaVisitor<'a, 'kdl> {
-    fn new(visitor: &'a mut dyn Trivia<'kdl>) -> Self {
-        TriviaVisitor(Some(visitor))
+    pub fn new(visitor: &'a mut dyn Trivia<'kdl>) -> Self {
+        TriviaVisitor(visitor)
     }
 
-    fn inner(&mut self) -> &mut dyn Trivia<'kdl> {
-        &mut **self
-            .0
-            .as_mut()
-            .expect("kdl visitor should not be called while visiting a child component")
+    fn child(&mut self) -> TriviaVisitor<'_, 'kdl> {
+        TriviaVisitor(&mut *self.0)
     }
 
     fn visit(&mut self, trivia: &'kdl str) {
-        self.inner().visit_trivia(trivia);
+        self.0.visit_trivia(trivia);
     }
 
     fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError> {
-        self.inner().visit_error(error)
+        self.0.visit_error(error)
     }
 }
 
@@ -115,13 +186,6 @@ impl<'kdl> Trivia<'kdl> for TriviaVisitor<'_, 'kdl> {
     fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError> {
         self.visit_error(error)
     }
-
-    fn just_trivia(&mut self) -> TriviaVisitor<'_, 'kdl>
-    where
-        Self: Sized,
-    {
-        TriviaVisitor::new(self.inner())
-    }
 }
 
 impl<'kdl> JustType<'kdl> for TriviaVisitor<'_, 'kdl> {
@@ -136,19 +200,18 @@ impl<'kdl> JustValue<'kdl> for TriviaVisitor<'_, 'kdl> {
     }
 }
 
-impl<'kdl> visit::Children<'kdl> for TriviaVisitor<'_, 'kdl> {
-    type VisitNode = Self;
+impl<'kdl> visit::LendChildren<'kdl> for TriviaVisitor<'_, 'kdl> {
+    type VisitNode<'a>
+        = TriviaVisitor<'a, 'kdl>
+    where
+        Self: 'a;
 
     fn visit_trivia(&mut self, trivia: &'kdl str) {
         self.visit(trivia);
     }
 
-    fn visit_node(&mut self) -> Self::VisitNode {
-        Self(self.0.take())
-    }
-
-    fn finish_node(&mut self, node: Self::VisitNode) {
-        self.0 = node.0;
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child()
     }
 
     fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError> {
@@ -156,10 +219,19 @@ impl<'kdl> visit::Children<'kdl> for TriviaVisitor<'_, 'kdl> {
     }
 }
 
-impl<'kdl> visit::Node<'kdl> for TriviaVisitor<'_, 'kdl> {
-    type VisitArgument = Self;
-    type VisitProperty = Self;
-    type VisitChildren = Self;
+impl<'kdl> visit::LendNode<'kdl> for TriviaVisitor<'_, 'kdl> {
+    type VisitArgument<'a>
+        = TriviaVisitor<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = TriviaVisitor<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = TriviaVisitor<'a, 'kdl>
+    where
+        Self: 'a;
 
     fn visit_trivia(&mut self, trivia: &'kdl str) {
         self.visit(trivia)
@@ -173,28 +245,16 @@ impl<'kdl> visit::Node<'kdl> for TriviaVisitor<'_, 'kdl> {
         self.visit(name.source());
     }
 
-    fn visit_argument(&mut self) -> Self::VisitArgument {
-        Self(self.0.take())
-    }
-
-    fn finish_argument(&mut self, argument: Self::VisitArgument) {
-        self.0 = argument.0;
-    }
-
-    fn visit_property(&mut self) -> Self::VisitProperty {
-        Self(self.0.take())
-    }
-
-    fn finish_property(&mut self, property: Self::VisitProperty) {
-        self.0 = property.0;
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child()
     }
 
-    fn visit_children(&mut self) -> Self::VisitChildren {
-        Self(self.0.take())
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child()
     }
 
-    fn finish_children(&mut self, children: Self::VisitChildren) {
-        self.0 = children.0;
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child()
     }
 
     fn visit_error(&mut self, error: crate::ParseError) -> Result<(), crate::ParseError> {
@@ -0,0 +1,67 @@
+use crate::{visit, ParseError};
+
+/// A visitor which discards everything, including errors.
+///
+/// Unlike the validating `()` visitor, this recovers from every error, so it
+/// can be used to skip over a subtree regardless of its contents.
+///
+/// It holds no state, so it is an owned visitor, usable as the child of owned
+/// and lending visitors alike.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ignore;
+
+impl visit::Document<'_> for Ignore {
+    type Output = ();
+
+    fn finish(self) {}
+
+    fn finish_error(self, _: ParseError) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+impl visit::Children<'_> for Ignore {
+    type VisitNode = Ignore;
+
+    fn visit_node(&mut self) -> Self::VisitNode {
+        Ignore
+    }
+
+    fn visit_error(&mut self, _: ParseError) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+impl visit::Node<'_> for Ignore {
+    type VisitArgument = Ignore;
+    type VisitProperty = Ignore;
+    type VisitChildren = Ignore;
+
+    fn visit_argument(&mut self) -> Self::VisitArgument {
+        Ignore
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty {
+        Ignore
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren {
+        Ignore
+    }
+
+    fn visit_error(&mut self, _: ParseError) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+impl visit::Property<'_> for Ignore {
+    fn visit_error(&mut self, _: ParseError) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+impl visit::Argument<'_> for Ignore {
+    fn visit_error(&mut self, _: ParseError) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,564 @@
+//! Lending versions of the visitor traits.
+//!
+//! With the [`visit::Children`] family of traits, child visitors are returned
+//! by value, so they cannot borrow their parent; state shared with a child
+//! has to be moved into it and moved back out when it is finished. With these
+//! traits, child visitors may borrow their parent, so e.g. a child can push
+//! directly into its parent's collection, and using the parent while a child
+//! is alive is a compile error rather than a runtime panic.
+//!
+//! There are no `finish_*` methods; a child visitor is finished when it is
+//! dropped. That includes when parsing stops at an error the visitor doesn't
+//! recover from, and when unwinding from a panic, so finishing a child in
+//! `Drop` must leave its parent valid however much of the child was visited.
+//! Argument and property visitors have no children of their own, so they are
+//! the usual [`visit::Argument`] and [`visit::Property`] traits.
+//!
+//! Every visitor implementing the owned traits is also a lending visitor,
+//! which lends out its children as [`OwnedChild`]ren, so the parser, the
+//! `dyn` traits and the adapters in [`visit`], such as [`visit::Tee`] and
+//! [`visit::FilterNodes`], are written against these traits only. The
+//! adapters lend out child adapters which borrow their state, so they can
+//! only be the children of lending visitors.
+//!
+//! [`OwnedChild`]: visit::OwnedChild
+
+use {
+    crate::{lint::Warning, visit, ParseError},
+    core::{
+        fmt,
+        hash::{Hash, Hasher},
+        ops::{Deref, DerefMut},
+    },
+    scopeguard::{guard, ScopeGuard},
+};
+
+/// Lending version of [`visit::Document`].
+pub trait LendDocument<'kdl>: Sized + LendChildren<'kdl> {
+    type Output;
+    fn finish(self) -> Self::Output;
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        Err(error)
+    }
+
+    /// See [`visit::Document::split_trivia`].
+    fn split_trivia(&self) -> bool {
+        false
+    }
+}
+
+/// Lending version of [`visit::Children`].
+pub trait LendChildren<'kdl> {
+    type VisitNode<'a>: LendNode<'kdl>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        let _ = trivia;
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_>;
+
+    /// See [`visit::Children::visit_slashdash_node`].
+    fn visit_slashdash_node(&mut self) -> bool {
+        false
+    }
+    fn finish_slashdash_node(&mut self) {}
+
+    /// See [`visit::Children::visit_warning`].
+    fn visit_warning(&mut self, warning: Warning) {
+        let _ = warning;
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Err(error)
+    }
+}
+
+/// Lending version of [`visit::Node`].
+pub trait LendNode<'kdl> {
+    type VisitArgument<'a>: visit::Argument<'kdl>
+    where
+        Self: 'a;
+    type VisitProperty<'a>: visit::Property<'kdl>
+    where
+        Self: 'a;
+    type VisitChildren<'a>: LendChildren<'kdl>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        let _ = trivia;
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+
+    fn visit_type(&mut self, _: visit::Identifier<'kdl>) {}
+    fn visit_name(&mut self, _: visit::Identifier<'kdl>) {}
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_>;
+    fn visit_property(&mut self) -> Self::VisitProperty<'_>;
+    fn visit_children(&mut self) -> Self::VisitChildren<'_>;
+
+    /// See [`visit::Node::visit_slashdash_entry`].
+    fn visit_slashdash_entry(&mut self) -> bool {
+        false
+    }
+    fn finish_slashdash_entry(&mut self) {}
+
+    /// See [`visit::Node::visit_slashdash_children`].
+    fn visit_slashdash_children(&mut self) -> bool {
+        false
+    }
+    fn finish_slashdash_children(&mut self) {}
+
+    /// See [`visit::Children::visit_warning`].
+    fn visit_warning(&mut self, warning: Warning) {
+        let _ = warning;
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Err(error)
+    }
+}
+
+/// State owned by a visitor and borrowed by the child visitors it lends out,
+/// so that a visitor and its children can be the same type.
+pub(crate) enum Shared<'a, T> {
+    Owned(T),
+    Borrowed(&'a mut T),
+}
+
+impl<T> Shared<'_, T> {
+    /// Borrow the state for a child visitor.
+    pub(crate) fn lend(&mut self) -> Shared<'_, T> {
+        Shared::Borrowed(self)
+    }
+}
+
+impl<T> Deref for Shared<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            Shared::Owned(state) => state,
+            Shared::Borrowed(state) => state,
+        }
+    }
+}
+
+impl<T> DerefMut for Shared<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            Shared::Owned(state) => state,
+            Shared::Borrowed(state) => state,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Shared<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+/// Cloning borrowed state clones the state itself.
+impl<T: Clone> Clone for Shared<'_, T> {
+    fn clone(&self) -> Self {
+        Shared::Owned(T::clone(self))
+    }
+}
+
+impl<T: PartialEq> PartialEq for Shared<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        T::eq(self, other)
+    }
+}
+
+impl<T: Eq> Eq for Shared<'_, T> {}
+
+impl<T: Hash> Hash for Shared<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        T::hash(self, state);
+    }
+}
+
+/// A child visitor lent out by a parent implementing owned visitor traits, such
+/// as a [`visit::Children`] visitor used as a lending visitor, or the visitor
+/// wrapped by [`visit::WithPath`].
+///
+/// When dropped, the child is given back to the parent's `finish_*` method.
+#[allow(clippy::type_complexity)]
+pub struct OwnedChild<'a, P, C>(ScopeGuard<(&'a mut P, C), fn((&'a mut P, C))>);
+
+impl<'a, P, C> OwnedChild<'a, P, C> {
+    pub(crate) fn new(parent: &'a mut P, child: C, finish: fn((&'a mut P, C))) -> Self {
+        OwnedChild(guard((parent, child), finish))
+    }
+
+    fn child(&mut self) -> &mut C {
+        &mut self.0 .1
+    }
+}
+
+impl<P, C: fmt::Debug> fmt::Debug for OwnedChild<'_, P, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OwnedChild").field(&self.0 .1).finish()
+    }
+}
+
+impl<'kdl, V: visit::Document<'kdl>> LendDocument<'kdl> for V {
+    type Output = V::Output;
+
+    fn finish(self) -> Self::Output {
+        visit::Document::finish(self)
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        visit::Document::finish_error(self, error)
+    }
+
+    fn split_trivia(&self) -> bool {
+        visit::Document::split_trivia(self)
+    }
+}
+
+impl<'kdl, V: visit::Children<'kdl>> LendChildren<'kdl> for V {
+    type VisitNode<'a>
+        = OwnedChild<'a, V, V::VisitNode>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        visit::Children::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        visit::Children::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        visit::Children::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        visit::Children::visit_escline(self, escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        let node = <V as visit::Children<'kdl>>::visit_node(self);
+        OwnedChild::new(self, node, |(parent, node)| parent.finish_node(node))
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        visit::Children::visit_slashdash_node(self)
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        visit::Children::finish_slashdash_node(self);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        visit::Children::visit_warning(self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        visit::Children::visit_error(self, error)
+    }
+}
+
+impl<'kdl, V: visit::Node<'kdl>> LendNode<'kdl> for V {
+    type VisitArgument<'a>
+        = OwnedChild<'a, V, V::VisitArgument>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = OwnedChild<'a, V, V::VisitProperty>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = OwnedChild<'a, V, V::VisitChildren>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        visit::Node::visit_trivia(self, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        visit::Node::visit_comment(self, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        visit::Node::visit_newline(self, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        visit::Node::visit_escline(self, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        visit::Node::visit_type(self, annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        visit::Node::visit_name(self, name);
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        let argument = <V as visit::Node<'kdl>>::visit_argument(self);
+        OwnedChild::new(self, argument, |(parent, argument)| {
+            parent.finish_argument(argument);
+        })
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        let property = <V as visit::Node<'kdl>>::visit_property(self);
+        OwnedChild::new(self, property, |(parent, property)| {
+            parent.finish_property(property);
+        })
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        let children = <V as visit::Node<'kdl>>::visit_children(self);
+        OwnedChild::new(self, children, |(parent, children)| {
+            parent.finish_children(children);
+        })
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        visit::Node::visit_slashdash_entry(self)
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        visit::Node::finish_slashdash_entry(self);
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        visit::Node::visit_slashdash_children(self)
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        visit::Node::finish_slashdash_children(self);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        visit::Node::visit_warning(self, warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        visit::Node::visit_error(self, error)
+    }
+}
+
+impl<'kdl, P, C: LendChildren<'kdl>> LendChildren<'kdl> for OwnedChild<'_, P, C> {
+    type VisitNode<'a>
+        = C::VisitNode<'a>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.child().visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.child().visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.child().visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.child().visit_escline(escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child().visit_node()
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.child().visit_slashdash_node()
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.child().finish_slashdash_node();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.child().visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.child().visit_error(error)
+    }
+}
+
+impl<'kdl, P, C: LendNode<'kdl>> LendNode<'kdl> for OwnedChild<'_, P, C> {
+    type VisitArgument<'a>
+        = C::VisitArgument<'a>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = C::VisitProperty<'a>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = C::VisitChildren<'a>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.child().visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.child().visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.child().visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.child().visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.child().visit_type(annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.child().visit_name(name);
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child().visit_argument()
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child().visit_property()
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child().visit_children()
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.child().visit_slashdash_entry()
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.child().finish_slashdash_entry();
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        self.child().visit_slashdash_children()
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        self.child().finish_slashdash_children();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.child().visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.child().visit_error(error)
+    }
+}
+
+impl<'kdl, P, C: visit::Property<'kdl>> visit::Property<'kdl> for OwnedChild<'_, P, C> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.child().visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.child().visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.child().visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.child().visit_escline(escline);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.child().visit_name(name);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.child().visit_type(annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.child().visit_value(value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.child().visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.child().visit_error(error)
+    }
+}
+
+impl<'kdl, P, C: visit::Argument<'kdl>> visit::Argument<'kdl> for OwnedChild<'_, P, C> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.child().visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.child().visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.child().visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.child().visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.child().visit_type(annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.child().visit_value(value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.child().visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.child().visit_error(error)
+    }
+}
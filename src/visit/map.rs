@@ -0,0 +1,328 @@
+use crate::{
+    lint::Warning,
+    visit::{self, Shared},
+    ParseError,
+};
+
+/// A visitor which rewrites argument and property values before forwarding
+/// them.
+///
+/// New string and number values can be created with
+/// [`visit::String::from_source`] and [`visit::Number::from_source`].
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// # use visit::prelude::*;
+/// struct Values<'kdl>(Vec<&'kdl str>);
+///
+/// impl<'kdl> visit::Document<'kdl> for Values<'kdl> {
+///     type Output = Vec<&'kdl str>;
+///     fn finish(self) -> Self::Output {
+///         self.0
+///     }
+/// }
+///
+/// impl<'kdl> visit::Children<'kdl> for Values<'kdl> {
+///     type VisitNode = Self;
+///     fn visit_node(&mut self) -> Self::VisitNode {
+///         Values(vec![])
+///     }
+///     fn finish_node(&mut self, node: Self::VisitNode) {
+///         self.0.extend(node.0);
+///     }
+/// }
+///
+/// impl<'kdl> visit::Node<'kdl> for Values<'kdl> {
+///     type VisitArgument = Self;
+///     type VisitProperty = Self;
+///     type VisitChildren = ();
+///     fn visit_argument(&mut self) -> Self::VisitArgument {
+///         Values(vec![])
+///     }
+///     fn finish_argument(&mut self, argument: Self::VisitArgument) {
+///         self.0.extend(argument.0);
+///     }
+///     fn visit_property(&mut self) -> Self::VisitProperty {
+///         Values(vec![])
+///     }
+///     fn finish_property(&mut self, property: Self::VisitProperty) {
+///         self.0.extend(property.0);
+///     }
+///     fn visit_children(&mut self) {}
+/// }
+///
+/// impl<'kdl> visit::Argument<'kdl> for Values<'kdl> {
+///     fn visit_value(&mut self, value: visit::Value<'kdl>) {
+///         self.0.push(value.source());
+///     }
+/// }
+///
+/// impl<'kdl> visit::Property<'kdl> for Values<'kdl> {
+///     fn visit_value(&mut self, value: visit::Value<'kdl>) {
+///         self.0.push(value.source());
+///     }
+/// }
+///
+/// let redacted = visit::String::from_source("\"<redacted>\"").unwrap();
+/// let redact = |value| match value {
+///     visit::Value::String(_) => visit::Value::String(redacted),
+///     value => value,
+/// };
+/// let kdl = "login user=\"admin\" password=\"hunter2\" 3";
+/// let values = visit_kdl_string(kdl, visit::MapValues::new(Values(vec![]), redact))?;
+/// assert_eq!(values, ["\"<redacted>\"", "\"<redacted>\"", "3"]);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MapValues<'a, V, F> {
+    visitor: V,
+    map: Shared<'a, F>,
+}
+
+impl<'a, V, F> MapValues<'a, V, F> {
+    pub fn new(visitor: V, map: F) -> Self {
+        MapValues {
+            visitor,
+            map: Shared::Owned(map),
+        }
+    }
+
+    pub fn into_inner(self) -> V {
+        self.visitor
+    }
+
+    fn child<'b, C>(&'b mut self, visit: impl FnOnce(&'b mut V) -> C) -> MapValues<'b, C, F> {
+        MapValues {
+            visitor: visit(&mut self.visitor),
+            map: self.map.lend(),
+        }
+    }
+}
+
+impl<'kdl, V, F> visit::LendDocument<'kdl> for MapValues<'_, V, F>
+where
+    V: visit::LendDocument<'kdl>,
+    F: FnMut(visit::Value<'kdl>) -> visit::Value<'kdl>,
+{
+    type Output = V::Output;
+
+    fn finish(self) -> Self::Output {
+        self.visitor.finish()
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        self.visitor.finish_error(error)
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.visitor.split_trivia()
+    }
+}
+
+impl<'kdl, V, F> visit::LendChildren<'kdl> for MapValues<'_, V, F>
+where
+    V: visit::LendChildren<'kdl>,
+    F: FnMut(visit::Value<'kdl>) -> visit::Value<'kdl>,
+{
+    type VisitNode<'a>
+        = MapValues<'a, V::VisitNode<'a>, F>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child(|v| v.visit_node())
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.visitor.visit_slashdash_node()
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.visitor.finish_slashdash_node();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+impl<'kdl, V, F> visit::LendNode<'kdl> for MapValues<'_, V, F>
+where
+    V: visit::LendNode<'kdl>,
+    F: FnMut(visit::Value<'kdl>) -> visit::Value<'kdl>,
+{
+    type VisitArgument<'a>
+        = MapValues<'a, V::VisitArgument<'a>, F>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = MapValues<'a, V::VisitProperty<'a>, F>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = MapValues<'a, V::VisitChildren<'a>, F>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visitor.visit_type(annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.visitor.visit_name(name);
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child(|v| v.visit_argument())
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child(|v| v.visit_property())
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child(|v| v.visit_children())
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.visitor.visit_slashdash_entry()
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.visitor.finish_slashdash_entry();
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        self.visitor.visit_slashdash_children()
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        self.visitor.finish_slashdash_children();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+impl<'kdl, V, F> visit::Property<'kdl> for MapValues<'_, V, F>
+where
+    V: visit::Property<'kdl>,
+    F: FnMut(visit::Value<'kdl>) -> visit::Value<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.visitor.visit_name(name);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visitor.visit_type(annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        let value = (*self.map)(value);
+        self.visitor.visit_value(value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+impl<'kdl, V, F> visit::Argument<'kdl> for MapValues<'_, V, F>
+where
+    V: visit::Argument<'kdl>,
+    F: FnMut(visit::Value<'kdl>) -> visit::Value<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visitor.visit_type(annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        let value = (*self.map)(value);
+        self.visitor.visit_value(value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
@@ -1,14 +1,62 @@
+pub(crate) use self::abort::Abortable;
 pub(crate) use self::helpers::*;
-pub use self::terminals::{Identifier, Number, String, Value};
-use crate::{visit, ParseError};
+pub(crate) use self::lend::Shared;
+pub use self::{
+    abort::{Abort, Aborted},
+    either::Either,
+    erased::{DynArgument, DynChildren, DynDocument, DynNode, DynProperty},
+    filter::{FilterNode, FilterNodes},
+    helpers::{
+        ArgumentExt, ArgumentVisitor, ChildrenExt, ChildrenVisitor, JustType, JustValue, NodeExt,
+        NodeVisitor, PropertyExt, PropertyVisitor, Trivia, TriviaVisitor,
+    },
+    ignore::Ignore,
+    lend::{LendChildren, LendDocument, LendNode, OwnedChild},
+    map::MapValues,
+    skip::Skip,
+    tee::Tee,
+    terminals::{Comment, CommentKind, Identifier, Number, String, Value},
+    top_level::TopLevelOnly,
+    verbatim::Verbatim,
+};
+#[cfg(feature = "alloc")]
+pub use self::{
+    closures::{fn_visitor, FnChild, FnVisitor},
+    path::{
+        Ancestor, Context, PathArgument, PathChildren, PathDocument, PathNode, PathProperty,
+        WithPath,
+    },
+    recorder::{Event, Recorder},
+};
+use crate::{lint::Warning, visit, ParseError};
 
+mod abort;
+#[cfg(feature = "alloc")]
+mod closures;
+mod either;
+mod erased;
+mod filter;
 mod helpers;
+mod ignore;
+mod lend;
+mod map;
+#[cfg(feature = "alloc")]
+mod path;
+#[cfg(feature = "alloc")]
+mod recorder;
+mod skip;
+mod tee;
 mod terminals;
+mod top_level;
+mod verbatim;
 
 pub mod prelude {
     #[allow(unreachable_pub)] // false positive as of Rust 1.63
-    pub use super::{Argument as _, Children as _, Node as _, Property as _};
-    pub(crate) use super::{ArgumentExt as _, ChildrenExt as _, NodeExt as _, PropertyExt as _};
+    pub use super::{
+        Argument as _, ArgumentExt as _, Children as _, ChildrenExt as _, Node as _, NodeExt as _,
+        Property as _, PropertyExt as _,
+    };
+    pub(crate) use super::{ParseChildrenExt as _, ParseNodeExt as _};
 }
 
 pub trait Document<'kdl>: Sized + visit::Children<'kdl> {
@@ -17,6 +65,17 @@ pub trait Document<'kdl>: Sized + visit::Children<'kdl> {
     fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
         Err(error)
     }
+
+    /// Opt in to visiting comments, newlines and line continuations with
+    /// `visit_comment`, `visit_newline` and `visit_escline`.
+    ///
+    /// If this returns `true`, whitespace is split into comments and the
+    /// whitespace between them, and runs of newlines into single newlines.
+    /// Otherwise, as by default, each run of whitespace or newlines is
+    /// visited with `visit_trivia` whole, comments included.
+    fn split_trivia(&self) -> bool {
+        false
+    }
 }
 
 pub trait Children<'kdl> {
@@ -26,9 +85,43 @@ pub trait Children<'kdl> {
         let _ = trivia;
     }
 
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+
     fn visit_node(&mut self) -> Self::VisitNode;
     fn finish_node(&mut self, _: Self::VisitNode) {}
 
+    /// Opt in to visiting the node commented out by a `/-` slashdash.
+    ///
+    /// If this returns `true`, the commented-out node is visited with
+    /// [`visit_node`] and [`finish_node`] like any other, followed by a call
+    /// to [`finish_slashdash_node`]. Otherwise, as by default, it is visited
+    /// as trivia.
+    ///
+    /// [`visit_node`]: Children::visit_node
+    /// [`finish_node`]: Children::finish_node
+    /// [`finish_slashdash_node`]: Children::finish_slashdash_node
+    fn visit_slashdash_node(&mut self) -> bool {
+        false
+    }
+    fn finish_slashdash_node(&mut self) {}
+
+    /// Called with a warning from an adapter, such as a [`Linter`].
+    ///
+    /// [`Linter`]: crate::lint::Linter
+    fn visit_warning(&mut self, warning: Warning) {
+        let _ = warning;
+    }
+
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
         Err(error)
     }
@@ -43,6 +136,18 @@ pub trait Node<'kdl> {
         let _ = trivia;
     }
 
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+
     fn visit_type(&mut self, _: visit::Identifier<'kdl>) {}
     fn visit_name(&mut self, _: visit::Identifier<'kdl>) {}
 
@@ -55,6 +160,39 @@ pub trait Node<'kdl> {
     fn visit_children(&mut self) -> Self::VisitChildren;
     fn finish_children(&mut self, _: Self::VisitChildren) {}
 
+    /// Opt in to visiting the argument or property commented out by a `/-`
+    /// slashdash.
+    ///
+    /// If this returns `true`, the commented-out entry is visited like any
+    /// other, followed by a call to [`finish_slashdash_entry`]. Otherwise, as
+    /// by default, it is visited as trivia.
+    ///
+    /// [`finish_slashdash_entry`]: Node::finish_slashdash_entry
+    fn visit_slashdash_entry(&mut self) -> bool {
+        false
+    }
+    fn finish_slashdash_entry(&mut self) {}
+
+    /// Opt in to visiting the children block commented out by a `/-`
+    /// slashdash.
+    ///
+    /// If this returns `true`, the commented-out block is visited with
+    /// [`visit_children`] and [`finish_children`], followed by a call to
+    /// [`finish_slashdash_children`]. Otherwise, as by default, it is visited
+    /// as trivia. Note that a node may then have its children visited twice.
+    ///
+    /// [`visit_children`]: Node::visit_children
+    /// [`finish_children`]: Node::finish_children
+    /// [`finish_slashdash_children`]: Node::finish_slashdash_children
+    fn visit_slashdash_children(&mut self) -> bool {
+        false
+    }
+    fn finish_slashdash_children(&mut self) {}
+
+    fn visit_warning(&mut self, warning: Warning) {
+        let _ = warning;
+    }
+
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
         Err(error)
     }
@@ -65,10 +203,26 @@ pub trait Property<'kdl> {
         let _ = trivia;
     }
 
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+
     fn visit_name(&mut self, _: visit::Identifier<'kdl>) {}
     fn visit_type(&mut self, _: visit::Identifier<'kdl>) {}
     fn visit_value(&mut self, _: visit::Value<'kdl>) {}
 
+    fn visit_warning(&mut self, warning: Warning) {
+        let _ = warning;
+    }
+
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
         Err(error)
     }
@@ -79,9 +233,25 @@ pub trait Argument<'kdl> {
         let _ = trivia;
     }
 
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(comment.source());
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visit_trivia(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visit_trivia(escline);
+    }
+
     fn visit_type(&mut self, _: visit::Identifier<'kdl>) {}
     fn visit_value(&mut self, _: visit::Value<'kdl>) {}
 
+    fn visit_warning(&mut self, warning: Warning) {
+        let _ = warning;
+    }
+
     fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
         Err(error)
     }
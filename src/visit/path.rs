@@ -0,0 +1,588 @@
+use {
+    crate::{
+        visit::{self, Shared},
+        ParseError,
+    },
+    alloc::vec::Vec,
+};
+
+/// Where in the document a visitor callback is being made.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context<'kdl> {
+    path: Vec<Ancestor<'kdl>>,
+}
+
+/// A node enclosing the current position in the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Ancestor<'kdl> {
+    /// The node's type annotation, once visited.
+    pub ty: Option<visit::Identifier<'kdl>>,
+    /// The node's name, once visited.
+    pub name: Option<visit::Identifier<'kdl>>,
+    /// The index of the node among its siblings.
+    pub index: usize,
+    /// The number of arguments visited so far, i.e. the index of the
+    /// argument currently being visited.
+    pub arguments: usize,
+    /// The number of properties visited so far, i.e. the index of the
+    /// property currently being visited.
+    pub properties: usize,
+}
+
+impl<'kdl> Context<'kdl> {
+    /// The nodes enclosing the current position, outermost first.
+    ///
+    /// For node callbacks, including the `visit_node` and `finish_node` calls
+    /// which create and finish it, the innermost is the node itself. For
+    /// argument, property, and children callbacks, it's their node.
+    pub fn path(&self) -> &[Ancestor<'kdl>] {
+        &self.path
+    }
+
+    /// The number of nodes enclosing the current position.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// The innermost enclosing node, if any.
+    pub fn node(&self) -> Option<&Ancestor<'kdl>> {
+        self.path.last()
+    }
+
+    /// The index of the innermost enclosing node among its siblings.
+    pub fn child_index(&self) -> Option<usize> {
+        self.node().map(|node| node.index)
+    }
+
+    /// The index of the argument currently being visited.
+    pub fn argument_index(&self) -> Option<usize> {
+        self.node().map(|node| node.arguments)
+    }
+
+    /// The index of the property currently being visited.
+    pub fn property_index(&self) -> Option<usize> {
+        self.node().map(|node| node.properties)
+    }
+
+    fn node_mut(&mut self) -> &mut Ancestor<'kdl> {
+        self.path
+            .last_mut()
+            .expect("kdl node visitor should be inside a node")
+    }
+}
+
+/// Like [`visit::Document`], but given the [`Context`]. See [`WithPath`].
+pub trait PathDocument<'kdl>: Sized + PathChildren<'kdl> {
+    type Output;
+    fn finish(self) -> Self::Output;
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        Err(error)
+    }
+
+    /// See [`visit::Document::split_trivia`].
+    fn split_trivia(&self) -> bool {
+        false
+    }
+}
+
+/// Like [`visit::Children`], but given the [`Context`]. See [`WithPath`].
+pub trait PathChildren<'kdl> {
+    type VisitNode: PathNode<'kdl>;
+
+    fn visit_trivia(&mut self, cx: &Context<'kdl>, trivia: &'kdl str) {
+        let _ = (cx, trivia);
+    }
+
+    fn visit_comment(&mut self, cx: &Context<'kdl>, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(cx, comment.source());
+    }
+
+    fn visit_newline(&mut self, cx: &Context<'kdl>, newline: &'kdl str) {
+        self.visit_trivia(cx, newline);
+    }
+
+    fn visit_escline(&mut self, cx: &Context<'kdl>, escline: &'kdl str) {
+        self.visit_trivia(cx, escline);
+    }
+
+    fn visit_node(&mut self, cx: &Context<'kdl>) -> Self::VisitNode;
+    fn finish_node(&mut self, cx: &Context<'kdl>, _: Self::VisitNode) {
+        let _ = cx;
+    }
+
+    fn visit_slashdash_node(&mut self, cx: &Context<'kdl>) -> bool {
+        let _ = cx;
+        false
+    }
+    fn finish_slashdash_node(&mut self, cx: &Context<'kdl>) {
+        let _ = cx;
+    }
+
+    fn visit_error(&mut self, cx: &Context<'kdl>, error: ParseError) -> Result<(), ParseError> {
+        let _ = cx;
+        Err(error)
+    }
+}
+
+/// Like [`visit::Node`], but given the [`Context`]. See [`WithPath`].
+pub trait PathNode<'kdl> {
+    type VisitArgument: PathArgument<'kdl>;
+    type VisitProperty: PathProperty<'kdl>;
+    type VisitChildren: PathChildren<'kdl>;
+
+    fn visit_trivia(&mut self, cx: &Context<'kdl>, trivia: &'kdl str) {
+        let _ = (cx, trivia);
+    }
+
+    fn visit_comment(&mut self, cx: &Context<'kdl>, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(cx, comment.source());
+    }
+
+    fn visit_newline(&mut self, cx: &Context<'kdl>, newline: &'kdl str) {
+        self.visit_trivia(cx, newline);
+    }
+
+    fn visit_escline(&mut self, cx: &Context<'kdl>, escline: &'kdl str) {
+        self.visit_trivia(cx, escline);
+    }
+
+    fn visit_type(&mut self, cx: &Context<'kdl>, _: visit::Identifier<'kdl>) {
+        let _ = cx;
+    }
+    fn visit_name(&mut self, cx: &Context<'kdl>, _: visit::Identifier<'kdl>) {
+        let _ = cx;
+    }
+
+    fn visit_argument(&mut self, cx: &Context<'kdl>) -> Self::VisitArgument;
+    fn finish_argument(&mut self, cx: &Context<'kdl>, _: Self::VisitArgument) {
+        let _ = cx;
+    }
+
+    fn visit_property(&mut self, cx: &Context<'kdl>) -> Self::VisitProperty;
+    fn finish_property(&mut self, cx: &Context<'kdl>, _: Self::VisitProperty) {
+        let _ = cx;
+    }
+
+    fn visit_children(&mut self, cx: &Context<'kdl>) -> Self::VisitChildren;
+    fn finish_children(&mut self, cx: &Context<'kdl>, _: Self::VisitChildren) {
+        let _ = cx;
+    }
+
+    fn visit_slashdash_entry(&mut self, cx: &Context<'kdl>) -> bool {
+        let _ = cx;
+        false
+    }
+    fn finish_slashdash_entry(&mut self, cx: &Context<'kdl>) {
+        let _ = cx;
+    }
+
+    fn visit_slashdash_children(&mut self, cx: &Context<'kdl>) -> bool {
+        let _ = cx;
+        false
+    }
+    fn finish_slashdash_children(&mut self, cx: &Context<'kdl>) {
+        let _ = cx;
+    }
+
+    fn visit_error(&mut self, cx: &Context<'kdl>, error: ParseError) -> Result<(), ParseError> {
+        let _ = cx;
+        Err(error)
+    }
+}
+
+/// Like [`visit::Property`], but given the [`Context`]. See [`WithPath`].
+pub trait PathProperty<'kdl> {
+    fn visit_trivia(&mut self, cx: &Context<'kdl>, trivia: &'kdl str) {
+        let _ = (cx, trivia);
+    }
+
+    fn visit_comment(&mut self, cx: &Context<'kdl>, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(cx, comment.source());
+    }
+
+    fn visit_newline(&mut self, cx: &Context<'kdl>, newline: &'kdl str) {
+        self.visit_trivia(cx, newline);
+    }
+
+    fn visit_escline(&mut self, cx: &Context<'kdl>, escline: &'kdl str) {
+        self.visit_trivia(cx, escline);
+    }
+
+    fn visit_name(&mut self, cx: &Context<'kdl>, _: visit::Identifier<'kdl>) {
+        let _ = cx;
+    }
+    fn visit_type(&mut self, cx: &Context<'kdl>, _: visit::Identifier<'kdl>) {
+        let _ = cx;
+    }
+    fn visit_value(&mut self, cx: &Context<'kdl>, _: visit::Value<'kdl>) {
+        let _ = cx;
+    }
+
+    fn visit_error(&mut self, cx: &Context<'kdl>, error: ParseError) -> Result<(), ParseError> {
+        let _ = cx;
+        Err(error)
+    }
+}
+
+/// Like [`visit::Argument`], but given the [`Context`]. See [`WithPath`].
+pub trait PathArgument<'kdl> {
+    fn visit_trivia(&mut self, cx: &Context<'kdl>, trivia: &'kdl str) {
+        let _ = (cx, trivia);
+    }
+
+    fn visit_comment(&mut self, cx: &Context<'kdl>, comment: visit::Comment<'kdl>) {
+        self.visit_trivia(cx, comment.source());
+    }
+
+    fn visit_newline(&mut self, cx: &Context<'kdl>, newline: &'kdl str) {
+        self.visit_trivia(cx, newline);
+    }
+
+    fn visit_escline(&mut self, cx: &Context<'kdl>, escline: &'kdl str) {
+        self.visit_trivia(cx, escline);
+    }
+
+    fn visit_type(&mut self, cx: &Context<'kdl>, _: visit::Identifier<'kdl>) {
+        let _ = cx;
+    }
+    fn visit_value(&mut self, cx: &Context<'kdl>, _: visit::Value<'kdl>) {
+        let _ = cx;
+    }
+
+    fn visit_error(&mut self, cx: &Context<'kdl>, error: ParseError) -> Result<(), ParseError> {
+        let _ = cx;
+        Err(error)
+    }
+}
+
+/// An adapter which maintains the [`Context`] of the current position in the
+/// document, giving it to every callback of the wrapped visitor.
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// struct Depths(Vec<(usize, usize)>);
+///
+/// impl visit::PathDocument<'_> for Depths {
+///     type Output = Vec<(usize, usize)>;
+///     fn finish(self) -> Self::Output {
+///         self.0
+///     }
+/// }
+///
+/// impl<'kdl> visit::PathChildren<'kdl> for Depths {
+///     type VisitNode = Self;
+///     fn visit_node(&mut self, _: &visit::Context<'kdl>) -> Self {
+///         Depths(vec![])
+///     }
+///     fn finish_node(&mut self, cx: &visit::Context<'kdl>, node: Self) {
+///         self.0.push((cx.depth(), cx.child_index().unwrap()));
+///         self.0.extend(node.0);
+///     }
+/// }
+///
+/// impl<'kdl> visit::PathNode<'kdl> for Depths {
+///     type VisitArgument = ();
+///     type VisitProperty = ();
+///     type VisitChildren = Self;
+///     fn visit_argument(&mut self, _: &visit::Context<'kdl>) {}
+///     fn visit_property(&mut self, _: &visit::Context<'kdl>) {}
+///     fn visit_children(&mut self, _: &visit::Context<'kdl>) -> Self {
+///         Depths(vec![])
+///     }
+///     fn finish_children(&mut self, _: &visit::Context<'kdl>, children: Self) {
+///         self.0.extend(children.0);
+///     }
+/// }
+///
+/// let kdl = "a {\n    b\n    c { d; }\n}";
+/// let depths = visit_kdl_string(kdl, visit::WithPath::new(Depths(vec![])))?;
+/// assert_eq!(depths, [(1, 0), (2, 0), (2, 1), (3, 0)]);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+///
+/// Child visitors are lent out as [`visit::OwnedChild`]ren, which borrow the
+/// context and give the wrapped child visitor back to its parent when dropped.
+#[derive(Debug)]
+pub struct WithPath<'a, 'kdl, V> {
+    visitor: V,
+    cx: Shared<'a, Context<'kdl>>,
+    /// The number of child nodes visited so far.
+    children: usize,
+}
+
+type PathChild<'a, 'kdl, P, C> = visit::OwnedChild<'a, P, WithPath<'a, 'kdl, C>>;
+
+impl<'a, 'kdl, V> WithPath<'a, 'kdl, V> {
+    pub fn new(visitor: V) -> Self {
+        WithPath {
+            visitor,
+            cx: Shared::Owned(Context::default()),
+            children: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> V {
+        self.visitor
+    }
+
+    fn child<C>(
+        &mut self,
+        visit: impl FnOnce(&mut V, &Context<'kdl>) -> C,
+        finish: fn((&mut V, WithPath<'_, 'kdl, C>)),
+    ) -> PathChild<'_, 'kdl, V, C> {
+        let child = WithPath {
+            visitor: visit(&mut self.visitor, &self.cx),
+            cx: self.cx.lend(),
+            children: 0,
+        };
+        visit::OwnedChild::new(&mut self.visitor, child, finish)
+    }
+}
+
+impl<'kdl, V: PathDocument<'kdl>> visit::LendDocument<'kdl> for WithPath<'_, 'kdl, V> {
+    type Output = V::Output;
+
+    fn finish(self) -> Self::Output {
+        self.visitor.finish()
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        self.visitor.finish_error(error)
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.visitor.split_trivia()
+    }
+}
+
+impl<'kdl, V: PathChildren<'kdl>> visit::LendChildren<'kdl> for WithPath<'_, 'kdl, V> {
+    type VisitNode<'a>
+        = PathChild<'a, 'kdl, V, V::VisitNode>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(&self.cx, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(&self.cx, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(&self.cx, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(&self.cx, escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        let index = self.children;
+        self.children += 1;
+        self.cx.path.push(Ancestor {
+            ty: None,
+            name: None,
+            index,
+            arguments: 0,
+            properties: 0,
+        });
+        self.child(
+            |visitor, cx| visitor.visit_node(cx),
+            |(visitor, mut node)| {
+                visitor.finish_node(&node.cx, node.visitor);
+                node.cx.path.pop();
+            },
+        )
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.visitor.visit_slashdash_node(&self.cx)
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.visitor.finish_slashdash_node(&self.cx);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(&self.cx, error)
+    }
+}
+
+impl<'kdl, V: PathNode<'kdl>> visit::LendNode<'kdl> for WithPath<'_, 'kdl, V> {
+    type VisitArgument<'a>
+        = PathChild<'a, 'kdl, V, V::VisitArgument>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = PathChild<'a, 'kdl, V, V::VisitProperty>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = PathChild<'a, 'kdl, V, V::VisitChildren>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(&self.cx, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(&self.cx, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(&self.cx, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(&self.cx, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.cx.node_mut().ty = Some(annotation);
+        self.visitor.visit_type(&self.cx, annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.cx.node_mut().name = Some(name);
+        self.visitor.visit_name(&self.cx, name);
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child(
+            |visitor, cx| visitor.visit_argument(cx),
+            |(visitor, mut argument)| {
+                visitor.finish_argument(&argument.cx, argument.visitor);
+                argument.cx.node_mut().arguments += 1;
+            },
+        )
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child(
+            |visitor, cx| visitor.visit_property(cx),
+            |(visitor, mut property)| {
+                visitor.finish_property(&property.cx, property.visitor);
+                property.cx.node_mut().properties += 1;
+            },
+        )
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child(
+            |visitor, cx| visitor.visit_children(cx),
+            |(visitor, children)| visitor.finish_children(&children.cx, children.visitor),
+        )
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.visitor.visit_slashdash_entry(&self.cx)
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.visitor.finish_slashdash_entry(&self.cx);
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        self.visitor.visit_slashdash_children(&self.cx)
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        self.visitor.finish_slashdash_children(&self.cx);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(&self.cx, error)
+    }
+}
+
+impl<'kdl, V: PathProperty<'kdl>> visit::Property<'kdl> for WithPath<'_, 'kdl, V> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(&self.cx, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(&self.cx, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(&self.cx, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(&self.cx, escline);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.visitor.visit_name(&self.cx, name);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visitor.visit_type(&self.cx, annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.visitor.visit_value(&self.cx, value);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(&self.cx, error)
+    }
+}
+
+impl<'kdl, V: PathArgument<'kdl>> visit::Argument<'kdl> for WithPath<'_, 'kdl, V> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(&self.cx, trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(&self.cx, comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(&self.cx, newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(&self.cx, escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visitor.visit_type(&self.cx, annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.visitor.visit_value(&self.cx, value);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(&self.cx, error)
+    }
+}
+
+// Like `()`, the canonical validator implementation
+impl<'kdl> PathDocument<'kdl> for () {
+    type Output = ();
+    fn finish(self) {}
+}
+
+impl<'kdl> PathChildren<'kdl> for () {
+    type VisitNode = ();
+
+    fn visit_node(&mut self, _: &Context<'kdl>) -> Self::VisitNode {}
+}
+
+impl<'kdl> PathNode<'kdl> for () {
+    type VisitArgument = ();
+    type VisitProperty = ();
+    type VisitChildren = ();
+
+    fn visit_argument(&mut self, _: &Context<'kdl>) -> Self::VisitArgument {}
+    fn visit_property(&mut self, _: &Context<'kdl>) -> Self::VisitProperty {}
+    fn visit_children(&mut self, _: &Context<'kdl>) -> Self::VisitChildren {}
+}
+
+impl<'kdl> PathProperty<'kdl> for () {}
+impl<'kdl> PathArgument<'kdl> for () {}
@@ -0,0 +1,447 @@
+use {
+    crate::{
+        lint::Warning,
+        visit::{self, Shared},
+        ParseError,
+    },
+    alloc::vec::Vec,
+    core::slice,
+};
+
+/// A single recorded visitor call.
+///
+/// Calls which create a child visitor are recorded as the start of that
+/// child, and the matching finish call as [`Event::End`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'kdl> {
+    Trivia(&'kdl str),
+    Comment(visit::Comment<'kdl>),
+    Newline(&'kdl str),
+    Escline(&'kdl str),
+    Type(visit::Identifier<'kdl>),
+    Name(visit::Identifier<'kdl>),
+    Value(visit::Value<'kdl>),
+    Error(ParseError),
+    Warning(Warning),
+    Node,
+    Argument,
+    Property,
+    Children,
+    End,
+}
+
+/// A visitor which records the calls made to it, to be replayed later.
+///
+/// This gives streaming visitors bounded lookahead: e.g. a node can be
+/// recorded, inspected, then replayed into a visitor chosen based on the
+/// whole node. Terminals borrow from the source, so recording is cheap.
+///
+/// All errors are recovered from while recording. When replaying, the
+/// recorded errors are given to the visitor, and replaying stops if the
+/// visitor doesn't recover from one.
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// let kdl = "node 1 2 kind=\"late\"";
+/// let recorder = visit_kdl_string(kdl, visit::Recorder::new())?;
+/// let mut events = recorder.events().iter();
+/// events.find(|event| matches!(event, visit::Event::Name(name) if name.source() == "kind"));
+/// let kind = events.find_map(|event| match event {
+///     visit::Event::Value(value) => Some(value.source()),
+///     _ => None,
+/// });
+/// assert_eq!(kind, Some("\"late\""));
+/// assert_eq!(recorder.replay_document(visit::Verbatim::new(kdl))?, kdl);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+///
+/// Child visitors record into the events of the recorder they were lent by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recorder<'a, 'kdl> {
+    events: Shared<'a, Vec<Event<'kdl>>>,
+    /// The error which stopped parsing, if recording a document.
+    fatal: Option<ParseError>,
+}
+
+impl<'a, 'kdl> Recorder<'a, 'kdl> {
+    pub fn new() -> Self {
+        Recorder {
+            events: Shared::Owned(Vec::new()),
+            fatal: None,
+        }
+    }
+
+    /// The recorded events.
+    pub fn events(&self) -> &[Event<'kdl>] {
+        &self.events
+    }
+
+    /// Replay the recorded events as a document.
+    pub fn replay_document<V: visit::LendDocument<'kdl>>(
+        &self,
+        mut visitor: V,
+    ) -> Result<V::Output, ParseError> {
+        match replay_children(&mut self.events().iter(), &mut visitor) {
+            Ok(()) => match self.fatal {
+                None => Ok(visitor.finish()),
+                Some(error) => visitor.finish_error(error),
+            },
+            Err(error) => visitor.finish_error(error),
+        }
+    }
+
+    /// Replay the recorded events as children.
+    pub fn replay_children(
+        &self,
+        visitor: &mut impl visit::LendChildren<'kdl>,
+    ) -> Result<(), ParseError> {
+        replay_children(&mut self.events().iter(), visitor)
+    }
+
+    /// Replay the recorded events as a node.
+    pub fn replay_node(&self, visitor: &mut impl visit::LendNode<'kdl>) -> Result<(), ParseError> {
+        replay_node(&mut self.events().iter(), visitor)
+    }
+
+    /// Replay the recorded events as a property.
+    pub fn replay_property(
+        &self,
+        visitor: &mut impl visit::Property<'kdl>,
+    ) -> Result<(), ParseError> {
+        replay_property(&mut self.events().iter(), visitor)
+    }
+
+    /// Replay the recorded events as an argument.
+    pub fn replay_argument(
+        &self,
+        visitor: &mut impl visit::Argument<'kdl>,
+    ) -> Result<(), ParseError> {
+        replay_argument(&mut self.events().iter(), visitor)
+    }
+
+    fn record(&mut self, event: Event<'kdl>) {
+        self.events.push(event);
+    }
+
+    fn child(&mut self, event: Event<'kdl>) -> Recorder<'_, 'kdl> {
+        self.record(event);
+        Recorder {
+            events: self.events.lend(),
+            fatal: None,
+        }
+    }
+}
+
+impl Drop for Recorder<'_, '_> {
+    fn drop(&mut self) {
+        if let Shared::Borrowed(events) = &mut self.events {
+            events.push(Event::End);
+        }
+    }
+}
+
+impl Default for Recorder<'_, '_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+type Events<'a, 'kdl> = slice::Iter<'a, Event<'kdl>>;
+
+/// Skip the rest of a child component which doesn't apply to this visitor.
+fn skip(events: &mut Events<'_, '_>) {
+    let mut depth = 0_usize;
+    for event in events {
+        match event {
+            Event::Node | Event::Argument | Event::Property | Event::Children => depth += 1,
+            Event::End if depth == 0 => return,
+            Event::End => depth -= 1,
+            _ => {}
+        }
+    }
+}
+
+fn replay_children<'kdl>(
+    events: &mut Events<'_, 'kdl>,
+    visitor: &mut impl visit::LendChildren<'kdl>,
+) -> Result<(), ParseError> {
+    while let Some(&event) = events.next() {
+        match event {
+            Event::Trivia(trivia) => visitor.visit_trivia(trivia),
+            Event::Comment(comment) => visitor.visit_comment(comment),
+            Event::Newline(newline) => visitor.visit_newline(newline),
+            Event::Escline(escline) => visitor.visit_escline(escline),
+            Event::Error(error) => visitor.visit_error(error)?,
+            Event::Warning(warning) => visitor.visit_warning(warning),
+            Event::Node => {
+                let mut node_visitor = visitor.visit_node();
+                replay_node(events, &mut node_visitor)?;
+            }
+            Event::Argument | Event::Property | Event::Children => skip(events),
+            Event::End => break,
+            Event::Type(_) | Event::Name(_) | Event::Value(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn replay_node<'kdl>(
+    events: &mut Events<'_, 'kdl>,
+    visitor: &mut impl visit::LendNode<'kdl>,
+) -> Result<(), ParseError> {
+    while let Some(&event) = events.next() {
+        match event {
+            Event::Trivia(trivia) => visitor.visit_trivia(trivia),
+            Event::Comment(comment) => visitor.visit_comment(comment),
+            Event::Newline(newline) => visitor.visit_newline(newline),
+            Event::Escline(escline) => visitor.visit_escline(escline),
+            Event::Type(annotation) => visitor.visit_type(annotation),
+            Event::Name(name) => visitor.visit_name(name),
+            Event::Error(error) => visitor.visit_error(error)?,
+            Event::Warning(warning) => visitor.visit_warning(warning),
+            Event::Argument => {
+                let mut argument_visitor = visitor.visit_argument();
+                replay_argument(events, &mut argument_visitor)?;
+            }
+            Event::Property => {
+                let mut property_visitor = visitor.visit_property();
+                replay_property(events, &mut property_visitor)?;
+            }
+            Event::Children => {
+                let mut children_visitor = visitor.visit_children();
+                replay_children(events, &mut children_visitor)?;
+            }
+            Event::Node => skip(events),
+            Event::End => break,
+            Event::Value(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn replay_property<'kdl>(
+    events: &mut Events<'_, 'kdl>,
+    visitor: &mut impl visit::Property<'kdl>,
+) -> Result<(), ParseError> {
+    while let Some(&event) = events.next() {
+        match event {
+            Event::Trivia(trivia) => visitor.visit_trivia(trivia),
+            Event::Comment(comment) => visitor.visit_comment(comment),
+            Event::Newline(newline) => visitor.visit_newline(newline),
+            Event::Escline(escline) => visitor.visit_escline(escline),
+            Event::Name(name) => visitor.visit_name(name),
+            Event::Type(annotation) => visitor.visit_type(annotation),
+            Event::Value(value) => visitor.visit_value(value),
+            Event::Error(error) => visitor.visit_error(error)?,
+            Event::Warning(warning) => visitor.visit_warning(warning),
+            Event::Node | Event::Argument | Event::Property | Event::Children => skip(events),
+            Event::End => break,
+        }
+    }
+    Ok(())
+}
+
+fn replay_argument<'kdl>(
+    events: &mut Events<'_, 'kdl>,
+    visitor: &mut impl visit::Argument<'kdl>,
+) -> Result<(), ParseError> {
+    while let Some(&event) = events.next() {
+        match event {
+            Event::Trivia(trivia) => visitor.visit_trivia(trivia),
+            Event::Comment(comment) => visitor.visit_comment(comment),
+            Event::Newline(newline) => visitor.visit_newline(newline),
+            Event::Escline(escline) => visitor.visit_escline(escline),
+            Event::Type(annotation) => visitor.visit_type(annotation),
+            Event::Value(value) => visitor.visit_value(value),
+            Event::Error(error) => visitor.visit_error(error)?,
+            Event::Warning(warning) => visitor.visit_warning(warning),
+            Event::Node | Event::Argument | Event::Property | Event::Children => skip(events),
+            Event::End => break,
+            Event::Name(_) => {}
+        }
+    }
+    Ok(())
+}
+
+impl<'kdl> visit::LendDocument<'kdl> for Recorder<'_, 'kdl> {
+    type Output = Self;
+
+    fn finish(self) -> Self::Output {
+        self
+    }
+
+    fn finish_error(mut self, error: ParseError) -> Result<Self::Output, ParseError> {
+        self.fatal = Some(error);
+        Ok(self)
+    }
+}
+
+impl<'kdl> visit::LendChildren<'kdl> for Recorder<'_, 'kdl> {
+    type VisitNode<'a>
+        = Recorder<'a, 'kdl>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.record(Event::Trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.record(Event::Comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.record(Event::Newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.record(Event::Escline(escline));
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child(Event::Node)
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.record(Event::Warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.record(Event::Error(error));
+        Ok(())
+    }
+}
+
+impl<'kdl> visit::LendNode<'kdl> for Recorder<'_, 'kdl> {
+    type VisitArgument<'a>
+        = Recorder<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = Recorder<'a, 'kdl>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = Recorder<'a, 'kdl>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.record(Event::Trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.record(Event::Comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.record(Event::Newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.record(Event::Escline(escline));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.record(Event::Type(annotation));
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.record(Event::Name(name));
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child(Event::Argument)
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child(Event::Property)
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child(Event::Children)
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.record(Event::Warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.record(Event::Error(error));
+        Ok(())
+    }
+}
+
+impl<'kdl> visit::Property<'kdl> for Recorder<'_, 'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.record(Event::Trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.record(Event::Comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.record(Event::Newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.record(Event::Escline(escline));
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.record(Event::Name(name));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.record(Event::Type(annotation));
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.record(Event::Value(value));
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.record(Event::Warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.record(Event::Error(error));
+        Ok(())
+    }
+}
+
+impl<'kdl> visit::Argument<'kdl> for Recorder<'_, 'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.record(Event::Trivia(trivia));
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.record(Event::Comment(comment));
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.record(Event::Newline(newline));
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.record(Event::Escline(escline));
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.record(Event::Type(annotation));
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.record(Event::Value(value));
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.record(Event::Warning(warning));
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.record(Event::Error(error));
+        Ok(())
+    }
+}
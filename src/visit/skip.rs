@@ -0,0 +1,97 @@
+use crate::{visit, ParseError};
+
+/// A visitor for a skipped subtree, used by adapters such as
+/// [`visit::FilterNodes`] and [`visit::TopLevelOnly`].
+///
+/// Nothing in the subtree is visited, except for errors, which are given to
+/// the visitor that the subtree was skipped for. Child visitors borrow that
+/// visitor in turn.
+#[derive(Debug)]
+pub struct Skip<'a, V> {
+    visitor: Option<&'a mut V>,
+    visit_error: fn(&mut V, ParseError) -> Result<(), ParseError>,
+}
+
+impl<'a, V> Skip<'a, V> {
+    pub(crate) fn new(
+        visitor: Option<&'a mut V>,
+        visit_error: fn(&mut V, ParseError) -> Result<(), ParseError>,
+    ) -> Self {
+        Skip {
+            visitor,
+            visit_error,
+        }
+    }
+
+    fn child(&mut self) -> Skip<'_, V> {
+        Skip {
+            visitor: self.visitor.as_deref_mut(),
+            visit_error: self.visit_error,
+        }
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        match &mut self.visitor {
+            Some(visitor) => (self.visit_error)(visitor, error),
+            None => Err(error),
+        }
+    }
+}
+
+impl<V> visit::LendChildren<'_> for Skip<'_, V> {
+    type VisitNode<'a>
+        = Skip<'a, V>
+    where
+        Self: 'a;
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        self.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Skip::visit_error(self, error)
+    }
+}
+
+impl<V> visit::LendNode<'_> for Skip<'_, V> {
+    type VisitArgument<'a>
+        = Skip<'a, V>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = Skip<'a, V>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = Skip<'a, V>
+    where
+        Self: 'a;
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.child()
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.child()
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        self.child()
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Skip::visit_error(self, error)
+    }
+}
+
+impl<V> visit::Property<'_> for Skip<'_, V> {
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Skip::visit_error(self, error)
+    }
+}
+
+impl<V> visit::Argument<'_> for Skip<'_, V> {
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        Skip::visit_error(self, error)
+    }
+}
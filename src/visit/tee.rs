@@ -0,0 +1,331 @@
+use crate::{lint::Warning, visit, ParseError};
+
+/// A visitor which forwards every call to two visitors.
+///
+/// Errors are given to both visitors, and parsing only continues if both
+/// visitors choose to continue.
+///
+/// Slashdashed components are only visited as components if both visitors opt
+/// in; otherwise, both visit them as trivia. In particular, a visitor which
+/// opts in never sees slashdashed components structurally when teed with one
+/// which does not, as the parser can only visit each component one way.
+///
+/// Trivia is [split](visit::Document::split_trivia) if either visitor opts
+/// in, so the other may see it in smaller pieces.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tee<A, B>(pub A, pub B);
+
+impl<A, B> Tee<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Tee(a, b)
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.0, self.1)
+    }
+}
+
+impl<'kdl, A, B> visit::LendDocument<'kdl> for Tee<A, B>
+where
+    A: visit::LendDocument<'kdl>,
+    B: visit::LendDocument<'kdl>,
+{
+    type Output = (A::Output, B::Output);
+
+    fn finish(self) -> Self::Output {
+        (self.0.finish(), self.1.finish())
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        let a = self.0.finish_error(error);
+        let b = self.1.finish_error(error);
+        Ok((a?, b?))
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.0.split_trivia() || self.1.split_trivia()
+    }
+}
+
+impl<'kdl, A, B> visit::LendChildren<'kdl> for Tee<A, B>
+where
+    A: visit::LendChildren<'kdl>,
+    B: visit::LendChildren<'kdl>,
+{
+    type VisitNode<'a>
+        = Tee<A::VisitNode<'a>, B::VisitNode<'a>>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.0.visit_trivia(trivia);
+        self.1.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.0.visit_comment(comment);
+        self.1.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.0.visit_newline(newline);
+        self.1.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.0.visit_escline(escline);
+        self.1.visit_escline(escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        Tee(self.0.visit_node(), self.1.visit_node())
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        match (self.0.visit_slashdash_node(), self.1.visit_slashdash_node()) {
+            (true, true) => true,
+            (true, false) => {
+                self.0.finish_slashdash_node();
+                false
+            }
+            (false, true) => {
+                self.1.finish_slashdash_node();
+                false
+            }
+            (false, false) => false,
+        }
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.0.finish_slashdash_node();
+        self.1.finish_slashdash_node();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.visit_warning(warning);
+        self.1.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        let a = self.0.visit_error(error);
+        let b = self.1.visit_error(error);
+        a.and(b)
+    }
+}
+
+impl<'kdl, A, B> visit::LendNode<'kdl> for Tee<A, B>
+where
+    A: visit::LendNode<'kdl>,
+    B: visit::LendNode<'kdl>,
+{
+    type VisitArgument<'a>
+        = Tee<A::VisitArgument<'a>, B::VisitArgument<'a>>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = Tee<A::VisitProperty<'a>, B::VisitProperty<'a>>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = Tee<A::VisitChildren<'a>, B::VisitChildren<'a>>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.0.visit_trivia(trivia);
+        self.1.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.0.visit_comment(comment);
+        self.1.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.0.visit_newline(newline);
+        self.1.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.0.visit_escline(escline);
+        self.1.visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.0.visit_type(annotation);
+        self.1.visit_type(annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.0.visit_name(name);
+        self.1.visit_name(name);
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        Tee(self.0.visit_argument(), self.1.visit_argument())
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        Tee(self.0.visit_property(), self.1.visit_property())
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        Tee(self.0.visit_children(), self.1.visit_children())
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        match (
+            self.0.visit_slashdash_entry(),
+            self.1.visit_slashdash_entry(),
+        ) {
+            (true, true) => true,
+            (true, false) => {
+                self.0.finish_slashdash_entry();
+                false
+            }
+            (false, true) => {
+                self.1.finish_slashdash_entry();
+                false
+            }
+            (false, false) => false,
+        }
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.0.finish_slashdash_entry();
+        self.1.finish_slashdash_entry();
+    }
+
+    fn visit_slashdash_children(&mut self) -> bool {
+        match (
+            self.0.visit_slashdash_children(),
+            self.1.visit_slashdash_children(),
+        ) {
+            (true, true) => true,
+            (true, false) => {
+                self.0.finish_slashdash_children();
+                false
+            }
+            (false, true) => {
+                self.1.finish_slashdash_children();
+                false
+            }
+            (false, false) => false,
+        }
+    }
+
+    fn finish_slashdash_children(&mut self) {
+        self.0.finish_slashdash_children();
+        self.1.finish_slashdash_children();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.visit_warning(warning);
+        self.1.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        let a = self.0.visit_error(error);
+        let b = self.1.visit_error(error);
+        a.and(b)
+    }
+}
+
+impl<'kdl, A, B> visit::Property<'kdl> for Tee<A, B>
+where
+    A: visit::Property<'kdl>,
+    B: visit::Property<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.0.visit_trivia(trivia);
+        self.1.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.0.visit_comment(comment);
+        self.1.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.0.visit_newline(newline);
+        self.1.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.0.visit_escline(escline);
+        self.1.visit_escline(escline);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.0.visit_name(name);
+        self.1.visit_name(name);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.0.visit_type(annotation);
+        self.1.visit_type(annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.0.visit_value(value);
+        self.1.visit_value(value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.visit_warning(warning);
+        self.1.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        let a = self.0.visit_error(error);
+        let b = self.1.visit_error(error);
+        a.and(b)
+    }
+}
+
+impl<'kdl, A, B> visit::Argument<'kdl> for Tee<A, B>
+where
+    A: visit::Argument<'kdl>,
+    B: visit::Argument<'kdl>,
+{
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.0.visit_trivia(trivia);
+        self.1.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.0.visit_comment(comment);
+        self.1.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.0.visit_newline(newline);
+        self.1.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.0.visit_escline(escline);
+        self.1.visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.0.visit_type(annotation);
+        self.1.visit_type(annotation);
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.0.visit_value(value);
+        self.1.visit_value(value);
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.0.visit_warning(warning);
+        self.1.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        let a = self.0.visit_error(error);
+        let b = self.1.visit_error(error);
+        a.and(b)
+    }
+}
@@ -1,7 +1,11 @@
 #[cfg(feature = "alloc")]
 use alloc::borrow::Cow;
 use {
-    crate::utils::{unescape, Fmt},
+    crate::{
+        parse::{is_number_literal, is_string_literal},
+        utils::{unescape, Fmt},
+        Span,
+    },
     core::fmt,
 };
 
@@ -29,6 +33,25 @@ pub struct Number<'kdl> {
     pub(crate) source: &'kdl str,
 }
 
+/// A `//` or `/* */` comment, with its span in the source.
+///
+/// Comments inside slashdashed components are visited as plain trivia.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comment<'kdl> {
+    pub(crate) source: &'kdl str,
+    pub(crate) span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentKind {
+    /// A `//` comment, running to the end of the line.
+    Line,
+    /// A `/* */` comment.
+    Block,
+    /// A `/* */` comment containing other `/* */` comments.
+    NestedBlock,
+}
+
 impl<'kdl> Identifier<'kdl> {
     pub fn source(self) -> &'kdl str {
         match self {
@@ -66,6 +89,13 @@ impl<'kdl> Value<'kdl> {
 }
 
 impl<'kdl> String<'kdl> {
+    /// Create a string from its KDL source, including quotes.
+    ///
+    /// Returns `None` if `source` is not a single valid string literal.
+    pub fn from_source(source: &'kdl str) -> Option<Self> {
+        is_string_literal(source).then_some(String { source })
+    }
+
     pub fn source(&self) -> &'kdl str {
         self.source
     }
@@ -98,13 +128,23 @@ impl<'kdl> String<'kdl> {
             if let Some(value) = self.raw_value() {
                 f.write_str(value)
             } else {
-                f.write_fmt(format_args!("{}", unescape(self.source)))
+                f.write_fmt(format_args!(
+                    "{}",
+                    unescape(&self.source[1..self.source.len() - 1])
+                ))
             }
         })
     }
 }
 
 impl<'kdl> Number<'kdl> {
+    /// Create a number from its KDL source.
+    ///
+    /// Returns `None` if `source` is not a single number literal.
+    pub fn from_source(source: &'kdl str) -> Option<Self> {
+        is_number_literal(source).then_some(Number { source })
+    }
+
     pub fn source(&self) -> &'kdl str {
         self.source
     }
@@ -133,6 +173,39 @@ impl<'kdl> Number<'kdl> {
     }
 }
 
+impl<'kdl> Comment<'kdl> {
+    pub fn source(self) -> &'kdl str {
+        self.source
+    }
+
+    pub fn span(self) -> Span {
+        self.span
+    }
+
+    pub fn kind(self) -> CommentKind {
+        if self.source.starts_with("//") {
+            CommentKind::Line
+        } else if self.body().contains("/*") {
+            CommentKind::NestedBlock
+        } else {
+            CommentKind::Block
+        }
+    }
+
+    /// The text of the comment, without the comment delimiters.
+    ///
+    /// A block comment left unclosed at the end of file has no closing `*/`,
+    /// so its body runs to the end of file.
+    pub fn body(self) -> &'kdl str {
+        let body = &self.source[2..];
+        if self.source.starts_with("//") {
+            body
+        } else {
+            body.strip_suffix("*/").unwrap_or(body)
+        }
+    }
+}
+
 #[allow(unreachable_pub)]
 #[cfg(any(feature = "alloc", feature = "lexical"))]
 mod hidden {
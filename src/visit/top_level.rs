@@ -0,0 +1,159 @@
+use crate::{lint::Warning, visit, ParseError};
+
+/// A visitor which only visits top-level nodes.
+///
+/// The children of top-level nodes are skipped: the wrapped node visitor's
+/// `visit_children` is never called, though the braces around the children
+/// are still visited as trivia. Errors in skipped children are still given to
+/// the wrapped node visitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TopLevelOnly<V> {
+    visitor: V,
+}
+
+impl<V> TopLevelOnly<V> {
+    pub fn new(visitor: V) -> Self {
+        TopLevelOnly { visitor }
+    }
+
+    pub fn into_inner(self) -> V {
+        self.visitor
+    }
+}
+
+impl<'kdl, V> visit::LendDocument<'kdl> for TopLevelOnly<V>
+where
+    V: visit::LendDocument<'kdl>,
+{
+    type Output = V::Output;
+
+    fn finish(self) -> Self::Output {
+        self.visitor.finish()
+    }
+
+    fn finish_error(self, error: ParseError) -> Result<Self::Output, ParseError> {
+        self.visitor.finish_error(error)
+    }
+
+    fn split_trivia(&self) -> bool {
+        self.visitor.split_trivia()
+    }
+}
+
+impl<'kdl, V> visit::LendChildren<'kdl> for TopLevelOnly<V>
+where
+    V: visit::LendChildren<'kdl>,
+{
+    type VisitNode<'a>
+        = TopLevelOnly<V::VisitNode<'a>>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode<'_> {
+        TopLevelOnly::new(self.visitor.visit_node())
+    }
+
+    fn visit_slashdash_node(&mut self) -> bool {
+        self.visitor.visit_slashdash_node()
+    }
+
+    fn finish_slashdash_node(&mut self) {
+        self.visitor.finish_slashdash_node();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
+
+impl<'kdl, V> visit::LendNode<'kdl> for TopLevelOnly<V>
+where
+    V: visit::LendNode<'kdl>,
+{
+    type VisitArgument<'a>
+        = V::VisitArgument<'a>
+    where
+        Self: 'a;
+    type VisitProperty<'a>
+        = V::VisitProperty<'a>
+    where
+        Self: 'a;
+    type VisitChildren<'a>
+        = visit::Skip<'a, V>
+    where
+        Self: 'a;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visitor.visit_trivia(trivia);
+    }
+
+    fn visit_comment(&mut self, comment: visit::Comment<'kdl>) {
+        self.visitor.visit_comment(comment);
+    }
+
+    fn visit_newline(&mut self, newline: &'kdl str) {
+        self.visitor.visit_newline(newline);
+    }
+
+    fn visit_escline(&mut self, escline: &'kdl str) {
+        self.visitor.visit_escline(escline);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visitor.visit_type(annotation);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.visitor.visit_name(name);
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument<'_> {
+        self.visitor.visit_argument()
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty<'_> {
+        self.visitor.visit_property()
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren<'_> {
+        visit::Skip::new(Some(&mut self.visitor), |visitor, error| {
+            visitor.visit_error(error)
+        })
+    }
+
+    fn visit_slashdash_entry(&mut self) -> bool {
+        self.visitor.visit_slashdash_entry()
+    }
+
+    fn finish_slashdash_entry(&mut self) {
+        self.visitor.finish_slashdash_entry();
+    }
+
+    fn visit_warning(&mut self, warning: Warning) {
+        self.visitor.visit_warning(warning);
+    }
+
+    fn visit_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        self.visitor.visit_error(error)
+    }
+}
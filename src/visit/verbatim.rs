@@ -0,0 +1,277 @@
+use crate::{visit, Span};
+
+/// A visitor which collects the exact source text of whatever it visits.
+///
+/// This can be used to keep the source of a node around until it's known
+/// whether it is needed, e.g. to copy unknown nodes verbatim:
+///
+/// ```
+/// # use kdl_visit::{visit, visit_kdl_string};
+/// use visit::Node as _;
+///
+/// struct Unknown<'kdl> {
+///     source: &'kdl str,
+///     unknown: Vec<&'kdl str>,
+/// }
+///
+/// impl<'kdl> visit::Document<'kdl> for Unknown<'kdl> {
+///     type Output = Vec<&'kdl str>;
+///     fn finish(self) -> Self::Output {
+///         self.unknown
+///     }
+/// }
+///
+/// impl<'kdl> visit::Children<'kdl> for Unknown<'kdl> {
+///     type VisitNode = Node<'kdl>;
+///     fn visit_node(&mut self) -> Self::VisitNode {
+///         Node(false, visit::Verbatim::new(self.source))
+///     }
+///     fn finish_node(&mut self, node: Self::VisitNode) {
+///         if !node.0 {
+///             self.unknown.push(node.1.as_str());
+///         }
+///     }
+/// }
+///
+/// struct Node<'kdl>(bool, visit::Verbatim<'kdl>);
+///
+/// impl<'kdl> visit::Node<'kdl> for Node<'kdl> {
+///     type VisitArgument = visit::Verbatim<'kdl>;
+///     type VisitProperty = visit::Verbatim<'kdl>;
+///     type VisitChildren = visit::Verbatim<'kdl>;
+///     fn visit_trivia(&mut self, trivia: &'kdl str) {
+///         self.1.visit_trivia(trivia);
+///     }
+///     fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+///         self.1.visit_type(annotation);
+///     }
+///     fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+///         self.0 = name.source() == "known";
+///         self.1.visit_name(name);
+///     }
+///     fn visit_argument(&mut self) -> Self::VisitArgument {
+///         self.1.visit_argument()
+///     }
+///     fn finish_argument(&mut self, argument: Self::VisitArgument) {
+///         self.1.finish_argument(argument);
+///     }
+///     fn visit_property(&mut self) -> Self::VisitProperty {
+///         self.1.visit_property()
+///     }
+///     fn finish_property(&mut self, property: Self::VisitProperty) {
+///         self.1.finish_property(property);
+///     }
+///     fn visit_children(&mut self) -> Self::VisitChildren {
+///         self.1.visit_children()
+///     }
+///     fn finish_children(&mut self, children: Self::VisitChildren) {
+///         self.1.finish_children(children);
+///     }
+/// }
+///
+/// let kdl = "known 1\nunknown 2 { child; }\nknown 3\n";
+/// let unknown = Unknown { source: kdl, unknown: vec![] };
+/// assert_eq!(visit_kdl_string(kdl, unknown)?, ["unknown 2 { child; }"]);
+/// # Ok::<(), kdl_visit::ParseError>(())
+/// ```
+///
+/// Unlike the other adapters, this is an owned visitor, lent out as an
+/// [`OwnedChild`](visit::OwnedChild) by lending visitors: each child collects
+/// its own text, and gives it back to be joined onto its parent's, so that
+/// the text of a single node can be taken before it's given back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verbatim<'kdl> {
+    source: &'kdl str,
+    span: Option<Span>,
+    /// Text visited before `span` was located in `source`: only the keywords
+    /// `true`, `false`, and `null`, which are not visited as slices of the
+    /// source. `unlocated` holds the last of them, `unlocated_len` all of them.
+    unlocated: &'kdl str,
+    unlocated_len: usize,
+}
+
+impl<'kdl> Verbatim<'kdl> {
+    /// Create a visitor for the given source text, which must be the same
+    /// string given to the parser.
+    pub fn new(source: &'kdl str) -> Self {
+        Verbatim {
+            source,
+            span: None,
+            unlocated: "",
+            unlocated_len: 0,
+        }
+    }
+
+    /// The span of the visited source text, if it could be located.
+    ///
+    /// Only a visitor created with [`new`](Verbatim::new) which has visited
+    /// nothing but a keyword value cannot be located.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// The visited source text.
+    pub fn as_str(&self) -> &'kdl str {
+        match self.span {
+            Some(span) => &self.source[span.start..span.end],
+            None => self.unlocated,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self.span {
+            Some(span) => span.end - span.start,
+            None => self.unlocated_len,
+        }
+    }
+
+    /// Find `text`, which the parser borrowed from `source`, in `source`.
+    fn locate(&self, text: &'kdl str) -> Option<usize> {
+        let start = (text.as_ptr() as usize).checked_sub(self.source.as_ptr() as usize)?;
+        let end = start.checked_add(text.len())?;
+        (end <= self.source.len()).then_some(start)
+    }
+
+    /// Visit text borrowed from the source.
+    fn visit(&mut self, text: &'kdl str) {
+        if let Some(span) = &mut self.span {
+            span.end += text.len();
+        } else if let Some(start) = self.locate(text) {
+            self.span = Some((start - self.unlocated_len..start + text.len()).into());
+        } else {
+            self.unlocated = text;
+            self.unlocated_len += text.len();
+        }
+    }
+
+    fn visit_literal(&mut self, value: visit::Value<'kdl>) {
+        match value {
+            visit::Value::String(_) | visit::Value::Number(_) => self.visit(value.source()),
+            // keywords are static strings, so must not be located by address
+            visit::Value::Boolean(_) | visit::Value::Null => match &mut self.span {
+                Some(span) => span.end += value.source().len(),
+                None => {
+                    self.unlocated = value.source();
+                    self.unlocated_len += value.source().len();
+                }
+            },
+        }
+    }
+
+    fn join(&mut self, child: Self) {
+        if let Some(span) = &mut self.span {
+            span.end += child.len();
+        } else if let Some(span) = child.span {
+            self.span = Some((span.start - self.unlocated_len..span.end).into());
+        } else {
+            self.unlocated = child.unlocated;
+            self.unlocated_len += child.unlocated_len;
+        }
+    }
+
+    /// A visitor for a child component, which starts where this one ends.
+    fn child(&self) -> Self {
+        Verbatim {
+            span: self.span.map(|span| (span.end..span.end).into()),
+            ..Verbatim::new(self.source)
+        }
+    }
+}
+
+impl<'kdl> visit::Document<'kdl> for Verbatim<'kdl> {
+    type Output = &'kdl str;
+
+    fn finish(self) -> Self::Output {
+        self.as_str()
+    }
+}
+
+impl<'kdl> visit::Children<'kdl> for Verbatim<'kdl> {
+    type VisitNode = Self;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visit(trivia);
+    }
+
+    fn visit_node(&mut self) -> Self::VisitNode {
+        self.child()
+    }
+
+    fn finish_node(&mut self, node: Self::VisitNode) {
+        self.join(node);
+    }
+}
+
+impl<'kdl> visit::Node<'kdl> for Verbatim<'kdl> {
+    type VisitArgument = Self;
+    type VisitProperty = Self;
+    type VisitChildren = Self;
+
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visit(trivia);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visit(annotation.source());
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.visit(name.source());
+    }
+
+    fn visit_argument(&mut self) -> Self::VisitArgument {
+        self.child()
+    }
+
+    fn finish_argument(&mut self, argument: Self::VisitArgument) {
+        self.join(argument);
+    }
+
+    fn visit_property(&mut self) -> Self::VisitProperty {
+        self.child()
+    }
+
+    fn finish_property(&mut self, property: Self::VisitProperty) {
+        self.join(property);
+    }
+
+    fn visit_children(&mut self) -> Self::VisitChildren {
+        self.child()
+    }
+
+    fn finish_children(&mut self, children: Self::VisitChildren) {
+        self.join(children);
+    }
+}
+
+impl<'kdl> visit::Property<'kdl> for Verbatim<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visit(trivia);
+    }
+
+    fn visit_name(&mut self, name: visit::Identifier<'kdl>) {
+        self.visit(name.source());
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visit(annotation.source());
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.visit_literal(value);
+    }
+}
+
+impl<'kdl> visit::Argument<'kdl> for Verbatim<'kdl> {
+    fn visit_trivia(&mut self, trivia: &'kdl str) {
+        self.visit(trivia);
+    }
+
+    fn visit_type(&mut self, annotation: visit::Identifier<'kdl>) {
+        self.visit(annotation.source());
+    }
+
+    fn visit_value(&mut self, value: visit::Value<'kdl>) {
+        self.visit_literal(value);
+    }
+}
@@ -0,0 +1,384 @@
+#![cfg(feature = "ast")]
+
+use kdl_visit::ast::Document;
+
+#[test]
+fn property_last_wins() {
+    let doc = Document::from_str(r#"node 1 a=1 "two" b=2 a=3 "a"=4"#).unwrap();
+    let node = doc.nodes().next().unwrap();
+
+    let a = node.property("a").unwrap();
+    assert_eq!(a.value().as_f64(), Some(4.0));
+    assert_eq!(node.property("b").unwrap().value().as_f64(), Some(2.0));
+    assert!(node.property("c").is_none());
+
+    assert_eq!(node.argument(0).unwrap().value().as_f64(), Some(1.0));
+    assert_eq!(node.argument(1).unwrap().value().as_str(), Some("two"));
+    assert!(node.argument(2).is_none());
+
+    let map = node.property_map();
+    let keys: Vec<&str> = map.iter().map(|p| &**p.name()).collect();
+    assert_eq!(keys, ["b", "a"]);
+    assert!(std::ptr::eq(map.get("a").unwrap(), a));
+
+    let dupes: Vec<f64> = node
+        .duplicate_properties()
+        .map(|p| p.value().as_f64().unwrap())
+        .collect();
+    assert_eq!(dupes, [1.0, 3.0]);
+}
+
+#[test]
+fn escaped_string_value() {
+    let doc = Document::from_str(r#"node "tab\there" r"raw\t""#).unwrap();
+    let node = doc.nodes().next().unwrap();
+    assert_eq!(
+        node.argument(0).unwrap().value().as_str(),
+        Some("tab\there")
+    );
+    assert_eq!(node.argument(1).unwrap().value().as_str(), Some("raw\\t"));
+}
+
+#[test]
+fn child_lookup() {
+    let doc = Document::from_str(
+        r#"
+        server {
+            (tcp)listen 80
+            "database" url="sqlite://"
+            listen 443
+        }
+        database url="postgres://"
+        "#,
+    )
+    .unwrap();
+    let server = doc.node("server").unwrap();
+
+    let database = server.child("database").unwrap();
+    assert_eq!(
+        database.property("url").unwrap().value().as_str(),
+        Some("sqlite://")
+    );
+    assert!(server.child("missing").is_none());
+
+    let ports: Vec<f64> = server
+        .children_named("listen")
+        .map(|node| node.argument(0).unwrap().value().as_f64().unwrap())
+        .collect();
+    assert_eq!(ports, [80.0, 443.0]);
+
+    let index = doc.index();
+    let databases: Vec<&str> = index
+        .nodes_named("database")
+        .iter()
+        .map(|node| node.property("url").unwrap().value().as_str().unwrap())
+        .collect();
+    assert_eq!(databases, ["sqlite://", "postgres://"]);
+    assert!(index.nodes_named("missing").is_empty());
+    assert!(index.nodes_typed("tcp").is_none());
+
+    let index = kdl_visit::ast::DocumentIndex::with_types(&doc);
+    assert_eq!(index.nodes_typed("tcp").unwrap().len(), 1);
+    assert!(index.nodes_typed("udp").unwrap().is_empty());
+}
+
+#[test]
+fn owned_documents() {
+    use kdl_visit::ast::OwnedDocument;
+    use std::{sync::Arc, thread};
+
+    let source = String::from(r#"node "borrowed" key="esc\"aped""#);
+    let doc: Document<'static> = Document::from_str(&source).unwrap().into_owned();
+    drop(source);
+    let node = doc.node("node").unwrap();
+    assert_eq!(node.argument(0).unwrap().value().as_str(), Some("borrowed"));
+    assert_eq!(
+        node.property("key").unwrap().value().as_str(),
+        Some("esc\"aped")
+    );
+
+    let parsed: Document<'static> = "a; b; c".parse().unwrap();
+    assert_eq!(parsed.nodes().count(), 3);
+    assert!("a=".parse::<Document<'static>>().is_err());
+
+    let shared = Arc::new(OwnedDocument::from_source("server port=8080").unwrap());
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                let port = shared.document().node("server").unwrap().property("port");
+                port.unwrap().value().as_f64().unwrap()
+            })
+        })
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), 8080.0);
+    }
+
+    let errors = OwnedDocument::from_source("- bare").unwrap_err();
+    assert_eq!(&*errors.source, "- bare");
+    assert_eq!(errors.errors.len(), 1);
+}
+
+fn dump<'a, 'kdl: 'a>(nodes: impl Iterator<Item = &'a kdl_visit::ast::Node<'kdl>>) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        if let Some(ty) = node.ty() {
+            out += &format!("({})", &**ty);
+        }
+        out += node.name();
+        for attr in node.attrs() {
+            out += " ";
+            if let Some(name) = attr.name() {
+                out += &format!("{}=", &**name);
+            }
+            if let Some(ty) = attr.ty() {
+                out += &format!("({})", &**ty);
+            }
+            let value = attr.value();
+            match (value.as_str(), value.as_f64(), value.as_bool()) {
+                (Some(s), _, _) => out += &format!("{s:?}"),
+                (_, Some(n), _) => out += &format!("{n}"),
+                (_, _, Some(b)) => out += &format!("{b}"),
+                _ => out += "null",
+            }
+        }
+        if node.children().next().is_some() {
+            out += &format!(" {{ {}}}", dump(node.children()));
+        }
+        out += "; ";
+    }
+    out
+}
+
+#[test]
+fn build_and_mutate() {
+    let mut doc = Document::new();
+    {
+        let mut server = doc.push_node("server");
+        server.push_argument("main").set_property("port", 80);
+        server.push_child("listen").push_argument(true);
+        server.push_child("tls").set_property("cert", ());
+    }
+    doc.push_node("client").set_type("web");
+    assert_eq!(
+        dump(doc.nodes()),
+        r#"server "main" port=80 { listen true; tls cert=null; }; (web)client; "#,
+    );
+
+    {
+        let mut nodes = doc.nodes_mut();
+        let mut server = nodes.get_mut(0).unwrap();
+        server.set_property("port", 443).push_argument(1.5);
+        assert!(server.remove_attr(0));
+        assert!(!server.remove_attr(5));
+        let mut children = server.children_mut();
+        children.insert(0, "log").push_argument("debug");
+        children.insert(2, "cache");
+        assert!(children.remove(3));
+        assert!(!children.remove(3));
+        children
+            .push("tail")
+            .push_argument(0)
+            .set_attr_type(0, Some("x"));
+    }
+    doc.nodes_mut().insert(1, "middle");
+    assert_eq!(
+        dump(doc.nodes()),
+        r#"server port=443 1.5 { log "debug"; listen true; cache; tail (x)0; }; middle; (web)client; "#,
+    );
+    assert_eq!(
+        dump(doc.nodes().rev()),
+        r#"(web)client; middle; server port=443 1.5 { log "debug"; listen true; cache; tail (x)0; }; "#,
+    );
+    let server = doc.node("server").unwrap();
+    assert_eq!(
+        dump(server.children().rev()),
+        r#"tail (x)0; cache; listen true; log "debug"; "#
+    );
+
+    doc.nodes_mut().remove(0);
+    doc.nodes_mut().remove(1);
+    assert_eq!(dump(doc.nodes()), "middle; ");
+    doc.nodes_mut().remove(0);
+    assert_eq!(dump(doc.nodes()), "");
+}
+
+#[test]
+fn mutate_nested() {
+    let mut doc = Document::from_str(
+        "a { (t)b { c 1; d; }
+ e; }
+ f { g; }",
+    )
+    .unwrap();
+    {
+        let mut nodes = doc.nodes_mut();
+        let mut a = nodes.get_mut(0).unwrap();
+        let mut children = a.children_mut();
+        let mut b = children.get_mut(0).unwrap();
+        b.clear_type().push_argument(2);
+        let mut grandchildren = b.children_mut();
+        grandchildren.get_mut(0).unwrap().push_argument(3);
+        grandchildren.get_mut(1).unwrap().push_child("h");
+        grandchildren.insert(1, "i");
+        assert!(grandchildren.remove(0));
+    }
+    assert_eq!(
+        dump(doc.nodes()),
+        "a { b 2 { i; d { h; }; }; e; }; f { g; }; ",
+    );
+    assert_eq!(
+        dump(doc.nodes().rev()),
+        "f { g; }; a { b 2 { i; d { h; }; }; e; }; ",
+    );
+    doc.nodes_mut().get_mut(0).unwrap().children_mut().remove(0);
+    assert_eq!(dump(doc.nodes()), "a { e; }; f { g; }; ");
+}
+
+#[test]
+fn replay_non_finite_as_null() {
+    let doc = Document::from_str(r#"node 1e999 "1x"=-1e999 1.5"#).unwrap();
+    let arguments: Vec<_> = doc
+        .nodes()
+        .next()
+        .unwrap()
+        .attrs()
+        .map(|a| a.value().as_f64())
+        .collect();
+    assert_eq!(
+        arguments,
+        [Some(f64::INFINITY), Some(f64::NEG_INFINITY), Some(1.5)]
+    );
+
+    let replay = doc.replay();
+    assert_eq!(replay.source(), "node null \"1x\"=null 1.5\n");
+    let reparsed = Document::from_str(replay.source()).unwrap();
+    assert_eq!(dump(reparsed.nodes()), "node null 1x=null 1.5; ");
+}
+
+#[test]
+fn replay_quotes_identifiers() {
+    let mut doc = Document::new();
+    doc.push_node("true")
+        .set_type("a b")
+        .push_argument("line\nbreak")
+        .set_property("", 1)
+        .set_property("1x", f64::INFINITY)
+        .push_child("r#raw")
+        .push_argument(0.25);
+    doc.push_node("ünïcödé").set_property("-", false);
+
+    let replay = doc.replay();
+    assert_eq!(
+        replay.source(),
+        concat!(
+            r#"("a b")"true" "line\nbreak" ""=1 "1x"=null {"#,
+            "\n",
+            r#"    "r#raw" 0.25"#,
+            "\n}\nünïcödé -=false\n",
+        ),
+    );
+    let reparsed = Document::from_str(replay.source()).unwrap();
+    assert_eq!(
+        dump(reparsed.nodes()),
+        r#"(a b)true "line\nbreak" =1 1x=null { r#raw 0.25; }; ünïcödé -=false; "#,
+    );
+
+    struct CountNodes(usize);
+    impl<'kdl> kdl_visit::visit::Document<'kdl> for CountNodes {
+        type Output = usize;
+        fn finish(self) -> usize {
+            self.0
+        }
+    }
+    impl<'kdl> kdl_visit::visit::Children<'kdl> for CountNodes {
+        type VisitNode = ();
+        fn visit_node(&mut self) {
+            self.0 += 1;
+        }
+    }
+    assert_eq!(doc.accept(CountNodes(0)), 2);
+}
+
+#[test]
+fn disabled_entries() {
+    use std::ops::Range;
+
+    let kdl = "/-old 1\nserver /-port=80 port=443 /-\"debug\" {\n    /-(v1)listen 80\n    listen 443\n}\n";
+    assert_eq!(
+        dump(Document::from_str(kdl).unwrap().nodes()),
+        "server port=443 { listen 443; }; ",
+    );
+
+    let mut doc = Document::from_str_with_disabled(kdl).unwrap();
+    assert_eq!(dump(doc.nodes()), "server port=443 { listen 443; }; ");
+    assert_eq!(doc.nodes_with_disabled().count(), 2);
+    assert!(doc.nodes_with_disabled().next().unwrap().is_disabled());
+    assert_eq!(doc.index().nodes_named("listen").len(), 1);
+    assert!(doc.node("old").is_none());
+
+    let server = doc.node("server").unwrap();
+    assert_eq!(
+        server.property("port").unwrap().value().as_f64(),
+        Some(443.0)
+    );
+    assert_eq!(server.duplicate_properties().count(), 0);
+    let disabled: Vec<_> = (server.attrs_with_disabled())
+        .filter(|attr| attr.is_disabled())
+        .map(|attr| &kdl[Range::from(attr.span())])
+        .collect();
+    assert_eq!(disabled, ["port=80", "\"debug\""]);
+    let listen = server.children_with_disabled().next().unwrap();
+    assert!(listen.is_disabled());
+    assert_eq!(&kdl[Range::from(listen.span())], "(v1)listen 80\n");
+
+    {
+        let mut nodes = doc.nodes_mut();
+        nodes.get_mut(0).unwrap().set_disabled(false);
+        let mut server = nodes.get_mut(1).unwrap();
+        server
+            .set_attr_disabled(0, false)
+            .set_property("port", 8080);
+        server
+            .children_mut()
+            .get_mut(0)
+            .unwrap()
+            .set_disabled(false);
+    }
+    assert_eq!(
+        dump(doc.nodes()),
+        "old 1; server port=80 port=8080 { (v1)listen 80; listen 443; }; ",
+    );
+    assert_eq!(
+        doc.replay().source(),
+        "old 1\nserver port=80 port=8080 {\n    (v1)listen 80\n    listen 443\n}\n",
+    );
+}
+
+#[test]
+fn attached_comments() {
+    use kdl_visit::visit::CommentKind;
+
+    let source =
+        String::from("/// The server.\n/* (v2) */\nserver { // main\n    port 80 // http\n}\n");
+    let doc: Document<'static> = Document::from_str(&source).unwrap().into_owned();
+    drop(source);
+    let server = doc.node("server").unwrap();
+    let leading: Vec<_> = server
+        .leading_comments()
+        .iter()
+        .map(|comment| (comment.kind(), comment.body()))
+        .collect();
+    assert_eq!(
+        leading,
+        [
+            (CommentKind::Line, "/ The server."),
+            (CommentKind::Block, " (v2) ")
+        ],
+    );
+    assert_eq!(server.trailing_comment().map(|c| c.source()), None);
+    let port = server.child("port").unwrap();
+    assert!(port.leading_comments().is_empty());
+    assert_eq!(port.trailing_comment().unwrap().source(), "// http");
+}
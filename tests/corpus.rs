@@ -1,5 +1,5 @@
 use {
-    kdl_visit::{visit, visit_kdl_string, ParseError},
+    kdl_visit::{visit, visit_kdl_string, visit_kdl_string_dyn, ParseError},
     std::{cell::RefCell, fmt::Write},
     tracing_subscriber::prelude::*,
 };
@@ -23,6 +23,38 @@ fn run_sexpr_tests() {
     }));
 }
 
+#[test]
+fn run_dyn_tests() {
+    insta::glob!("corpus/*.kdl", |path| with_setup(|| {
+        let input = std::fs::read_to_string(path).unwrap();
+        let input = input.replace("\r\n", "\n");
+        let dump = RefCell::new(String::new());
+        visit_kdl_string(&input, BuildSExpr::new(&dump)).ok();
+        let dyn_dump = RefCell::new(String::new());
+        let mut builder = BuildSExpr::new(&dyn_dump);
+        match visit_kdl_string_dyn(&input, &mut builder) {
+            Ok(()) => visit::Document::finish(builder),
+            Err(error) => visit::Document::finish_error(builder, error).unwrap_or(()),
+        };
+        assert_eq!(dump.into_inner(), dyn_dump.into_inner());
+    }));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn run_recorder_tests() {
+    insta::glob!("corpus/*.kdl", |path| with_setup(|| {
+        let input = std::fs::read_to_string(path).unwrap();
+        let input = input.replace("\r\n", "\n");
+        let dump = RefCell::new(String::new());
+        visit_kdl_string(&input, BuildSExpr::new(&dump)).ok();
+        let recorder = visit_kdl_string(&input, visit::Recorder::new()).unwrap();
+        let replay_dump = RefCell::new(String::new());
+        recorder.replay_document(BuildSExpr::new(&replay_dump)).ok();
+        assert_eq!(dump.into_inner(), replay_dump.into_inner());
+    }));
+}
+
 #[cfg(feature = "ast")]
 #[cfg(feature = "miette")]
 fn render_diagnostic(diagnostic: &dyn miette::Diagnostic) -> String {
@@ -70,6 +102,120 @@ fn run_ast_tests() {
     }));
 }
 
+#[test]
+#[cfg(feature = "ast")]
+fn run_plain_tests() {
+    insta::glob!("corpus/*.kdl", |path| with_setup(|| {
+        let input = std::fs::read_to_string(path).unwrap();
+        let input = input.replace("\r\n", "\n");
+        if let Err(errors) = kdl_visit::ast::Document::from_str(&input) {
+            let file_name = path.file_name().unwrap().to_string_lossy();
+            let report = errors.display_plain(&file_name).to_string();
+            insta::assert_snapshot!("plain", report, &input);
+
+            let json = errors.display_json_lines(&file_name).to_string();
+            for line in json.lines() {
+                serde_json::from_str::<serde_json::Value>(line).unwrap();
+            }
+            insta::assert_snapshot!("json", json, &input);
+
+            // the plain report should say everything the miette report does
+            let stem = path.file_stem().unwrap().to_string_lossy();
+            let example = path
+                .parent()
+                .unwrap()
+                .join(format!("../examples/{stem}.stderr"));
+            if let Ok(example) = std::fs::read_to_string(example) {
+                let expected = example.lines().filter_map(|line| {
+                    let line = line.trim_start();
+                    None.or_else(|| line.strip_prefix("Error: ")?.split(' ').next())
+                        .or_else(|| {
+                            line.strip_prefix("× ")
+                                .filter(|&line| line != "errors occured while parsing")
+                        })
+                        .or_else(|| line.strip_prefix("help: "))
+                        .or_else(|| Some(line.split_once("╰── ")?.1))
+                });
+                for expected in expected {
+                    assert!(
+                        report.contains(expected),
+                        "{expected:?} missing from:\n{report}"
+                    );
+                }
+            }
+        };
+    }));
+}
+
+#[test]
+#[cfg(feature = "ast")]
+fn run_replay_tests() {
+    insta::glob!("corpus/*.kdl", |path| with_setup(|| {
+        let input = std::fs::read_to_string(path).unwrap();
+        let input = input.replace("\r\n", "\n");
+        if let Ok(doc) = kdl_visit::ast::Document::from_str(&input) {
+            let replay = doc.replay();
+
+            // replaying should be indistinguishable from parsing the replay source
+            let replayed = RefCell::new(String::new());
+            replay.accept(BuildSExpr::new(&replayed));
+            let reparsed = RefCell::new(String::new());
+            visit_kdl_string(replay.source(), BuildSExpr::new(&reparsed)).unwrap();
+            assert_eq!(replayed.into_inner(), reparsed.into_inner());
+
+            // and collecting the replay should reproduce the same document
+            let recollected = kdl_visit::ast::Document::from_str(replay.source()).unwrap();
+            assert_eq!(recollected.replay().source(), replay.source());
+
+            insta::assert_snapshot!("replay", replay.source(), &input);
+        };
+    }));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn run_fix_tests() {
+    insta::glob!("corpus/*.kdl", |path| with_setup(|| {
+        let input = std::fs::read_to_string(path).unwrap();
+        let input = input.replace("\r\n", "\n");
+        if let Ok(errors) = kdl_visit::ParseErrors::from_str(&input) {
+            let fixed = kdl_visit::apply_fixes(&input, &errors);
+            insta::assert_snapshot!("fixed", fixed, &input);
+
+            // fixes must not introduce errors, so at most the errors without
+            // a machine applicable fix are left
+            let unfixable = (errors.errors.iter())
+                .filter(|error| {
+                    let mut suggestions = error.suggestions().peekable();
+                    suggestions.peek().is_none()
+                        || suggestions.any(|suggestion| {
+                            suggestion.applicability != kdl_visit::Applicability::MachineApplicable
+                        })
+                })
+                .count();
+            let left = kdl_visit::ParseErrors::from_str(&fixed).map_or(0, |e| e.errors.len());
+            assert!(left <= unfixable, "{fixed}");
+        }
+    }));
+}
+
+#[test]
+#[cfg(feature = "ast")]
+#[cfg(feature = "miette")]
+fn run_lint_tests() {
+    insta::glob!("corpus/*.kdl", |path| with_setup(|| {
+        let input = std::fs::read_to_string(path).unwrap();
+        let input = input.replace("\r\n", "\n");
+        let mut rules = kdl_visit::lint::Rules::new();
+        rules.set_all(kdl_visit::lint::Level::Warn);
+        let warnings = kdl_visit::lint::Warnings::collect(&input, rules);
+        if !warnings.warnings.is_empty() {
+            let report = render_diagnostic(&warnings);
+            insta::assert_snapshot!("lint", report, &input);
+        }
+    }));
+}
+
 #[derive(Clone, Copy)]
 struct BuildSExpr<'a> {
     dump: &'a RefCell<String>,